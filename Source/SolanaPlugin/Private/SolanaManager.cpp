@@ -5,6 +5,13 @@
 #include "Misc/Paths.h"
 #include "Misc/FileHelper.h"
 
+// Returns the SDK's error message for the last failed call on this thread, or Fallback if none was recorded
+static FString GetLastSolanaError(const FString &Fallback)
+{
+    const char *Message = sol_last_error_message();
+    return Message ? FString(UTF8_TO_TCHAR(Message)) : Fallback;
+}
+
 // Constructor: Initialize Solana Client
 USolanaManager::USolanaManager()
 {
@@ -52,7 +59,7 @@ void USolanaManager::ConnectToSolana(const FString &RpcUrl)
     }
     else
     {
-        UE_LOG(LogTemp, Error, TEXT("Failed to connect to Solana RPC at: %s"), *RpcUrl);
+        UE_LOG(LogTemp, Error, TEXT("Failed to connect to Solana RPC at: %s (%s)"), *RpcUrl, *GetLastSolanaError(TEXT("unknown error")));
     }
 }
 
@@ -104,9 +111,9 @@ void USolanaManager::GetBalance(const FSolanaPublicKey &PublicKey, FString &Bala
 
     uint64_t Bal = get_balance(SolanaClient, &SolPubKey);
 
-    if (Bal == UINT64_MAX) // Assuming the SDK uses UINT64_MAX for errors
+    if (sol_last_error_code() != SolErrorCode_Ok)
     {
-        ErrorMessage = TEXT("Failed to retrieve balance. Ensure the public key is correct or try again.");
+        ErrorMessage = GetLastSolanaError(TEXT("Failed to retrieve balance. Ensure the public key is correct or try again."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
     }
     else
@@ -259,7 +266,7 @@ FSolanaKeyPair USolanaManager::CreateAndSaveWallet(const FString &FilePath, FStr
     }
     else
     {
-        ErrorMessage = FString::Printf(TEXT("Failed to create and save wallet to: %s"), *FinalFilePath);
+        ErrorMessage = GetLastSolanaError(FString::Printf(TEXT("Failed to create and save wallet to: %s"), *FinalFilePath));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
     }
 
@@ -293,7 +300,7 @@ FSolanaKeyPair USolanaManager::LoadWalletFromFile(const FString &FilePath, FStri
     // Null pointer check to prevent crash
     if (KeyPair == nullptr)
     {
-        ErrorMessage = FString::Printf(TEXT("Failed to load wallet from file (null pointer): %s"), *FinalFilePath);
+        ErrorMessage = GetLastSolanaError(FString::Printf(TEXT("Failed to load wallet from file: %s"), *FinalFilePath));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return Wallet;
     }
@@ -359,7 +366,7 @@ bool USolanaManager::RequestFaucet(const FSolanaPublicKey &PublicKey, int64 Amou
     }
     else
     {
-        ErrorMessage = TEXT("Faucet request failed. Possible causes: insufficient funds on the test network or invalid public key.");
+        ErrorMessage = GetLastSolanaError(TEXT("Faucet request failed."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return false;
     }
@@ -398,7 +405,7 @@ bool USolanaManager::CreateSPLToken(const FSolanaKeyPair &PayerKeyPair, const FS
     }
     else
     {
-        ErrorMessage = TEXT("Failed to create SPL token.");
+        ErrorMessage = GetLastSolanaError(TEXT("Failed to create SPL token."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return false;
     }
@@ -422,7 +429,7 @@ bool USolanaManager::GetMintInfo(const FSolanaPublicKey &MintPublicKey, FString
 
     if (!MintInfo)
     {
-        ErrorMessage = TEXT("Failed to retrieve mint information.");
+        ErrorMessage = GetLastSolanaError(TEXT("Failed to retrieve mint information."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return false;
     }
@@ -510,7 +517,7 @@ bool USolanaManager::MintSPL(const FSolanaKeyPair &PayerKeyPair, const FSolanaKe
     }
     else
     {
-        ErrorMessage = TEXT("Failed to mint SPL tokens.");
+        ErrorMessage = GetLastSolanaError(TEXT("Failed to mint SPL tokens."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return false;
     }
@@ -535,9 +542,9 @@ bool USolanaManager::GetAssociatedTokenBalance(const FSolanaPublicKey &OwnerPubl
     // Call the SDK function to get the associated token balance
     uint64_t Result = get_associated_token_balance(SolanaClient, &SolOwnerPubKey, &SolMintPubKey);
 
-    if (Result == UINT64_MAX) // Assuming `UINT64_MAX` indicates an error, based on the SDK's behavior
+    if (sol_last_error_code() != SolErrorCode_Ok)
     {
-        ErrorMessage = TEXT("Failed to retrieve the associated token balance.");
+        ErrorMessage = GetLastSolanaError(TEXT("Failed to retrieve the associated token balance."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return false;
     }
@@ -622,7 +629,7 @@ FString USolanaManager::TransferSPL(const FSolanaKeyPair &SenderKeyPair, const F
     else
     {
         // Failure
-        ErrorMessage = TEXT("Failed to transfer SPL tokens. Check Unreal logs for potential Rust error messages."); // Rust side likely printed specifics
+        ErrorMessage = GetLastSolanaError(TEXT("Failed to transfer SPL tokens."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return FString(); // Return empty string indicates failure
     }
@@ -673,7 +680,7 @@ FString USolanaManager::GetTransactionDetailsAsJson(const FString& TransactionSi
     else
     {
         // Failure
-        ErrorMessage = FString::Printf(TEXT("Failed to fetch transaction details for signature: %s. Check logs for Rust errors."), *TransactionSignature);
+        ErrorMessage = GetLastSolanaError(FString::Printf(TEXT("Failed to fetch transaction details for signature: %s."), *TransactionSignature));
         UE_LOG(LogTemp, Error, TEXT("[GetTransactionDetailsAsJson] %s"), *ErrorMessage);
         return FString();
     }
@@ -748,7 +755,7 @@ FString USolanaManager::TransferSOL(const FSolanaKeyPair &SenderKeyPair, const F
     else
     {
         // Failure
-        ErrorMessage = TEXT("Failed to transfer SOL. Check logs for details (ensure sender has sufficient balance, recipient is valid).");
+        ErrorMessage = GetLastSolanaError(TEXT("Failed to transfer SOL."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return FString(); // Return empty string for failure
     }
@@ -773,9 +780,9 @@ TArray<FTokenInfo> USolanaManager::GetAllTokens(const FSolanaPublicKey &WalletPu
 
     // Call the SDK function to retrieve tokens
     TokenList *TokenListPtr = get_all_tokens(SolanaClient, &SolPubKey);
-    if (!TokenListPtr || TokenListPtr->len == 0)
+    if (!TokenListPtr)
     {
-        ErrorMessage = TEXT("No tokens found or failed to retrieve tokens.");
+        ErrorMessage = GetLastSolanaError(TEXT("Failed to retrieve tokens."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return Tokens;
     }
@@ -834,7 +841,7 @@ FSolanaPublicKey USolanaManager::AddressToPublicKey(const FString &Address, FStr
     SolPublicKey *RawPubKey = get_pubkey_from_address(CAddress);
     if (!RawPubKey)
     {
-        ErrorMessage = TEXT("Invalid Solana address.");
+        ErrorMessage = GetLastSolanaError(TEXT("Invalid Solana address."));
         UE_LOG(LogTemp, Error, TEXT("%s"), *ErrorMessage);
         return PublicKey;
     }
@@ -1,12 +1,35 @@
 // Copyright Epic Games, Inc. All Rights Reserved.
 
 #include "SolanaPlugin.h"
+#include "solana_sdk.h"
 
 #define LOCTEXT_NAMESPACE "FSolanaPluginModule"
 
+// Route SDK logs into the Unreal log; may be called from SDK worker threads
+static void RouteSolanaLog(SolLogLevel Level, const char *Target, const char *Message, void *UserData)
+{
+	const FString Text = FString::Printf(TEXT("[%s] %s"), UTF8_TO_TCHAR(Target), UTF8_TO_TCHAR(Message));
+	switch (Level)
+	{
+	case SolLogLevel_Error:
+		UE_LOG(LogTemp, Error, TEXT("%s"), *Text);
+		break;
+	case SolLogLevel_Warn:
+		UE_LOG(LogTemp, Warning, TEXT("%s"), *Text);
+		break;
+	case SolLogLevel_Info:
+		UE_LOG(LogTemp, Log, TEXT("%s"), *Text);
+		break;
+	default:
+		UE_LOG(LogTemp, Verbose, TEXT("%s"), *Text);
+		break;
+	}
+}
+
 void FSolanaPluginModule::StartupModule()
 {
 	UE_LOG(LogTemp, Warning, TEXT("Solana Plugin Loaded"));
+	sol_set_log_callback(SolLogLevel_Info, RouteSolanaLog, nullptr);
 	// This code will execute after your module is loaded into memory; the exact timing is specified in the .uplugin file per-module
 }
 
@@ -14,6 +37,7 @@ void FSolanaPluginModule::ShutdownModule()
 {
 	// This function may be called during shutdown to clean up your module.  For modules that support dynamic reloading,
 	// we call this function before unloading the module.
+	sol_set_log_callback(SolLogLevel_Off, nullptr, nullptr);
 }
 
 #undef LOCTEXT_NAMESPACE
@@ -0,0 +1,315 @@
+// Generated by idl_codegen from the `anchor_counter` IDL. Do not edit.
+
+#ifndef ANCHOR_COUNTER_IDL_H
+#define ANCHOR_COUNTER_IDL_H
+
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "solana_sdk.h"
+
+#define ANCHOR_COUNTER_PROGRAM_ID "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy"
+
+#ifndef SOL_IDL_RUNTIME
+#define SOL_IDL_RUNTIME
+
+// Growable buffer instruction data is Borsh-encoded into
+typedef struct SolIdlWriter {
+  uint8_t *data;
+  uintptr_t len;
+  uintptr_t cap;
+  bool failed;
+} SolIdlWriter;
+
+// Cursor over Borsh-encoded account data
+typedef struct SolIdlReader {
+  const uint8_t *data;
+  uintptr_t len;
+  bool failed;
+} SolIdlReader;
+
+static inline void sol_idl_write(SolIdlWriter *w, const void *bytes, uintptr_t len) {
+  if (w->failed || len == 0) {
+    return;
+  }
+  if (w->cap - w->len < len) {
+    uintptr_t cap = w->cap ? w->cap : 64;
+    while (cap - w->len < len) {
+      cap *= 2;
+    }
+    uint8_t *data = (uint8_t *)realloc(w->data, cap);
+    if (!data) {
+      w->failed = true;
+      return;
+    }
+    w->data = data;
+    w->cap = cap;
+  }
+  memcpy(w->data + w->len, bytes, len);
+  w->len += len;
+}
+
+// Little-endian integer of `size` bytes
+static inline void sol_idl_write_uint(SolIdlWriter *w, uint64_t value, int size) {
+  uint8_t bytes[8];
+  for (int i = 0; i < size; i++) {
+    bytes[i] = (uint8_t)(value >> (8 * i));
+  }
+  sol_idl_write(w, bytes, (uintptr_t)size);
+}
+
+static inline void sol_idl_write_f32(SolIdlWriter *w, float value) {
+  uint32_t bits;
+  memcpy(&bits, &value, sizeof(bits));
+  sol_idl_write_uint(w, bits, 4);
+}
+
+static inline void sol_idl_write_f64(SolIdlWriter *w, double value) {
+  uint64_t bits;
+  memcpy(&bits, &value, sizeof(bits));
+  sol_idl_write_uint(w, bits, 8);
+}
+
+static inline void sol_idl_write_string(SolIdlWriter *w, const char *value) {
+  if (!value) {
+    w->failed = true;
+    return;
+  }
+  uintptr_t len = strlen(value);
+  sol_idl_write_uint(w, len, 4);
+  sol_idl_write(w, value, len);
+}
+
+static inline const uint8_t *sol_idl_read(SolIdlReader *r, uintptr_t len) {
+  if (r->failed || r->len < len) {
+    r->failed = true;
+    return NULL;
+  }
+  const uint8_t *bytes = r->data;
+  r->data += len;
+  r->len -= len;
+  return bytes;
+}
+
+static inline void sol_idl_read_into(SolIdlReader *r, void *out, uintptr_t len) {
+  const uint8_t *bytes = sol_idl_read(r, len);
+  if (bytes) {
+    memcpy(out, bytes, len);
+  }
+}
+
+static inline uint64_t sol_idl_read_uint(SolIdlReader *r, int size) {
+  const uint8_t *bytes = sol_idl_read(r, (uintptr_t)size);
+  uint64_t value = 0;
+  for (int i = 0; bytes && i < size; i++) {
+    value |= (uint64_t)bytes[i] << (8 * i);
+  }
+  return value;
+}
+
+static inline float sol_idl_read_f32(SolIdlReader *r) {
+  uint32_t bits = (uint32_t)sol_idl_read_uint(r, 4);
+  float value;
+  memcpy(&value, &bits, sizeof(value));
+  return value;
+}
+
+static inline double sol_idl_read_f64(SolIdlReader *r) {
+  uint64_t bits = sol_idl_read_uint(r, 8);
+  double value;
+  memcpy(&value, &bits, sizeof(value));
+  return value;
+}
+
+static inline char *sol_idl_read_string(SolIdlReader *r) {
+  uintptr_t len = (uintptr_t)sol_idl_read_uint(r, 4);
+  const uint8_t *bytes = sol_idl_read(r, len);
+  char *value = bytes ? (char *)malloc(len + 1) : NULL;
+  if (!value) {
+    r->failed = true;
+    return NULL;
+  }
+  memcpy(value, bytes, len);
+  value[len] = '\0';
+  return value;
+}
+
+static inline uint8_t *sol_idl_read_bytes(SolIdlReader *r, uintptr_t *len) {
+  *len = 0;
+  uintptr_t count = (uintptr_t)sol_idl_read_uint(r, 4);
+  const uint8_t *bytes = sol_idl_read(r, count);
+  uint8_t *value = bytes ? (uint8_t *)malloc(count ? count : 1) : NULL;
+  if (!value) {
+    r->failed = true;
+    return NULL;
+  }
+  memcpy(value, bytes, count);
+  *len = count;
+  return value;
+}
+
+// Zeroed storage for a Borsh vector. Unless the elements encode to zero bytes,
+// each takes at least one, so a count beyond the bytes left is bogus
+static inline void *sol_idl_read_vec(SolIdlReader *r,
+                                     uintptr_t size,
+                                     bool zero_sized,
+                                     uintptr_t *len) {
+  *len = 0;
+  uintptr_t count = (uintptr_t)sol_idl_read_uint(r, 4);
+  if (r->failed || (!zero_sized && count > r->len)) {
+    r->failed = true;
+    return NULL;
+  }
+  void *items = calloc(count ? count : 1, size);
+  if (!items) {
+    r->failed = true;
+    return NULL;
+  }
+  *len = count;
+  return items;
+}
+
+static inline SolAccountMeta sol_idl_account_meta(const SolPublicKey *pubkey,
+                                                  bool is_signer,
+                                                  bool is_writable) {
+  SolAccountMeta meta;
+  meta.pubkey = *pubkey;
+  meta.is_signer = is_signer;
+  meta.is_writable = is_writable;
+  return meta;
+}
+
+#endif
+
+typedef struct AnchorCounterCounter AnchorCounterCounter;
+
+struct AnchorCounterCounter {
+  uint64_t count;
+};
+
+static inline void anchor_counter_write_counter(SolIdlWriter *_w, const AnchorCounterCounter *_v);
+static inline void anchor_counter_read_counter(SolIdlReader *_r, AnchorCounterCounter *_v);
+static inline void anchor_counter_free_counter(AnchorCounterCounter *_v);
+
+static inline void anchor_counter_write_counter(SolIdlWriter *_w, const AnchorCounterCounter *_v) {
+  sol_idl_write_uint(_w, (uint64_t)_v->count, 8);
+}
+
+static inline void anchor_counter_read_counter(SolIdlReader *_r, AnchorCounterCounter *_v) {
+  _v->count = (uint64_t)sol_idl_read_uint(_r, 8);
+}
+
+static inline void anchor_counter_free_counter(AnchorCounterCounter *_v) {
+  (void)_v;
+}
+
+// Send `decrement`. Accounts: counter (writable), user (signer).
+// `program_id` may be NULL to use ANCHOR_COUNTER_PROGRAM_ID.
+// Returns the signature (free with free_transaction_signature) or NULL on error.
+static inline char *anchor_counter_decrement(SolClient *client,
+                                             const char *program_id,
+                                             const SolPublicKey *counter,
+                                             const SolPublicKey *user,
+                                             SolKeyPair *const *signers,
+                                             uintptr_t signer_count) {
+  static const uint8_t _discriminator[] = {106, 227, 168, 59, 248, 27, 150, 101};
+  SolIdlWriter _buffer = {NULL, 0, 0, false};
+  SolIdlWriter *_w = &_buffer;
+  sol_idl_write(_w, _discriminator, sizeof(_discriminator));
+  SolAccountMeta _accounts[2];
+  _accounts[0] = sol_idl_account_meta(counter, false, true);
+  _accounts[1] = sol_idl_account_meta(user, true, false);
+  char *_signature = NULL;
+  if (!_buffer.failed) {
+    _signature = send_raw_instruction_c(client, program_id ? program_id : ANCHOR_COUNTER_PROGRAM_ID, _accounts, 2, signers, signer_count, _buffer.data, _buffer.len, NULL);
+  }
+  free(_buffer.data);
+  return _signature;
+}
+
+// Send `increment`. Accounts: counter (writable), user (signer).
+// `program_id` may be NULL to use ANCHOR_COUNTER_PROGRAM_ID.
+// Returns the signature (free with free_transaction_signature) or NULL on error.
+static inline char *anchor_counter_increment(SolClient *client,
+                                             const char *program_id,
+                                             const SolPublicKey *counter,
+                                             const SolPublicKey *user,
+                                             SolKeyPair *const *signers,
+                                             uintptr_t signer_count) {
+  static const uint8_t _discriminator[] = {11, 18, 104, 9, 104, 174, 59, 33};
+  SolIdlWriter _buffer = {NULL, 0, 0, false};
+  SolIdlWriter *_w = &_buffer;
+  sol_idl_write(_w, _discriminator, sizeof(_discriminator));
+  SolAccountMeta _accounts[2];
+  _accounts[0] = sol_idl_account_meta(counter, false, true);
+  _accounts[1] = sol_idl_account_meta(user, true, false);
+  char *_signature = NULL;
+  if (!_buffer.failed) {
+    _signature = send_raw_instruction_c(client, program_id ? program_id : ANCHOR_COUNTER_PROGRAM_ID, _accounts, 2, signers, signer_count, _buffer.data, _buffer.len, NULL);
+  }
+  free(_buffer.data);
+  return _signature;
+}
+
+// Send `initialize`. Accounts: counter (signer, writable), user (signer, writable).
+// `program_id` may be NULL to use ANCHOR_COUNTER_PROGRAM_ID.
+// Returns the signature (free with free_transaction_signature) or NULL on error.
+static inline char *anchor_counter_initialize(SolClient *client,
+                                              const char *program_id,
+                                              const SolPublicKey *counter,
+                                              const SolPublicKey *user,
+                                              SolKeyPair *const *signers,
+                                              uintptr_t signer_count) {
+  static const uint8_t _discriminator[] = {175, 175, 109, 31, 13, 152, 155, 237};
+  SolIdlWriter _buffer = {NULL, 0, 0, false};
+  SolIdlWriter *_w = &_buffer;
+  sol_idl_write(_w, _discriminator, sizeof(_discriminator));
+  SolAccountMeta _accounts[3];
+  _accounts[0] = sol_idl_account_meta(counter, true, true);
+  _accounts[1] = sol_idl_account_meta(user, true, true);
+  static const SolPublicKey _fixed_system_program = {{0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0}};
+  _accounts[2] = sol_idl_account_meta(&_fixed_system_program, false, false);
+  char *_signature = NULL;
+  if (!_buffer.failed) {
+    _signature = send_raw_instruction_c(client, program_id ? program_id : ANCHOR_COUNTER_PROGRAM_ID, _accounts, 3, signers, signer_count, _buffer.data, _buffer.len, NULL);
+  }
+  free(_buffer.data);
+  return _signature;
+}
+
+// Decode a `Counter` account, discriminator included. Release it with anchor_counter_free_counter.
+static inline bool anchor_counter_decode_counter(const uint8_t *data,
+                                                 uintptr_t len,
+                                                 AnchorCounterCounter *out) {
+  static const uint8_t _discriminator[] = {255, 176, 4, 245, 188, 253, 124, 25};
+  if (len < sizeof(_discriminator) ||
+      memcmp(data, _discriminator, sizeof(_discriminator)) != 0) {
+    return false;
+  }
+  SolIdlReader _reader = {data + sizeof(_discriminator), len - sizeof(_discriminator), false};
+  memset(out, 0, sizeof(*out));
+  anchor_counter_read_counter(&_reader, out);
+  if (_reader.failed) {
+    anchor_counter_free_counter(out);
+    return false;
+  }
+  return true;
+}
+
+// Fetch `pubkey` and decode it as a `Counter` account
+static inline bool anchor_counter_fetch_counter(SolClient *client,
+                                                const SolPublicKey *pubkey,
+                                                AnchorCounterCounter *out) {
+  uint8_t *_data = (uint8_t *)malloc(16);
+  if (!_data) {
+    return false;
+  }
+  uintptr_t _len = get_account_data_c(client, (SolPublicKey *)pubkey, _data, 16, 0);
+  bool _ok = _len > 0 && anchor_counter_decode_counter(_data, _len, out);
+  free(_data);
+  return _ok;
+}
+
+#endif /* ANCHOR_COUNTER_IDL_H */
@@ -11,17 +11,212 @@ extern "C" {
 #include <stdint.h>
 #include <stdlib.h>
 
+typedef enum SolAccountStatus {
+  SolAccountStatus_Found = 0,
+  SolAccountStatus_NotFound = 1,
+  SolAccountStatus_Error = 2,
+} SolAccountStatus;
+
+typedef enum SolConfirmationStatus {
+  SolConfirmationStatus_Confirmed = 0,
+  SolConfirmationStatus_Failed = 1,
+  SolConfirmationStatus_TimedOut = 2,
+  SolConfirmationStatus_Cancelled = 3,
+} SolConfirmationStatus;
+
+typedef enum SolErrorCode {
+  SolErrorCode_Ok = 0,
+  SolErrorCode_RpcTransport = 1,
+  SolErrorCode_RpcServer = 2,
+  SolErrorCode_InvalidInput = 3,
+  SolErrorCode_Signing = 4,
+  SolErrorCode_InsufficientFunds = 5,
+  SolErrorCode_AccountNotFound = 6,
+  SolErrorCode_ProgramError = 7,
+  SolErrorCode_TransactionFailed = 8,
+  SolErrorCode_Internal = 9,
+  SolErrorCode_Panic = 10,
+  SolErrorCode_Timeout = 11,
+} SolErrorCode;
+
+typedef enum SolConfirmationStage {
+  SolConfirmationStage_NotFound = 0,
+  SolConfirmationStage_Processed = 1,
+  SolConfirmationStage_Confirmed = 2,
+  SolConfirmationStage_Finalized = 3,
+} SolConfirmationStage;
+
+typedef enum SolJobStatus {
+  SolJobStatus_Pending = 0,
+  SolJobStatus_Succeeded = 1,
+  SolJobStatus_Failed = 2,
+  SolJobStatus_Cancelled = 3,
+  SolJobStatus_Unknown = 4,
+} SolJobStatus;
+
+typedef enum SolLogLevel {
+  SolLogLevel_Off = 0,
+  SolLogLevel_Error = 1,
+  SolLogLevel_Warn = 2,
+  SolLogLevel_Info = 3,
+  SolLogLevel_Debug = 4,
+  SolLogLevel_Trace = 5,
+} SolLogLevel;
+
+typedef enum SolCommitment {
+  SolCommitment_Processed = 0,
+  SolCommitment_Confirmed = 1,
+  SolCommitment_Finalized = 2,
+} SolCommitment;
+
+typedef enum SolIdlItemKind {
+  SolIdlItemKind_Instruction = 0,
+  SolIdlItemKind_Account = 1,
+  SolIdlItemKind_Type = 2,
+} SolIdlItemKind;
+
+typedef enum SolAccountFilterKind {
+  SolAccountFilterKind_Memcmp = 0,
+  SolAccountFilterKind_DataSize = 1,
+} SolAccountFilterKind;
+
+typedef enum SolAccountEncoding {
+  SolAccountEncoding_Base64 = 0,
+  SolAccountEncoding_Base58 = 1,
+  SolAccountEncoding_Base64Zstd = 2,
+} SolAccountEncoding;
+
 typedef struct SolClient SolClient;
 
+typedef struct SolIdl SolIdl;
+
+typedef struct SolTransactionBuilder SolTransactionBuilder;
+
+typedef struct SolAccountFilter {
+  uint32_t kind;
+  uintptr_t offset;
+  const uint8_t *bytes;
+  uintptr_t bytes_len;
+  uint64_t data_size;
+} SolAccountFilter;
+
+typedef struct SolProgramAccountsConfig {
+  const struct SolAccountFilter *filters;
+  uintptr_t filter_count;
+  bool has_data_slice;
+  uintptr_t data_slice_offset;
+  uintptr_t data_slice_length;
+  uint32_t encoding;
+} SolProgramAccountsConfig;
+
 typedef struct SolPublicKey {
   uint8_t data[32];
 } SolPublicKey;
 
+typedef struct SolProgramAccount {
+  struct SolPublicKey pubkey;
+  uint64_t lamports;
+  struct SolPublicKey owner;
+  uint8_t *data;
+  uintptr_t data_len;
+  char *json;
+} SolProgramAccount;
+
+typedef struct SolProgramAccountList {
+  struct SolProgramAccount *accounts;
+  uintptr_t len;
+} SolProgramAccountList;
+
+typedef struct SolAccountInfo {
+  uint64_t lamports;
+  struct SolPublicKey owner;
+  bool executable;
+  uint64_t rent_epoch;
+  uint8_t *data;
+  uintptr_t data_len;
+} SolAccountInfo;
+
+typedef struct SolAccountMeta {
+  struct SolPublicKey pubkey;
+  bool is_signer;
+  bool is_writable;
+} SolAccountMeta;
+
 typedef struct SolKeyPair {
   uint8_t bytes[64];
   struct SolPublicKey pubkey;
 } SolKeyPair;
 
+typedef struct SolConfirmation {
+  enum SolConfirmationStatus status;
+  uint64_t slot;
+  enum SolErrorCode error_code;
+  int64_t program_error_code;
+} SolConfirmation;
+
+typedef struct SolComputeBudget {
+  uint32_t unit_limit;
+  uint64_t unit_price;
+  bool auto_unit_limit;
+} SolComputeBudget;
+
+typedef struct SolClientConfig {
+  uint32_t commitment;
+  uint32_t transaction_commitment;
+  uint64_t request_timeout_ms;
+  uint64_t confirm_transaction_timeout_ms;
+  uint32_t max_retries;
+  uint64_t retry_backoff_ms;
+  uint64_t confirm_poll_interval_ms;
+  bool simulate_before_send;
+  struct SolComputeBudget compute_budget;
+} SolClientConfig;
+
+typedef struct SolSendOptions {
+  const struct SolComputeBudget *budget;
+  const struct SolPublicKey *lookup_tables;
+  uintptr_t lookup_table_count;
+  struct SolConfirmation *outcome;
+} SolSendOptions;
+
+typedef struct SolConfirmationProgress {
+  enum SolConfirmationStage stage;
+  uint64_t slot;
+  uint64_t elapsed_ms;
+  uint32_t polls;
+} SolConfirmationProgress;
+
+typedef void (*SolConfirmationProgressCallback)(const char *signature,
+                                                const struct SolConfirmationProgress *progress,
+                                                void *user_data);
+
+typedef struct SolFeeEstimate {
+  uint32_t created_accounts;
+  uint64_t network_fee;
+  uint64_t rent;
+  uint64_t total;
+} SolFeeEstimate;
+
+typedef struct SolCompletionStatus {
+  enum SolConfirmationStatus outcome;
+  const char *error_message;
+  enum SolErrorCode error_code;
+  int64_t program_error_code;
+} SolCompletionStatus;
+
+typedef void (*SolCompletionCallback)(const char *signature,
+                                      const struct SolCompletionStatus *status,
+                                      void *user_data);
+
+typedef struct SolProgramError {
+  uint8_t instruction_index;
+  uint32_t code;
+  char *name;
+  char *message;
+  char **logs;
+  uintptr_t log_count;
+} SolProgramError;
+
 typedef struct TokenInfo {
   const char *mint;
   const char *balance;
@@ -33,6 +228,41 @@ typedef struct TokenList {
   uintptr_t len;
 } TokenList;
 
+typedef void (*SolLogCallback)(enum SolLogLevel level,
+                               const char *target,
+                               const char *message,
+                               void *user_data);
+
+typedef struct SolLookupTable {
+  struct SolPublicKey authority;
+  bool has_authority;
+  uint64_t deactivation_slot;
+  uint64_t last_extended_slot;
+  uint8_t last_extended_slot_start_index;
+  struct SolPublicKey *addresses;
+  uintptr_t address_count;
+} SolLookupTable;
+
+typedef struct SolNonceAccount {
+  struct SolPublicKey authority;
+  uint8_t nonce[32];
+  uint64_t lamports_per_signature;
+  uint64_t lamports;
+} SolNonceAccount;
+
+typedef struct SolSimulationResult {
+  bool success;
+  enum SolErrorCode error_code;
+  int64_t program_error_code;
+  char *error_message;
+  uint64_t units_consumed;
+  char **logs;
+  uintptr_t log_count;
+  struct SolAccountInfo *accounts;
+  bool *account_found;
+  uintptr_t account_count;
+} SolSimulationResult;
+
 typedef struct SolMint {
   struct SolPublicKey *mint_authority;
   uint64_t supply;
@@ -45,12 +275,337 @@ typedef struct SolSecretKey {
   uint8_t data[64];
 } SolSecretKey;
 
+struct SolProgramAccountsConfig sol_program_accounts_config_default(void);
+
+struct SolProgramAccountList *sol_get_program_accounts(struct SolClient *client,
+                                                       const struct SolPublicKey *program_id,
+                                                       const struct SolProgramAccountsConfig *config);
+
+void free_program_account_list(struct SolProgramAccountList *list);
+
+enum SolAccountStatus sol_get_account_info(struct SolClient *client,
+                                           const struct SolPublicKey *pubkey,
+                                           struct SolAccountInfo *info);
+
+bool sol_get_multiple_accounts(struct SolClient *client,
+                               const struct SolPublicKey *pubkeys,
+                               uintptr_t count,
+                               struct SolAccountInfo *infos,
+                               bool *found);
+
+void free_account_info(struct SolAccountInfo *info);
+
+struct SolTransactionBuilder *sol_tx_builder_new(void);
+
+void sol_tx_builder_free(struct SolTransactionBuilder *builder);
+
+uintptr_t sol_tx_builder_instruction_count(const struct SolTransactionBuilder *builder);
+
+bool sol_tx_builder_set_fee_payer(struct SolTransactionBuilder *builder,
+                                  const struct SolPublicKey *fee_payer);
+
+bool sol_tx_builder_add_transfer(struct SolTransactionBuilder *builder,
+                                 const struct SolPublicKey *from,
+                                 const struct SolPublicKey *to,
+                                 uint64_t lamports);
+
+bool sol_tx_builder_add_spl_transfer(struct SolTransactionBuilder *builder,
+                                     const struct SolPublicKey *owner,
+                                     const struct SolPublicKey *recipient,
+                                     const struct SolPublicKey *mint,
+                                     uint64_t amount);
+
+bool sol_tx_builder_add_create_ata(struct SolTransactionBuilder *builder,
+                                   const struct SolPublicKey *payer,
+                                   const struct SolPublicKey *owner,
+                                   const struct SolPublicKey *mint);
+
+bool sol_tx_builder_add_memo(struct SolTransactionBuilder *builder, const char *memo);
+
+bool sol_tx_builder_set_compute_unit_limit(struct SolTransactionBuilder *builder, uint32_t units);
+
+bool sol_tx_builder_set_compute_unit_price(struct SolTransactionBuilder *builder,
+                                           uint64_t micro_lamports);
+
+bool sol_tx_builder_add_lookup_table(struct SolTransactionBuilder *builder,
+                                     const struct SolPublicKey *table);
+
+bool sol_tx_builder_set_nonce_account(struct SolTransactionBuilder *builder,
+                                      const struct SolPublicKey *nonce_account,
+                                      const struct SolPublicKey *authority);
+
+bool sol_tx_builder_add_instruction(struct SolTransactionBuilder *builder,
+                                    const struct SolPublicKey *program_id,
+                                    const struct SolAccountMeta *accounts,
+                                    uintptr_t account_count,
+                                    const uint8_t *data_ptr,
+                                    uintptr_t data_len);
+
+bool sol_tx_builder_add_anchor_instruction(struct SolTransactionBuilder *builder,
+                                           const struct SolPublicKey *program_id,
+                                           const char *method_name,
+                                           const struct SolAccountMeta *accounts,
+                                           uintptr_t account_count,
+                                           const uint8_t *data_ptr,
+                                           uintptr_t data_len);
+
+bool sol_tx_builder_sign(struct SolTransactionBuilder *builder,
+                         struct SolClient *client,
+                         struct SolKeyPair *const *signers,
+                         uintptr_t signer_count);
+
+char *sol_tx_builder_send(const struct SolTransactionBuilder *builder,
+                          struct SolClient *client,
+                          struct SolConfirmation *outcome);
+
+uint8_t *sol_tx_builder_serialize(const struct SolTransactionBuilder *builder, uintptr_t *out_len);
+
+void free_serialized_transaction(uint8_t *bytes, uintptr_t len);
+
+char *sol_send_serialized_transaction(struct SolClient *client,
+                                      const uint8_t *bytes,
+                                      uintptr_t len,
+                                      struct SolConfirmation *outcome);
+
+struct SolClientConfig sol_client_config_default(void);
+
 struct SolClient *new_sol_client(const char *url);
 
+struct SolClient *new_sol_client_with_config(const char *url, const struct SolClientConfig *config);
+
 uint64_t get_balance(struct SolClient *client, struct SolPublicKey *pubkey);
 
+uint64_t get_balance_with_commitment(struct SolClient *client,
+                                     struct SolPublicKey *pubkey,
+                                     uint32_t commitment);
+
 bool request_airdrop(struct SolClient *client, struct SolPublicKey *pubkey, uint64_t lamports);
 
+struct SolSendOptions sol_send_options_default(void);
+
+bool sol_client_set_confirmation_callback(struct SolClient *client,
+                                          SolConfirmationProgressCallback callback,
+                                          void *user_data);
+
+bool sol_confirm_transaction(struct SolClient *client,
+                             const char *signature,
+                             struct SolConfirmation *outcome);
+
+enum SolErrorCode sol_last_error_code(void);
+
+const char *sol_last_error_message(void);
+
+int64_t sol_last_error_program_code(void);
+
+void sol_clear_last_error(void);
+
+bool sol_estimate_priority_fee(struct SolClient *client,
+                               const struct SolPublicKey *accounts,
+                               uintptr_t account_count,
+                               uint8_t percentile,
+                               uint64_t *micro_lamports);
+
+bool sol_estimate_fee(struct SolClient *client,
+                      const uint8_t *message,
+                      uintptr_t len,
+                      struct SolFeeEstimate *estimate);
+
+bool sol_tx_builder_estimate_fee(const struct SolTransactionBuilder *builder,
+                                 struct SolClient *client,
+                                 struct SolFeeEstimate *estimate);
+
+bool sol_estimate_transfer_spl_fee(struct SolClient *client,
+                                   const struct SolPublicKey *sender,
+                                   const struct SolPublicKey *recipient,
+                                   const struct SolPublicKey *mint,
+                                   uint64_t amount,
+                                   struct SolFeeEstimate *estimate);
+
+bool sol_tx_builder_estimate_priority_fee(const struct SolTransactionBuilder *builder,
+                                          struct SolClient *client,
+                                          uint8_t percentile,
+                                          uint64_t *micro_lamports);
+
+struct SolIdl *sol_idl_load(const char *json);
+
+struct SolIdl *sol_idl_load_file(const char *path);
+
+void sol_idl_free(struct SolIdl *idl);
+
+bool sol_idl_program_id(const struct SolIdl *idl, struct SolPublicKey *program_id);
+
+uintptr_t sol_idl_item_count(const struct SolIdl *idl, uint32_t kind);
+
+const char *sol_idl_item_name(const struct SolIdl *idl, uint32_t kind, uintptr_t index);
+
+uint8_t *sol_idl_encode_instruction(const struct SolIdl *idl,
+                                    const char *method_name,
+                                    const char *args_json,
+                                    uintptr_t *out_len);
+
+void free_instruction_data(uint8_t *data, uintptr_t len);
+
+char *sol_idl_decode_account(struct SolClient *client,
+                             const struct SolIdl *idl,
+                             const char *account_type,
+                             const struct SolPublicKey *pubkey);
+
+char *sol_idl_decode_account_data(const struct SolIdl *idl,
+                                  const char *account_type,
+                                  const uint8_t *data_ptr,
+                                  uintptr_t data_len);
+
+struct SolProgramAccountList *sol_idl_get_program_accounts(struct SolClient *client,
+                                                           const struct SolIdl *idl,
+                                                           const char *account_type,
+                                                           const struct SolProgramAccountsConfig *config);
+
+void free_idl_json(char *json);
+
+char *sol_idl_send_instruction(struct SolClient *client,
+                               const struct SolIdl *idl,
+                               const char *method_name,
+                               const struct SolPublicKey *accounts,
+                               uintptr_t account_count,
+                               struct SolKeyPair *const *signers,
+                               uintptr_t signer_count,
+                               const char *args_json,
+                               const struct SolSendOptions *options);
+
+uint64_t sol_idl_send_instruction_async(struct SolClient *client,
+                                        const struct SolIdl *idl,
+                                        const char *method_name,
+                                        const struct SolPublicKey *accounts,
+                                        uintptr_t account_count,
+                                        struct SolKeyPair *const *signers,
+                                        uintptr_t signer_count,
+                                        const char *args_json,
+                                        const struct SolSendOptions *options);
+
+uint64_t sol_idl_send_instruction_with_callback(struct SolClient *client,
+                                                const struct SolIdl *idl,
+                                                const char *method_name,
+                                                const struct SolPublicKey *accounts,
+                                                uintptr_t account_count,
+                                                struct SolKeyPair *const *signers,
+                                                uintptr_t signer_count,
+                                                const char *args_json,
+                                                const struct SolSendOptions *options,
+                                                SolCompletionCallback callback,
+                                                void *user_data);
+
+bool sol_last_program_error(const struct SolIdl *idl, struct SolProgramError *out);
+
+void free_program_error(struct SolProgramError *error);
+
+char *sol_idl_parse_events(struct SolClient *client,
+                           const struct SolIdl *idl,
+                           const char *signature,
+                           const struct SolPublicKey *program_id);
+
+char *sol_idl_parse_event_logs(const struct SolIdl *idl,
+                               const char *const *logs,
+                               uintptr_t log_count,
+                               const struct SolPublicKey *program_id);
+
+enum SolJobStatus sol_job_poll(uint64_t job_id);
+
+char *sol_job_result(uint64_t job_id);
+
+struct TokenList *sol_job_token_list(uint64_t job_id);
+
+bool sol_job_cancel(uint64_t job_id);
+
+bool sol_job_forget(uint64_t job_id);
+
+bool sol_set_log_callback(uint32_t level, SolLogCallback callback, void *user_data);
+
+char *sol_create_lookup_table(struct SolClient *client,
+                              struct SolKeyPair *payer,
+                              const struct SolPublicKey *authority,
+                              uint64_t recent_slot,
+                              struct SolPublicKey *table);
+
+char *sol_extend_lookup_table(struct SolClient *client,
+                              struct SolKeyPair *payer,
+                              struct SolKeyPair *authority,
+                              const struct SolPublicKey *table,
+                              const struct SolPublicKey *addresses,
+                              uintptr_t count);
+
+char *sol_deactivate_lookup_table(struct SolClient *client,
+                                  struct SolKeyPair *authority,
+                                  const struct SolPublicKey *table);
+
+char *sol_close_lookup_table(struct SolClient *client,
+                             struct SolKeyPair *authority,
+                             const struct SolPublicKey *table,
+                             const struct SolPublicKey *recipient);
+
+bool sol_get_lookup_table(struct SolClient *client,
+                          const struct SolPublicKey *table,
+                          struct SolLookupTable *out);
+
+void free_lookup_table(struct SolLookupTable *table);
+
+char *sol_create_nonce_account(struct SolClient *client,
+                               struct SolKeyPair *payer,
+                               struct SolKeyPair *nonce_account,
+                               const struct SolPublicKey *authority,
+                               uint64_t lamports);
+
+bool sol_get_nonce_account(struct SolClient *client,
+                           const struct SolPublicKey *nonce_account,
+                           struct SolNonceAccount *out);
+
+char *sol_advance_nonce_account(struct SolClient *client,
+                                struct SolKeyPair *authority,
+                                const struct SolPublicKey *nonce_account);
+
+char *sol_withdraw_nonce_account(struct SolClient *client,
+                                 struct SolKeyPair *authority,
+                                 const struct SolPublicKey *nonce_account,
+                                 const struct SolPublicKey *recipient,
+                                 uint64_t lamports);
+
+char *sol_authorize_nonce_account(struct SolClient *client,
+                                  struct SolKeyPair *authority,
+                                  const struct SolPublicKey *nonce_account,
+                                  const struct SolPublicKey *new_authority);
+
+bool sol_find_program_address(const uint8_t *const *seeds,
+                              const uintptr_t *seed_lens,
+                              uintptr_t count,
+                              const struct SolPublicKey *program_id,
+                              struct SolPublicKey *address,
+                              uint8_t *bump);
+
+bool sol_create_program_address(const uint8_t *const *seeds,
+                                const uintptr_t *seed_lens,
+                                uintptr_t count,
+                                const struct SolPublicKey *program_id,
+                                struct SolPublicKey *address);
+
+bool sol_create_with_seed(const struct SolPublicKey *base,
+                          const char *seed,
+                          const struct SolPublicKey *owner,
+                          struct SolPublicKey *address);
+
+bool sol_tx_builder_simulate(const struct SolTransactionBuilder *builder,
+                             struct SolClient *client,
+                             const struct SolPublicKey *addresses,
+                             uintptr_t address_count,
+                             struct SolSimulationResult *out);
+
+bool sol_simulate_transaction(struct SolClient *client,
+                              const uint8_t *transaction,
+                              uintptr_t transaction_len,
+                              const struct SolPublicKey *addresses,
+                              uintptr_t address_count,
+                              struct SolSimulationResult *out);
+
+void free_simulation_result(struct SolSimulationResult *result);
+
 struct SolPublicKey get_system_program_id(void);
 
 uintptr_t get_account_data_c(struct SolClient *client,
@@ -69,7 +624,98 @@ char *send_generic_transaction_c(struct SolClient *client,
                                  const uint8_t *data_ptr,
                                  uintptr_t data_len);
 
-void initialize_account_c(struct SolClient *client,
+uint64_t send_generic_transaction_async(struct SolClient *client,
+                                        const char *program_id,
+                                        const char *method_name,
+                                        const struct SolPublicKey *account_pubkeys,
+                                        uintptr_t account_count,
+                                        struct SolKeyPair *const *signers,
+                                        uintptr_t signer_count,
+                                        const uint8_t *data_ptr,
+                                        uintptr_t data_len,
+                                        const struct SolSendOptions *options);
+
+uint64_t send_generic_transaction_with_callback(struct SolClient *client,
+                                                const char *program_id,
+                                                const char *method_name,
+                                                const struct SolPublicKey *account_pubkeys,
+                                                uintptr_t account_count,
+                                                struct SolKeyPair *const *signers,
+                                                uintptr_t signer_count,
+                                                const uint8_t *data_ptr,
+                                                uintptr_t data_len,
+                                                const struct SolSendOptions *options,
+                                                SolCompletionCallback callback,
+                                                void *user_data);
+
+char *send_anchor_transaction_c(struct SolClient *client,
+                                const char *program_id,
+                                const char *method_name,
+                                const struct SolAccountMeta *accounts,
+                                uintptr_t account_count,
+                                struct SolKeyPair *const *signers,
+                                uintptr_t signer_count,
+                                const uint8_t *data_ptr,
+                                uintptr_t data_len,
+                                const struct SolSendOptions *options);
+
+uint64_t send_anchor_transaction_async(struct SolClient *client,
+                                       const char *program_id,
+                                       const char *method_name,
+                                       const struct SolAccountMeta *accounts,
+                                       uintptr_t account_count,
+                                       struct SolKeyPair *const *signers,
+                                       uintptr_t signer_count,
+                                       const uint8_t *data_ptr,
+                                       uintptr_t data_len,
+                                       const struct SolSendOptions *options);
+
+uint64_t send_anchor_transaction_with_callback(struct SolClient *client,
+                                               const char *program_id,
+                                               const char *method_name,
+                                               const struct SolAccountMeta *accounts,
+                                               uintptr_t account_count,
+                                               struct SolKeyPair *const *signers,
+                                               uintptr_t signer_count,
+                                               const uint8_t *data_ptr,
+                                               uintptr_t data_len,
+                                               const struct SolSendOptions *options,
+                                               SolCompletionCallback callback,
+                                               void *user_data);
+
+char *send_raw_instruction_c(struct SolClient *client,
+                             const char *program_id,
+                             const struct SolAccountMeta *accounts,
+                             uintptr_t account_count,
+                             struct SolKeyPair *const *signers,
+                             uintptr_t signer_count,
+                             const uint8_t *data_ptr,
+                             uintptr_t data_len,
+                             const struct SolSendOptions *options);
+
+uint64_t send_raw_instruction_async(struct SolClient *client,
+                                    const char *program_id,
+                                    const struct SolAccountMeta *accounts,
+                                    uintptr_t account_count,
+                                    struct SolKeyPair *const *signers,
+                                    uintptr_t signer_count,
+                                    const uint8_t *data_ptr,
+                                    uintptr_t data_len,
+                                    const struct SolSendOptions *options);
+
+uint64_t send_raw_instruction_with_callback(struct SolClient *client,
+                                            const char *program_id,
+                                            const struct SolAccountMeta *accounts,
+                                            uintptr_t account_count,
+                                            struct SolKeyPair *const *signers,
+                                            uintptr_t signer_count,
+                                            const uint8_t *data_ptr,
+                                            uintptr_t data_len,
+                                            const struct SolSendOptions *options,
+                                            SolCompletionCallback callback,
+                                            void *user_data);
+
+bool initialize_account_c(struct SolClient *client,
                           struct SolKeyPair *payer,
                           struct SolKeyPair *account,
                           const char *program_id);
@@ -80,10 +726,16 @@ void free_payer(struct SolKeyPair *payer);
 
 struct TokenList *get_all_tokens(struct SolClient *client, struct SolPublicKey *wallet);
 
+uint64_t get_all_tokens_async(struct SolClient *client, struct SolPublicKey *wallet);
+
 struct TokenInfo *token_list_get_data(const struct TokenList *list);
 
 char *get_transaction_details_json(struct SolClient *client, const char *signature_str);
 
+char *get_transaction_details_json_with_commitment(struct SolClient *client,
+                                                   const char *signature_str,
+                                                   uint32_t commitment);
+
 void free_transaction_details_json(char *json_ptr);
 
 uintptr_t token_list_get_len(const struct TokenList *list);
@@ -95,12 +747,55 @@ char *transfer_sol(struct SolClient *client,
                    struct SolPublicKey *recipient,
                    uint64_t lamports);
 
+char *transfer_sol_with_options(struct SolClient *client,
+                                struct SolKeyPair *sender,
+                                struct SolPublicKey *recipient,
+                                uint64_t lamports,
+                                const struct SolSendOptions *options);
+
+uint64_t transfer_sol_async(struct SolClient *client,
+                            struct SolKeyPair *sender,
+                            struct SolPublicKey *recipient,
+                            uint64_t lamports,
+                            const struct SolSendOptions *options);
+
+uint64_t transfer_sol_with_callback(struct SolClient *client,
+                                    struct SolKeyPair *sender,
+                                    struct SolPublicKey *recipient,
+                                    uint64_t lamports,
+                                    const struct SolSendOptions *options,
+                                    SolCompletionCallback callback,
+                                    void *user_data);
+
 char *transfer_spl(struct SolClient *client,
                    struct SolKeyPair *sender,
                    struct SolPublicKey *recipient,
                    struct SolPublicKey *mint,
                    uint64_t amount);
 
+char *transfer_spl_with_options(struct SolClient *client,
+                                struct SolKeyPair *sender,
+                                struct SolPublicKey *recipient,
+                                struct SolPublicKey *mint,
+                                uint64_t amount,
+                                const struct SolSendOptions *options);
+
+uint64_t transfer_spl_async(struct SolClient *client,
+                            struct SolKeyPair *sender,
+                            struct SolPublicKey *recipient,
+                            struct SolPublicKey *mint,
+                            uint64_t amount,
+                            const struct SolSendOptions *options);
+
+uint64_t transfer_spl_with_callback(struct SolClient *client,
+                                    struct SolKeyPair *sender,
+                                    struct SolPublicKey *recipient,
+                                    struct SolPublicKey *mint,
+                                    uint64_t amount,
+                                    const struct SolSendOptions *options,
+                                    SolCompletionCallback callback,
+                                    void *user_data);
+
 void free_transaction_signature(char *signature);
 
 bool create_spl_token(struct SolClient *client, struct SolKeyPair *payer, struct SolKeyPair *mint);
@@ -118,10 +813,38 @@ bool mint_spl(struct SolClient *client,
               struct SolPublicKey *recipient,
               uint64_t amount);
 
+bool mint_spl_with_options(struct SolClient *client,
+                           struct SolKeyPair *payer,
+                           struct SolKeyPair *mint_authority,
+                           struct SolPublicKey *recipient,
+                           uint64_t amount,
+                           const struct SolSendOptions *options);
+
+uint64_t mint_spl_async(struct SolClient *client,
+                        struct SolKeyPair *payer,
+                        struct SolKeyPair *mint_authority,
+                        struct SolPublicKey *recipient,
+                        uint64_t amount,
+                        const struct SolSendOptions *options);
+
+uint64_t mint_spl_with_callback(struct SolClient *client,
+                                struct SolKeyPair *payer,
+                                struct SolKeyPair *mint_authority,
+                                struct SolPublicKey *recipient,
+                                uint64_t amount,
+                                const struct SolSendOptions *options,
+                                SolCompletionCallback callback,
+                                void *user_data);
+
 uint64_t get_associated_token_balance(struct SolClient *client,
                                       struct SolPublicKey *owner,
                                       struct SolPublicKey *mint);
 
+uint64_t get_associated_token_balance_with_commitment(struct SolClient *client,
+                                                      struct SolPublicKey *owner,
+                                                      struct SolPublicKey *mint,
+                                                      uint32_t commitment);
+
 struct SolPublicKey *get_public_key(struct SolKeyPair *wallet);
 
 struct SolSecretKey *get_secret_key(struct SolKeyPair *wallet);
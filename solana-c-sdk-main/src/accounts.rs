@@ -0,0 +1,664 @@
+// Account queries: fetch accounts with all their fields, one at a time or in
+// batches, and enumerate the accounts a program owns, optionally filtered on
+// their data.
+
+use serde_json::json;
+use solana_account_decoder::{UiAccount, UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::{RpcRequest, MAX_MULTIPLE_ACCOUNTS},
+    rpc_response::Response,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::ffi::{c_char, CString};
+
+use crate::{
+    client::SolClient,
+    error::{ffi_call, ffi_guard, mut_arg, ref_arg, slice_arg, SolError},
+    wallet::SolPublicKey,
+};
+
+// An account with all its fields. `data` is owned: release it with
+// `free_account_info`.
+#[repr(C)]
+pub struct SolAccountInfo {
+    pub lamports: u64,
+    pub owner: SolPublicKey,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    pub data: *mut u8,
+    pub data_len: usize,
+}
+
+impl SolAccountInfo {
+    pub(crate) fn new(account: Account) -> Self {
+        let data = account.data.into_boxed_slice();
+        SolAccountInfo {
+            lamports: account.lamports,
+            owner: SolPublicKey::new(account.owner),
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            data_len: data.len(),
+            data: Box::into_raw(data).cast(),
+        }
+    }
+
+    // What a missing account reads as
+    pub(crate) fn empty() -> Self {
+        SolAccountInfo {
+            lamports: 0,
+            owner: SolPublicKey::new(Pubkey::default()),
+            executable: false,
+            rent_epoch: 0,
+            data: std::ptr::null_mut(),
+            data_len: 0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolAccountStatus {
+    Found = 0,
+    NotFound = 1, // The account does not exist (no lamports); not an error
+    Error = 2,    // See `sol_last_error_code`
+}
+
+// Kinds of `SolAccountFilter`. C stores them as `uint32_t`, so unknown values
+// are rejected instead of becoming an invalid enum.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolAccountFilterKind {
+    Memcmp = 0,   // Data at `offset` equals `bytes`
+    DataSize = 1, // Data is exactly `data_size` bytes long
+}
+
+impl TryFrom<u32> for SolAccountFilterKind {
+    type Error = SolError;
+
+    fn try_from(value: u32) -> Result<Self, SolError> {
+        match value {
+            0 => Ok(SolAccountFilterKind::Memcmp),
+            1 => Ok(SolAccountFilterKind::DataSize),
+            _ => Err(SolError::InvalidInput(format!(
+                "unknown account filter kind {}",
+                value
+            ))),
+        }
+    }
+}
+
+#[repr(C)]
+pub struct SolAccountFilter {
+    pub kind: u32,        // SolAccountFilterKind
+    pub offset: usize,    // Memcmp: offset into the account data
+    pub bytes: *const u8, // Memcmp: bytes to compare
+    pub bytes_len: usize, // Memcmp: length of `bytes`
+    pub data_size: u64,   // DataSize: data length in bytes
+}
+
+// Wire encodings of account data, stored as `uint32_t` like the filter kinds
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolAccountEncoding {
+    Base64 = 0,
+    Base58 = 1,     // Only for accounts up to 128 bytes
+    Base64Zstd = 2, // Compressed by the node, for large accounts
+}
+
+impl TryFrom<u32> for SolAccountEncoding {
+    type Error = SolError;
+
+    fn try_from(value: u32) -> Result<Self, SolError> {
+        match value {
+            0 => Ok(SolAccountEncoding::Base64),
+            1 => Ok(SolAccountEncoding::Base58),
+            2 => Ok(SolAccountEncoding::Base64Zstd),
+            _ => Err(SolError::InvalidInput(format!(
+                "unknown account encoding {}",
+                value
+            ))),
+        }
+    }
+}
+
+// Options of `sol_get_program_accounts`. Start from
+// `sol_program_accounts_config_default()`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SolProgramAccountsConfig {
+    pub filters: *const SolAccountFilter, // Accounts must match every filter
+    pub filter_count: usize,
+    pub has_data_slice: bool, // Only return `data_slice_length` bytes from `data_slice_offset`
+    pub data_slice_offset: usize,
+    pub data_slice_length: usize,
+    pub encoding: u32, // SolAccountEncoding of the account data on the wire
+}
+
+impl Default for SolProgramAccountsConfig {
+    fn default() -> Self {
+        SolProgramAccountsConfig {
+            filters: std::ptr::null(),
+            filter_count: 0,
+            has_data_slice: false,
+            data_slice_offset: 0,
+            data_slice_length: 0,
+            encoding: SolAccountEncoding::Base64 as u32,
+        }
+    }
+}
+
+// One account of a `SolProgramAccountList`
+#[repr(C)]
+pub struct SolProgramAccount {
+    pub pubkey: SolPublicKey,
+    pub lamports: u64,
+    pub owner: SolPublicKey,
+    pub data: *mut u8, // The whole data, or the requested slice of it
+    pub data_len: usize,
+    pub json: *mut c_char, // Data decoded with an IDL, or NULL
+}
+
+#[repr(C)]
+pub struct SolProgramAccountList {
+    pub accounts: *mut SolProgramAccount,
+    pub len: usize,
+}
+
+// The RPC config of `config` (NULL: the defaults), with `extra` filters first
+// Safety: see `ref_arg` and `slice_arg`.
+unsafe fn rpc_config(
+    config: *const SolProgramAccountsConfig,
+    extra: Vec<RpcFilterType>,
+) -> Result<RpcProgramAccountsConfig, SolError> {
+    let config = match config.is_null() {
+        true => SolProgramAccountsConfig::default(),
+        false => *ref_arg(config, "config")?,
+    };
+    let mut filters = extra;
+    for filter in slice_arg(config.filters, config.filter_count, "filters")? {
+        filters.push(match SolAccountFilterKind::try_from(filter.kind)? {
+            SolAccountFilterKind::Memcmp => {
+                let bytes = slice_arg(filter.bytes, filter.bytes_len, "memcmp bytes")?;
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(filter.offset, bytes.to_vec()))
+            }
+            SolAccountFilterKind::DataSize => RpcFilterType::DataSize(filter.data_size),
+        });
+    }
+    let encoding = match SolAccountEncoding::try_from(config.encoding)? {
+        SolAccountEncoding::Base64 => UiAccountEncoding::Base64,
+        SolAccountEncoding::Base58 => UiAccountEncoding::Base58,
+        SolAccountEncoding::Base64Zstd => UiAccountEncoding::Base64Zstd,
+    };
+    let data_slice = config.has_data_slice.then_some(UiDataSliceConfig {
+        offset: config.data_slice_offset,
+        length: config.data_slice_length,
+    });
+
+    Ok(RpcProgramAccountsConfig {
+        filters: (!filters.is_empty()).then_some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(encoding),
+            data_slice,
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    })
+}
+
+// Accounts owned by `program_id` matching `config`, plus `extra` filters
+// Safety: see `rpc_config`.
+pub(crate) unsafe fn fetch_program_accounts(
+    client: &SolClient,
+    program_id: &Pubkey,
+    config: *const SolProgramAccountsConfig,
+    extra: Vec<RpcFilterType>,
+) -> Result<Vec<(Pubkey, Account)>, SolError> {
+    let config = rpc_config(config, extra)?;
+    Ok(client.rpc(|rpc| rpc.get_program_accounts_with_config(program_id, config.clone()))?)
+}
+
+// Hand `accounts` to C, with their decoded JSON when `json` has one per account
+pub(crate) fn into_account_list(
+    accounts: Vec<(Pubkey, Account)>,
+    json: Vec<CString>,
+) -> *mut SolProgramAccountList {
+    let mut json = json.into_iter();
+    let accounts: Box<[SolProgramAccount]> = accounts
+        .into_iter()
+        .map(|(pubkey, account)| {
+            let data = account.data.into_boxed_slice();
+            SolProgramAccount {
+                pubkey: SolPublicKey::new(pubkey),
+                lamports: account.lamports,
+                owner: SolPublicKey::new(account.owner),
+                data_len: data.len(),
+                data: Box::into_raw(data).cast(),
+                json: json.next().map_or(std::ptr::null_mut(), CString::into_raw),
+            }
+        })
+        .collect();
+    let list = SolProgramAccountList {
+        len: accounts.len(),
+        accounts: Box::into_raw(accounts).cast(),
+    };
+    Box::into_raw(Box::new(list))
+}
+
+// ==================== C API ==================== //
+
+#[no_mangle]
+pub extern "C" fn sol_program_accounts_config_default() -> SolProgramAccountsConfig {
+    SolProgramAccountsConfig::default()
+}
+
+// Accounts owned by `program_id` (`getProgramAccounts`) matching every filter of
+// `config` (NULL: all accounts, whole data). Free with `free_program_account_list`.
+#[no_mangle]
+pub extern "C" fn sol_get_program_accounts(
+    client: *mut SolClient,
+    program_id: *const SolPublicKey,
+    config: *const SolProgramAccountsConfig,
+) -> *mut SolProgramAccountList {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let program_id = unsafe { ref_arg(program_id, "program_id")? }.to_pubkey();
+
+        let accounts = unsafe { fetch_program_accounts(client, &program_id, config, Vec::new())? };
+        log::debug!("{} accounts owned by {}", accounts.len(), program_id);
+        Ok(into_account_list(accounts, Vec::new()))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn free_program_account_list(list: *mut SolProgramAccountList) {
+    ffi_guard((), || {
+        if list.is_null() {
+            return;
+        }
+        unsafe {
+            let list = Box::from_raw(list);
+            let accounts =
+                Box::from_raw(std::ptr::slice_from_raw_parts_mut(list.accounts, list.len));
+            for account in accounts.iter() {
+                drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                    account.data,
+                    account.data_len,
+                )));
+                if !account.json.is_null() {
+                    drop(CString::from_raw(account.json));
+                }
+            }
+        }
+    })
+}
+
+impl SolClient {
+    // The account at `pubkey`, or None if it does not exist. `RpcClient::get_account*`
+    // report any failure as "AccountNotFound", so the request is sent as is to
+    // tell a missing account from a failed query.
+    pub(crate) fn fetch_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, SolError> {
+        let response: Response<Option<UiAccount>> = self.rpc(|rpc| {
+            let config = RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            };
+            rpc.send(
+                RpcRequest::GetAccountInfo,
+                json!([pubkey.to_string(), config]),
+            )
+        })?;
+        response
+            .value
+            .map(|account| {
+                account.decode().ok_or_else(|| SolError::RpcServer {
+                    code: 0,
+                    message: format!("undecodable data of account {}", pubkey),
+                })
+            })
+            .transpose()
+    }
+}
+
+// Fetch `pubkey` into `info`. Returns `NotFound` (and leaves `info` untouched)
+// if the account does not exist, `Error` if the query failed.
+#[no_mangle]
+pub extern "C" fn sol_get_account_info(
+    client: *mut SolClient,
+    pubkey: *const SolPublicKey,
+    info: *mut SolAccountInfo,
+) -> SolAccountStatus {
+    ffi_call(SolAccountStatus::Error, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let pubkey = unsafe { ref_arg(pubkey, "pubkey")? }.to_pubkey();
+        let info = unsafe { mut_arg(info, "info")? };
+
+        Ok(match client.fetch_account(&pubkey)? {
+            Some(account) => {
+                *info = SolAccountInfo::new(account);
+                SolAccountStatus::Found
+            }
+            None => SolAccountStatus::NotFound,
+        })
+    })
+}
+
+// Fetch `count` accounts (`getMultipleAccounts`, 100 per request) into `infos`
+// and `found`, both arrays of `count` entries. Missing accounts are zeroed with
+// `found[i]` false. Release each entry with `free_account_info`.
+#[no_mangle]
+pub extern "C" fn sol_get_multiple_accounts(
+    client: *mut SolClient,
+    pubkeys: *const SolPublicKey,
+    count: usize,
+    infos: *mut SolAccountInfo,
+    found: *mut bool,
+) -> bool {
+    ffi_call(false, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let pubkeys: Vec<Pubkey> = unsafe { slice_arg(pubkeys, count, "pubkeys")? }
+            .iter()
+            .map(SolPublicKey::to_pubkey)
+            .collect();
+        if count > 0 && (infos.is_null() || found.is_null()) {
+            return Err(SolError::InvalidInput("infos or found is null".to_string()));
+        }
+
+        // Fetch everything before handing out any data, so nothing leaks on failure
+        let mut accounts = Vec::with_capacity(count);
+        for chunk in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            accounts.extend(
+                client
+                    .rpc(|rpc| rpc.get_multiple_accounts_with_commitment(chunk, rpc.commitment()))?
+                    .value,
+            );
+        }
+        if accounts.len() != count {
+            return Err(SolError::Internal(format!(
+                "asked for {} accounts, got {}",
+                count,
+                accounts.len()
+            )));
+        }
+        for (i, account) in accounts.into_iter().enumerate() {
+            unsafe {
+                *found.add(i) = account.is_some();
+                infos
+                    .add(i)
+                    .write(account.map_or_else(SolAccountInfo::empty, SolAccountInfo::new));
+            }
+        }
+        Ok(true)
+    })
+}
+
+// Release the data of a `SolAccountInfo` filled by `sol_get_account_info` or
+// `sol_get_multiple_accounts`
+#[no_mangle]
+pub extern "C" fn free_account_info(info: *mut SolAccountInfo) {
+    ffi_guard((), || {
+        let Some(info) = (unsafe { info.as_mut() }) else {
+            return;
+        };
+        if !info.data.is_null() {
+            unsafe {
+                drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                    info.data,
+                    info.data_len,
+                )));
+            }
+        }
+        info.data = std::ptr::null_mut();
+        info.data_len = 0;
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::client::new_sol_client;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use crate::mock_rpc::{with_context, MockRpc};
+    use crate::smart_contract::free_client;
+    use crate::token::get_or_create_associated_token_account;
+    use crate::wallet::SolKeyPair;
+    use base64::Engine;
+    use serde_json::Value;
+    use spl_associated_token_account::get_associated_token_address;
+    use std::sync::{Arc, Mutex};
+
+    // A `getProgramAccounts` entry
+    pub(crate) fn keyed_account(
+        pubkey: &Pubkey,
+        owner: &Pubkey,
+        lamports: u64,
+        data: &[u8],
+    ) -> Value {
+        json!({
+            "pubkey": pubkey.to_string(),
+            "account": {
+                "lamports": lamports,
+                "owner": owner.to_string(),
+                "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                "executable": false,
+                "rentEpoch": 0,
+                "space": data.len(),
+            },
+        })
+    }
+
+    pub(crate) fn list_accounts(
+        list: *mut SolProgramAccountList,
+    ) -> Vec<&'static SolProgramAccount> {
+        assert!(!list.is_null());
+        let list = unsafe { &*list };
+        (0..list.len)
+            .map(|i| unsafe { &*list.accounts.add(i) })
+            .collect()
+    }
+
+    #[test]
+    fn lists_program_accounts_with_filters() {
+        let program = Pubkey::new_unique();
+        let players = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let params = Arc::new(Mutex::new(Value::Null));
+        let seen = Arc::clone(&params);
+        let rpc = MockRpc::start(move |method, request| match method {
+            "getProgramAccounts" => {
+                *seen.lock().unwrap() = request.clone();
+                Ok(json!([
+                    keyed_account(&players[0], &program, 10, &[1, 2, 3]),
+                    keyed_account(&players[1], &program, 20, &[]),
+                ]))
+            }
+            _ => Err(format!("mock does not implement {}", method)),
+        });
+        let client = new_sol_client(rpc.url.as_ptr());
+        let program_id = SolPublicKey::new(program);
+
+        let owner = [7u8; 4];
+        let filters = [
+            SolAccountFilter {
+                kind: SolAccountFilterKind::Memcmp as u32,
+                offset: 8,
+                bytes: owner.as_ptr(),
+                bytes_len: owner.len(),
+                data_size: 0,
+            },
+            SolAccountFilter {
+                kind: SolAccountFilterKind::DataSize as u32,
+                offset: 0,
+                bytes: std::ptr::null(),
+                bytes_len: 0,
+                data_size: 48,
+            },
+        ];
+        let mut config = sol_program_accounts_config_default();
+        config.filters = filters.as_ptr();
+        config.filter_count = filters.len();
+        config.has_data_slice = true;
+        config.data_slice_offset = 8;
+        config.data_slice_length = 16;
+
+        let list = sol_get_program_accounts(client, &program_id, &config);
+        let accounts = list_accounts(list);
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].pubkey.to_pubkey(), players[0]);
+        assert_eq!(accounts[0].owner.to_pubkey(), program);
+        assert_eq!(accounts[0].lamports, 10);
+        assert_eq!(
+            unsafe { std::slice::from_raw_parts(accounts[0].data, accounts[0].data_len) },
+            [1, 2, 3]
+        );
+        assert!(accounts[0].json.is_null());
+        assert_eq!(accounts[1].data_len, 0);
+        free_program_account_list(list);
+
+        let params = params.lock().unwrap().clone();
+        assert_eq!(params[0], json!(program.to_string()));
+        let sent = &params[1];
+        assert_eq!(sent["encoding"], json!("base64"));
+        assert_eq!(sent["dataSlice"], json!({"offset": 8, "length": 16}));
+        assert_eq!(sent["filters"][0]["memcmp"]["offset"], json!(8));
+        assert_eq!(sent["filters"][1], json!({"dataSize": 48}));
+
+        // NULL config lists every account
+        let list = sol_get_program_accounts(client, &program_id, std::ptr::null());
+        assert_eq!(list_accounts(list).len(), 2);
+        free_program_account_list(list);
+
+        let bad = [SolAccountFilter {
+            kind: SolAccountFilterKind::Memcmp as u32,
+            offset: 0,
+            bytes: std::ptr::null(),
+            bytes_len: 4,
+            data_size: 0,
+        }];
+        config.filters = bad.as_ptr();
+        config.filter_count = 1;
+        assert!(sol_get_program_accounts(client, &program_id, &config).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        // Unknown filter kinds and encodings
+        let unknown = [SolAccountFilter {
+            kind: 2,
+            offset: 0,
+            bytes: std::ptr::null(),
+            bytes_len: 0,
+            data_size: 0,
+        }];
+        config.filters = unknown.as_ptr();
+        assert!(sol_get_program_accounts(client, &program_id, &config).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        config.filter_count = 0;
+        config.encoding = 3;
+        assert!(sol_get_program_accounts(client, &program_id, &config).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        free_client(client);
+    }
+
+    // A `getAccountInfo`/`getMultipleAccounts` account
+    fn ui_account(owner: &Pubkey, lamports: u64, data: &[u8]) -> Value {
+        keyed_account(&Pubkey::default(), owner, lamports, data)["account"].clone()
+    }
+
+    #[test]
+    fn gets_account_info_and_tells_missing_accounts_apart() {
+        let owner = Pubkey::new_unique();
+        let existing = Pubkey::new_unique();
+        let (mut wallet, mut mint) = (SolPublicKey::new(Pubkey::new_unique()), SolKeyPair::new());
+        let broken = get_associated_token_address(&wallet.to_pubkey(), &mint.get_pubkey());
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&batches);
+        let rpc = MockRpc::start(move |method, params| {
+            let account = |key: &Value| {
+                (key == &json!(existing.to_string())).then(|| ui_account(&owner, 5, &[1, 2]))
+            };
+            match method {
+                "getAccountInfo" if params[0] == json!(broken.to_string()) => {
+                    Err("node is behind".to_string())
+                }
+                "getAccountInfo" => Ok(with_context(json!(account(&params[0])))),
+                "getMultipleAccounts" => {
+                    let keys = params[0].as_array().unwrap();
+                    seen.lock().unwrap().push(keys.len());
+                    Ok(with_context(json!(keys
+                        .iter()
+                        .map(account)
+                        .collect::<Vec<_>>())))
+                }
+                _ => Err(format!("mock does not implement {}", method)),
+            }
+        });
+        let client = new_sol_client(rpc.url.as_ptr());
+
+        let mut info = SolAccountInfo::empty();
+        let pubkey = SolPublicKey::new(existing);
+        assert_eq!(
+            sol_get_account_info(client, &pubkey, &mut info),
+            SolAccountStatus::Found
+        );
+        assert_eq!(info.lamports, 5);
+        assert_eq!(info.owner.to_pubkey(), owner);
+        assert!(!info.executable);
+        assert_eq!(
+            unsafe { std::slice::from_raw_parts(info.data, info.data_len) },
+            [1, 2]
+        );
+        free_account_info(&mut info);
+        assert!(info.data.is_null());
+
+        let missing = SolPublicKey::new(Pubkey::new_unique());
+        assert_eq!(
+            sol_get_account_info(client, &missing, &mut info),
+            SolAccountStatus::NotFound
+        );
+        assert_eq!(sol_last_error_code(), SolErrorCode::Ok);
+        let broken = SolPublicKey::new(broken);
+        assert_eq!(
+            sol_get_account_info(client, &broken, &mut info),
+            SolAccountStatus::Error
+        );
+        assert_eq!(sol_last_error_code(), SolErrorCode::RpcServer);
+        // Nor is a failed lookup taken for a missing token account to create
+        let mut payer = SolKeyPair::new();
+        assert!(
+            get_or_create_associated_token_account(client, &mut payer, &mut wallet, &mut mint)
+                .is_null()
+        );
+        match crate::error::last_error() {
+            Some(SolError::RpcServer { message, .. }) => {
+                assert!(message.contains("node is behind"))
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        // Batches are split into requests of 100 accounts
+        let mut pubkeys: Vec<SolPublicKey> = (0..150)
+            .map(|_| SolPublicKey::new(Pubkey::new_unique()))
+            .collect();
+        pubkeys[120] = SolPublicKey::new(existing);
+        let mut infos: Vec<SolAccountInfo> = (0..150).map(|_| SolAccountInfo::empty()).collect();
+        let mut found = vec![true; 150];
+        assert!(sol_get_multiple_accounts(
+            client,
+            pubkeys.as_ptr(),
+            pubkeys.len(),
+            infos.as_mut_ptr(),
+            found.as_mut_ptr(),
+        ));
+        assert_eq!(*batches.lock().unwrap(), [100, 50]);
+        assert_eq!(found.iter().filter(|&&found| found).count(), 1);
+        assert!(found[120]);
+        assert_eq!(infos[120].lamports, 5);
+        assert!(infos[0].data.is_null() && infos[0].lamports == 0);
+        for info in &mut infos {
+            free_account_info(info);
+        }
+
+        free_client(client);
+    }
+}
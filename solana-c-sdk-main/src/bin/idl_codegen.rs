@@ -0,0 +1,59 @@
+// Generate a typed C header from an Anchor IDL:
+//
+//   idl_codegen <idl.json> [output.h] [--name <prefix>]
+//
+// The header is written to stdout when no output path is given. `--name`
+// overrides the identifier prefix, which defaults to the IDL's program name.
+
+use std::process::ExitCode;
+
+fn usage() -> ExitCode {
+    eprintln!("usage: idl_codegen <idl.json> [output.h] [--name <prefix>]");
+    ExitCode::FAILURE
+}
+
+fn main() -> ExitCode {
+    let mut paths = Vec::new();
+    let mut name = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => match args.next() {
+                Some(value) => name = Some(value),
+                None => return usage(),
+            },
+            "-h" | "--help" => return usage(),
+            _ => paths.push(arg),
+        }
+    }
+    let (input, output) = match paths.as_slice() {
+        [input] => (input, None),
+        [input, output] => (input, Some(output)),
+        _ => return usage(),
+    };
+
+    let idl = match std::fs::read_to_string(input) {
+        Ok(idl) => idl,
+        Err(err) => {
+            eprintln!("failed to read {}: {}", input, err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let header = match solana_c_sdk::generate_c_header(&idl, name.as_deref()) {
+        Ok(header) => header,
+        Err(err) => {
+            eprintln!("{}: {}", input, err);
+            return ExitCode::FAILURE;
+        }
+    };
+    match output {
+        Some(output) => {
+            if let Err(err) = std::fs::write(output, header) {
+                eprintln!("failed to write {}: {}", output, err);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{}", header),
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,723 @@
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    hash::Hash,
+    message::VersionedMessage,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::VersionedTransaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use std::ffi::{c_char, CString};
+
+use crate::{
+    client::SolClient,
+    confirm::{write_outcome, SolConfirmation},
+    error::{ffi_call, ffi_guard, mut_arg, ref_arg, slice_arg, str_arg, SolError},
+    lookup_table::{compile_message, sign_message},
+    smart_contract::{anchor_instruction, SolAccountMeta},
+    wallet::{SolKeyPair, SolPublicKey},
+};
+
+// SPL Memo program (v2)
+const MEMO_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+// Collects instructions into a single atomic transaction. Opaque to C: create
+// it with `sol_tx_builder_new`, release it with `sol_tx_builder_free`.
+#[derive(Default)]
+pub struct SolTransactionBuilder {
+    instructions: Vec<Instruction>,
+    fee_payer: Option<Pubkey>,
+    compute_unit_limit: Option<u32>,
+    compute_unit_price: Option<u64>,
+    // Address lookup tables of a v0 transaction; legacy without any
+    lookup_tables: Vec<Pubkey>,
+    // Nonce account and its authority, signing over the stored nonce instead of
+    // a recent blockhash
+    nonce_account: Option<(Pubkey, Pubkey)>,
+    // Set by `sol_tx_builder_sign`, cleared whenever the builder changes
+    signed: Option<VersionedTransaction>,
+}
+
+impl SolTransactionBuilder {
+    fn push(&mut self, instruction: Instruction) {
+        self.instructions.push(instruction);
+        self.signed = None;
+    }
+
+    // Compute budget instructions come first, as the runtime expects, after
+    // only the nonce advance, which must be the very first instruction
+    pub(crate) fn build_instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::with_capacity(self.instructions.len() + 3);
+        if let Some((nonce_account, authority)) = &self.nonce_account {
+            instructions.push(system_instruction::advance_nonce_account(
+                nonce_account,
+                authority,
+            ));
+        }
+        if let Some(units) = self.compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if let Some(micro_lamports) = self.compute_unit_price {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+                micro_lamports,
+            ));
+        }
+        instructions.extend(self.instructions.iter().cloned());
+        instructions
+    }
+
+    // Sign with `signers` over the latest blockhash, or the stored nonce of the
+    // nonce account. The fee payer defaults to the first signer.
+    pub(crate) fn sign(
+        &mut self,
+        client: &SolClient,
+        signers: &[Keypair],
+    ) -> Result<&VersionedTransaction, SolError> {
+        if self.instructions.is_empty() {
+            return Err(SolError::InvalidInput(
+                "transaction has no instructions".to_string(),
+            ));
+        }
+        let fee_payer = match (self.fee_payer, signers.first()) {
+            (Some(fee_payer), _) => fee_payer,
+            (None, Some(first)) => first.pubkey(),
+            (None, None) => {
+                return Err(SolError::InvalidInput(
+                    "at least one signer (payer) is required".to_string(),
+                ))
+            }
+        };
+
+        let tables = client.fetch_lookup_tables(&self.lookup_tables)?;
+        let blockhash = match &self.nonce_account {
+            Some((nonce_account, authority)) => {
+                let nonce = client.fetch_nonce(nonce_account)?;
+                if nonce.authority != *authority {
+                    return Err(SolError::InvalidInput(format!(
+                        "nonce authority of {} is {}, not {}",
+                        nonce_account, nonce.authority, authority
+                    )));
+                }
+                nonce.blockhash()
+            }
+            None => client.rpc(|rpc| rpc.get_latest_blockhash())?,
+        };
+        let signer_refs: Vec<&Keypair> = signers.iter().collect();
+        let transaction = sign_message(
+            &self.build_instructions(),
+            &fee_payer,
+            &signer_refs,
+            &tables,
+            blockhash,
+        )?;
+        Ok(self.signed.insert(transaction))
+    }
+
+    // The message to sign: that of the signed transaction, or else one for the
+    // fee payer set with `sol_tx_builder_set_fee_payer`, with no blockhash yet
+    pub(crate) fn message(&self, client: &SolClient) -> Result<VersionedMessage, SolError> {
+        if let Some(transaction) = &self.signed {
+            return Ok(transaction.message.clone());
+        }
+        if self.instructions.is_empty() {
+            return Err(SolError::InvalidInput(
+                "transaction has no instructions".to_string(),
+            ));
+        }
+        let fee_payer = self.fee_payer.ok_or_else(|| {
+            SolError::InvalidInput(
+                "no fee payer; call sol_tx_builder_set_fee_payer or sign first".to_string(),
+            )
+        })?;
+        let tables = client.fetch_lookup_tables(&self.lookup_tables)?;
+        compile_message(&self.build_instructions(), &fee_payer, &tables, Hash::default())
+    }
+
+    // The signed transaction, or else one for `message` with placeholder
+    // signatures, which is only good for simulating
+    pub(crate) fn transaction(&self, client: &SolClient) -> Result<VersionedTransaction, SolError> {
+        if let Some(transaction) = &self.signed {
+            return Ok(transaction.clone());
+        }
+        let message = self.message(client)?;
+        let signatures = message.header().num_required_signatures as usize;
+        Ok(VersionedTransaction {
+            signatures: vec![Signature::default(); signatures],
+            message,
+        })
+    }
+
+    pub(crate) fn signed(&self) -> Result<&VersionedTransaction, SolError> {
+        self.signed.as_ref().ok_or_else(|| {
+            SolError::InvalidInput(
+                "transaction is not signed; call sol_tx_builder_sign first".to_string(),
+            )
+        })
+    }
+}
+
+// Copy the metas of a C account array
+// Safety: see `slice_arg`.
+unsafe fn account_metas(
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+) -> Result<Vec<AccountMeta>, SolError> {
+    Ok(slice_arg(accounts, account_count, "accounts")?
+        .iter()
+        .map(SolAccountMeta::to_account_meta)
+        .collect())
+}
+
+// ==================== Lifecycle ==================== //
+
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_new() -> *mut SolTransactionBuilder {
+    ffi_call(std::ptr::null_mut(), || Ok(Box::into_raw(Box::default())))
+}
+
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_free(builder: *mut SolTransactionBuilder) {
+    ffi_guard((), || {
+        if builder.is_null() {
+            return;
+        }
+        unsafe {
+            drop(Box::from_raw(builder));
+        }
+    })
+}
+
+// Number of instructions added so far, not counting compute budget settings
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_instruction_count(builder: *const SolTransactionBuilder) -> usize {
+    ffi_guard(0, || {
+        unsafe { builder.as_ref() }.map_or(0, |builder| builder.instructions.len())
+    })
+}
+
+// Account paying the fee. Defaults to the first signer passed to `sol_tx_builder_sign`.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_set_fee_payer(
+    builder: *mut SolTransactionBuilder,
+    fee_payer: *const SolPublicKey,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        let fee_payer = unsafe { ref_arg(fee_payer, "fee_payer")? };
+        builder.fee_payer = Some(fee_payer.to_pubkey());
+        builder.signed = None;
+        Ok(true)
+    })
+}
+
+// ==================== Instructions ==================== //
+
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_add_transfer(
+    builder: *mut SolTransactionBuilder,
+    from: *const SolPublicKey,
+    to: *const SolPublicKey,
+    lamports: u64,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        let from = unsafe { ref_arg(from, "from")? }.to_pubkey();
+        let to = unsafe { ref_arg(to, "to")? }.to_pubkey();
+        builder.push(system_instruction::transfer(&from, &to, lamports));
+        Ok(true)
+    })
+}
+
+// Transfer `amount` base units of `mint` between the associated token accounts
+// of `owner` and `recipient`. The recipient's account must already exist or be
+// created earlier in the same transaction with `sol_tx_builder_add_create_ata`.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_add_spl_transfer(
+    builder: *mut SolTransactionBuilder,
+    owner: *const SolPublicKey,
+    recipient: *const SolPublicKey,
+    mint: *const SolPublicKey,
+    amount: u64,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        let owner = unsafe { ref_arg(owner, "owner")? }.to_pubkey();
+        let recipient = unsafe { ref_arg(recipient, "recipient")? }.to_pubkey();
+        let mint = unsafe { ref_arg(mint, "mint")? }.to_pubkey();
+
+        let instruction = spl_token::instruction::transfer(
+            &spl_token::id(),
+            &get_associated_token_address(&owner, &mint),
+            &get_associated_token_address(&recipient, &mint),
+            &owner,
+            &[&owner],
+            amount,
+        )?;
+        builder.push(instruction);
+        Ok(true)
+    })
+}
+
+// Create the associated token account of `owner` for `mint`, paid by `payer`.
+// Idempotent: does nothing on-chain if the account already exists.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_add_create_ata(
+    builder: *mut SolTransactionBuilder,
+    payer: *const SolPublicKey,
+    owner: *const SolPublicKey,
+    mint: *const SolPublicKey,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        let payer = unsafe { ref_arg(payer, "payer")? }.to_pubkey();
+        let owner = unsafe { ref_arg(owner, "owner")? }.to_pubkey();
+        let mint = unsafe { ref_arg(mint, "mint")? }.to_pubkey();
+        builder.push(create_associated_token_account_idempotent(
+            &payer,
+            &owner,
+            &mint,
+            &spl_token::id(),
+        ));
+        Ok(true)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_add_memo(
+    builder: *mut SolTransactionBuilder,
+    memo: *const c_char,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        let memo = unsafe { str_arg(memo, "memo")? };
+        builder.push(Instruction::new_with_bytes(
+            MEMO_PROGRAM_ID,
+            memo.as_bytes(),
+            vec![],
+        ));
+        Ok(true)
+    })
+}
+
+// Compute unit limit of the whole transaction (0 clears it and uses the runtime default)
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_set_compute_unit_limit(
+    builder: *mut SolTransactionBuilder,
+    units: u32,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        builder.compute_unit_limit = (units > 0).then_some(units);
+        builder.signed = None;
+        Ok(true)
+    })
+}
+
+// Priority fee in micro-lamports per compute unit (0 clears it)
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_set_compute_unit_price(
+    builder: *mut SolTransactionBuilder,
+    micro_lamports: u64,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        builder.compute_unit_price = (micro_lamports > 0).then_some(micro_lamports);
+        builder.signed = None;
+        Ok(true)
+    })
+}
+
+// Make the transaction a v0 transaction that reads its accounts from the
+// address lookup table `table` where it can. Signers and programs always stay
+// in the message. The table is fetched when the transaction is signed.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_add_lookup_table(
+    builder: *mut SolTransactionBuilder,
+    table: *const SolPublicKey,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        let table = unsafe { ref_arg(table, "table")? }.to_pubkey();
+        if !builder.lookup_tables.contains(&table) {
+            builder.lookup_tables.push(table);
+        }
+        builder.signed = None;
+        Ok(true)
+    })
+}
+
+// Sign over the stored nonce of `nonce_account` instead of a recent blockhash,
+// so the signed transaction stays valid until the nonce is advanced rather than
+// for about a minute. An instruction advancing the nonce, signed by
+// `authority`, is put first. NULL `nonce_account` goes back to recent blockhashes.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_set_nonce_account(
+    builder: *mut SolTransactionBuilder,
+    nonce_account: *const SolPublicKey,
+    authority: *const SolPublicKey,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        builder.nonce_account = match unsafe { nonce_account.as_ref() } {
+            Some(nonce_account) => Some((
+                nonce_account.to_pubkey(),
+                unsafe { ref_arg(authority, "authority")? }.to_pubkey(),
+            )),
+            None => None,
+        };
+        builder.signed = None;
+        Ok(true)
+    })
+}
+
+// Any instruction: `data` is passed to the program unchanged
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_add_instruction(
+    builder: *mut SolTransactionBuilder,
+    program_id: *const SolPublicKey,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        let program_id = unsafe { ref_arg(program_id, "program_id")? }.to_pubkey();
+        let accounts = unsafe { account_metas(accounts, account_count)? };
+        let data = unsafe { slice_arg(data_ptr, data_len, "data")? };
+        builder.push(Instruction::new_with_bytes(program_id, data, accounts));
+        Ok(true)
+    })
+}
+
+// Anchor method call: `data` (the Borsh-encoded arguments) is prefixed with the
+// discriminator of `method_name`
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_add_anchor_instruction(
+    builder: *mut SolTransactionBuilder,
+    program_id: *const SolPublicKey,
+    method_name: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        let program_id = unsafe { ref_arg(program_id, "program_id")? }.to_pubkey();
+        let method_name = unsafe { str_arg(method_name, "method name")? };
+        let accounts = unsafe { account_metas(accounts, account_count)? };
+        let data = unsafe { slice_arg(data_ptr, data_len, "data")? }.to_vec();
+        builder.push(anchor_instruction(&program_id, method_name, accounts, data));
+        Ok(true)
+    })
+}
+
+// ==================== Sign / Send ==================== //
+
+// Sign the transaction over the latest blockhash, or the nonce set with
+// `sol_tx_builder_set_nonce_account`. Every account marked as a signer, and
+// the fee payer, must be among `signers`.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_sign(
+    builder: *mut SolTransactionBuilder,
+    client: *mut SolClient,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { mut_arg(builder, "builder")? };
+        let client = unsafe { ref_arg(client, "client")? };
+        let signers = unsafe { slice_arg(signers, signer_count, "signers")? }
+            .iter()
+            .map(|s| unsafe { ref_arg(*s, "signer")? }.to_keypair())
+            .collect::<Result<Vec<Keypair>, SolError>>()?;
+
+        builder.sign(client, &signers)?;
+        Ok(true)
+    })
+}
+
+// Send the signed transaction and wait for confirmation. Returns its signature
+// (free with `free_transaction_signature`), or NULL on failure. `outcome` is optional.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_send(
+    builder: *const SolTransactionBuilder,
+    client: *mut SolClient,
+    outcome: *mut SolConfirmation,
+) -> *mut c_char {
+    let (signature, slot) = ffi_call((std::ptr::null_mut(), None), || {
+        let builder = unsafe { ref_arg(builder, "builder")? };
+        let client = unsafe { ref_arg(client, "client")? };
+
+        let (signature, slot) = client.send_and_confirm(builder.signed()?)?;
+        log::info!(
+            "Sent transaction with {} instructions. Signature: {}",
+            builder.instructions.len(),
+            signature
+        );
+        Ok((CString::new(signature.to_string())?.into_raw(), Some(slot)))
+    });
+    write_outcome(outcome, slot);
+    signature
+}
+
+// Wire format of the signed transaction, e.g. to send it later or elsewhere.
+// Free with `free_serialized_transaction`.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_serialize(
+    builder: *const SolTransactionBuilder,
+    out_len: *mut usize,
+) -> *mut u8 {
+    ffi_call(std::ptr::null_mut(), || {
+        let builder = unsafe { ref_arg(builder, "builder")? };
+        let out_len = unsafe { mut_arg(out_len, "out_len")? };
+
+        let bytes = bincode::serialize(builder.signed()?).map_err(|err| {
+            SolError::Internal(format!("failed to serialize transaction: {}", err))
+        })?;
+        *out_len = bytes.len();
+        Ok(Box::into_raw(bytes.into_boxed_slice()) as *mut u8)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn free_serialized_transaction(bytes: *mut u8, len: usize) {
+    ffi_guard((), || {
+        if bytes.is_null() {
+            return;
+        }
+        unsafe {
+            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                bytes, len,
+            )));
+        }
+    })
+}
+
+// Send a transaction from `sol_tx_builder_serialize`, possibly signed
+// elsewhere or long before, and wait for confirmation. Returns its signature
+// (free with `free_transaction_signature`), or NULL on failure. `outcome` is optional.
+#[no_mangle]
+pub extern "C" fn sol_send_serialized_transaction(
+    client: *mut SolClient,
+    bytes: *const u8,
+    len: usize,
+    outcome: *mut SolConfirmation,
+) -> *mut c_char {
+    let (signature, slot) = ffi_call((std::ptr::null_mut(), None), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let bytes = unsafe { slice_arg(bytes, len, "bytes")? };
+
+        let transaction: VersionedTransaction = bincode::deserialize(bytes).map_err(|err| {
+            SolError::InvalidInput(format!("not a serialized transaction: {}", err))
+        })?;
+        // A nonce transaction is only checked against its nonce, not the blockhash expiry
+        let (signature, slot) = client.send_and_confirm(&transaction)?;
+        log::info!("Sent serialized transaction. Signature: {}", signature);
+        Ok((CString::new(signature.to_string())?.into_raw(), Some(slot)))
+    });
+    write_outcome(outcome, slot);
+    signature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::new_sol_client;
+    use crate::confirm::SolConfirmationStatus;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use crate::mock_rpc::{sent_signature, transaction_handler, MockRpc};
+    use crate::smart_contract::free_client;
+    use crate::token::free_transaction_signature;
+    use solana_sdk::{compute_budget, system_program, transaction::Transaction};
+    use std::ffi::CStr;
+    use std::sync::{Arc, Mutex};
+
+    fn pubkey(pubkey: &Pubkey) -> SolPublicKey {
+        SolPublicKey::new(*pubkey)
+    }
+
+    #[test]
+    fn batched_transfers_sign_and_serialize() {
+        let rpc = MockRpc::start(transaction_handler);
+        let client = new_sol_client(rpc.url.as_ptr());
+        let mut payer = SolKeyPair::new();
+        let payer_key = pubkey(&payer.get_pubkey());
+        let recipients: Vec<SolPublicKey> = (0..3).map(|_| pubkey(&Pubkey::new_unique())).collect();
+
+        let builder = sol_tx_builder_new();
+        assert!(sol_tx_builder_set_compute_unit_limit(builder, 50_000));
+        assert!(sol_tx_builder_set_compute_unit_price(builder, 10));
+        for (i, recipient) in recipients.iter().enumerate() {
+            assert!(sol_tx_builder_add_transfer(
+                builder,
+                &payer_key,
+                recipient,
+                1_000 * (i as u64 + 1)
+            ));
+        }
+        assert!(sol_tx_builder_add_memo(
+            builder,
+            c"round 7 rewards".as_ptr()
+        ));
+        assert_eq!(sol_tx_builder_instruction_count(builder), 4);
+
+        let signers = [&mut payer as *mut SolKeyPair];
+        assert!(sol_tx_builder_sign(
+            builder,
+            client,
+            signers.as_ptr(),
+            signers.len()
+        ));
+
+        let mut len = 0;
+        let bytes = sol_tx_builder_serialize(builder, &mut len);
+        assert!(!bytes.is_null());
+        let transaction: Transaction =
+            bincode::deserialize(unsafe { std::slice::from_raw_parts(bytes, len) }).unwrap();
+        free_serialized_transaction(bytes, len);
+
+        transaction.verify().unwrap();
+        assert_eq!(transaction.message.account_keys[0], payer.get_pubkey());
+        let programs: Vec<Pubkey> = transaction
+            .message
+            .instructions
+            .iter()
+            .map(|ix| *ix.program_id(&transaction.message.account_keys))
+            .collect();
+        assert_eq!(
+            programs,
+            [
+                compute_budget::id(),
+                compute_budget::id(),
+                system_program::id(),
+                system_program::id(),
+                system_program::id(),
+                MEMO_PROGRAM_ID,
+            ]
+        );
+
+        sol_tx_builder_free(builder);
+        free_client(client);
+    }
+
+    #[test]
+    fn send_submits_the_signed_transaction() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&sent);
+        let rpc = MockRpc::start(move |method, params| {
+            if method == "sendTransaction" {
+                recorded.lock().unwrap().push(sent_signature(params));
+            }
+            transaction_handler(method, params)
+        });
+        let client = new_sol_client(rpc.url.as_ptr());
+        let mut payer = SolKeyPair::new();
+        let mut authority = SolKeyPair::new();
+        let program_id = pubkey(&Pubkey::new_unique());
+        let accounts = [SolAccountMeta {
+            pubkey: pubkey(&authority.get_pubkey()),
+            is_signer: true,
+            is_writable: false,
+        }];
+
+        let builder = sol_tx_builder_new();
+        assert!(sol_tx_builder_set_fee_payer(
+            builder,
+            &pubkey(&payer.get_pubkey())
+        ));
+        assert!(sol_tx_builder_add_anchor_instruction(
+            builder,
+            &program_id,
+            c"increment".as_ptr(),
+            accounts.as_ptr(),
+            accounts.len(),
+            [1u8, 2].as_ptr(),
+            2,
+        ));
+
+        // Sending before signing is rejected
+        assert!(sol_tx_builder_send(builder, client, std::ptr::null_mut()).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        // The authority must sign as well as the fee payer
+        let signers = [&mut payer as *mut SolKeyPair];
+        assert!(!sol_tx_builder_sign(
+            builder,
+            client,
+            signers.as_ptr(),
+            signers.len()
+        ));
+        assert_eq!(sol_last_error_code(), SolErrorCode::Signing);
+
+        let signers = [
+            &mut authority as *mut SolKeyPair,
+            &mut payer as *mut SolKeyPair,
+        ];
+        assert!(sol_tx_builder_sign(
+            builder,
+            client,
+            signers.as_ptr(),
+            signers.len()
+        ));
+        let mut outcome = SolConfirmation {
+            status: SolConfirmationStatus::Failed,
+            slot: 0,
+            error_code: SolErrorCode::Internal,
+            program_error_code: 0,
+        };
+        let signature = sol_tx_builder_send(builder, client, &mut outcome);
+        assert!(!signature.is_null());
+        assert_eq!(outcome.status, SolConfirmationStatus::Confirmed);
+        let signature_str = unsafe { CStr::from_ptr(signature) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            sent.lock()
+                .unwrap()
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>(),
+            [signature_str]
+        );
+        free_transaction_signature(signature);
+
+        // Changing the builder drops the signature
+        assert!(sol_tx_builder_add_memo(builder, c"late".as_ptr()));
+        assert!(sol_tx_builder_send(builder, client, std::ptr::null_mut()).is_null());
+
+        sol_tx_builder_free(builder);
+        free_client(client);
+    }
+
+    #[test]
+    fn empty_builder_cannot_be_signed() {
+        let client = new_sol_client(c"http://127.0.0.1:1".as_ptr());
+        let mut payer = SolKeyPair::new();
+        let signers = [&mut payer as *mut SolKeyPair];
+        let builder = sol_tx_builder_new();
+        assert!(!sol_tx_builder_sign(
+            builder,
+            client,
+            signers.as_ptr(),
+            signers.len()
+        ));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        assert!(!sol_tx_builder_add_transfer(
+            std::ptr::null_mut(),
+            &payer.pubkey,
+            &payer.pubkey,
+            1
+        ));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        sol_tx_builder_free(builder);
+        free_client(client);
+    }
+}
@@ -1,66 +1,324 @@
-use std::ffi::{c_char, CStr};
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
+use std::ffi::c_char;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use solana_client::{
+    client_error::{ClientErrorKind, Result as ClientResult},
+    rpc_client::RpcClient,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use tokio::runtime::Runtime;
 
+use crate::confirm::ProgressHook;
+use crate::error::{ffi_call, ref_arg, str_arg, SolError};
+use crate::fees::SolComputeBudget;
 use crate::wallet::SolPublicKey;
 
+// Commitment levels. C passes them as `uint32_t`, so unknown values are
+// rejected instead of becoming an invalid enum.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolCommitment {
+    Processed = 0,
+    Confirmed = 1,
+    Finalized = 2,
+}
+
+impl TryFrom<u32> for SolCommitment {
+    type Error = SolError;
+
+    fn try_from(value: u32) -> Result<Self, SolError> {
+        match value {
+            0 => Ok(SolCommitment::Processed),
+            1 => Ok(SolCommitment::Confirmed),
+            2 => Ok(SolCommitment::Finalized),
+            _ => Err(SolError::InvalidInput(format!("unknown commitment {}", value))),
+        }
+    }
+}
+
+impl SolCommitment {
+    pub fn to_commitment_config(self) -> CommitmentConfig {
+        match self {
+            SolCommitment::Processed => CommitmentConfig::processed(),
+            SolCommitment::Confirmed => CommitmentConfig::confirmed(),
+            SolCommitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+
+    // `getTransaction` rejects `processed`, so transaction lookups read it as `confirmed`
+    pub fn to_transaction_commitment_config(self) -> CommitmentConfig {
+        match self {
+            SolCommitment::Processed => CommitmentConfig::confirmed(),
+            commitment => commitment.to_commitment_config(),
+        }
+    }
+}
+
+// Connection settings for `new_sol_client_with_config`.
+// Start from `sol_client_config_default()`; a zero timeout means "use the default".
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SolClientConfig {
+    pub commitment: u32,                     // SolCommitment for account/balance queries and sends
+    pub transaction_commitment: u32,         // SolCommitment for transaction lookups (Processed is read as Confirmed)
+    pub request_timeout_ms: u64,             // HTTP timeout of a single RPC request
+    pub confirm_transaction_timeout_ms: u64, // How long to wait for a sent transaction to be confirmed (0 = 60 s)
+    pub max_retries: u32,                    // Retries after a transport failure (0 = no retry)
+    pub retry_backoff_ms: u64,               // Delay before the first retry, doubled each attempt
+    pub confirm_poll_interval_ms: u64,       // Delay between signature status polls (0 = 500 ms)
+    pub simulate_before_send: bool,          // Simulate every transaction first and fail with the simulation error instead of sending it
+    pub compute_budget: SolComputeBudget,    // Compute unit limit and price of the SDK's transfers, mints and program calls (zeroed = none)
+}
+
+const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 30_000;
+const DEFAULT_RETRY_BACKOFF_MS: u64 = 500;
+
+impl Default for SolClientConfig {
+    // Matches the behaviour of a plain `new_sol_client`
+    fn default() -> Self {
+        SolClientConfig {
+            commitment: SolCommitment::Finalized as u32,
+            transaction_commitment: SolCommitment::Confirmed as u32,
+            request_timeout_ms: DEFAULT_REQUEST_TIMEOUT_MS,
+            confirm_transaction_timeout_ms: 0,
+            max_retries: 0,
+            retry_backoff_ms: DEFAULT_RETRY_BACKOFF_MS,
+            confirm_poll_interval_ms: 0,
+            simulate_before_send: false,
+            compute_budget: SolComputeBudget::default(),
+        }
+    }
+}
+
 pub struct SolClient {
-    pub rpc_client: RpcClient,
+    pub rpc_client: Arc<RpcClient>,
+    pub config: SolClientConfig,
+    pub confirmation_progress: Option<ProgressHook>,
+    transaction_commitment: CommitmentConfig,
+    // Runs `_async` jobs; created on first use so blocking-only callers never pay for it
+    runtime: OnceLock<Runtime>,
+}
+
+impl SolClient {
+    // Fails if either commitment of `config` is not a `SolCommitment`
+    pub fn new(url: String, config: SolClientConfig) -> Result<Self, SolError> {
+        let timeout = Duration::from_millis(match config.request_timeout_ms {
+            0 => DEFAULT_REQUEST_TIMEOUT_MS,
+            ms => ms,
+        });
+        let commitment = SolCommitment::try_from(config.commitment)?.to_commitment_config();
+        let transaction_commitment = SolCommitment::try_from(config.transaction_commitment)?
+            .to_transaction_commitment_config();
+        let rpc_client = RpcClient::new_with_timeout_and_commitment(url, timeout, commitment);
+        Ok(SolClient {
+            rpc_client: Arc::new(rpc_client),
+            config,
+            confirmation_progress: None,
+            transaction_commitment,
+            runtime: OnceLock::new(),
+        })
+    }
+
+    // A handle sharing this client's connection and settings, but not its runtime.
+    // Jobs run against one of these so they never own (and drop) the runtime they run on.
+    pub fn detached(&self) -> SolClient {
+        SolClient {
+            rpc_client: Arc::clone(&self.rpc_client),
+            config: self.config,
+            confirmation_progress: self.confirmation_progress,
+            transaction_commitment: self.transaction_commitment,
+            runtime: OnceLock::new(),
+        }
+    }
+
+    pub fn runtime(&self) -> Result<&Runtime, SolError> {
+        if let Some(runtime) = self.runtime.get() {
+            return Ok(runtime);
+        }
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("solana-sdk-job")
+            .enable_all()
+            .build()
+            .map_err(|err| SolError::Internal(format!("failed to start job runtime: {}", err)))?;
+        Ok(self.runtime.get_or_init(|| runtime))
+    }
+
+    pub fn transaction_commitment(&self) -> CommitmentConfig {
+        self.transaction_commitment
+    }
+
+    // Run an RPC call, retrying transport failures according to the client's retry policy
+    pub fn rpc<T>(&self, call: impl Fn(&RpcClient) -> ClientResult<T>) -> ClientResult<T> {
+        let mut backoff = Duration::from_millis(self.config.retry_backoff_ms);
+        let mut attempt = 0;
+        loop {
+            match call(&self.rpc_client) {
+                Err(err) if attempt < self.config.max_retries && is_transient(err.kind()) => {
+                    log::warn!("RPC request failed ({}), retrying in {:?}", err, backoff);
+                    std::thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Drop for SolClient {
+    fn drop(&mut self) {
+        // Don't block the caller of `free_client` on jobs still in flight;
+        // they finish in the background and their results stay pollable.
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+fn is_transient(kind: &ClientErrorKind) -> bool {
+    matches!(kind, ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_))
+}
+
+#[no_mangle]
+pub extern "C" fn sol_client_config_default() -> SolClientConfig {
+    SolClientConfig::default()
 }
 
 #[no_mangle]
 pub extern "C" fn new_sol_client(url: *const c_char) -> *mut SolClient {
-    // Convert the C string to a Rust string
-    let c_str = unsafe { CStr::from_ptr(url) };
-    let url_str = match c_str.to_str() {
-        Ok(str) => str,
-        Err(_) => return std::ptr::null_mut(),
-    };
+    ffi_call(std::ptr::null_mut(), || {
+        // Convert the C string to a Rust string
+        let url_str = unsafe { str_arg(url, "RPC URL")? };
+
+        // Create a new Solana client
+        let client = SolClient::new(url_str.to_string(), SolClientConfig::default())?;
+        Ok(Box::into_raw(Box::new(client)))
+    })
+}
 
-    // Create a new Solana client
-    let rpc_client = RpcClient::new(url_str.to_string());
-    let client = SolClient { rpc_client };
-    Box::into_raw(Box::new(client))
+#[no_mangle]
+pub extern "C" fn new_sol_client_with_config(
+    url: *const c_char,
+    config: *const SolClientConfig,
+) -> *mut SolClient {
+    ffi_call(std::ptr::null_mut(), || {
+        let url_str = unsafe { str_arg(url, "RPC URL")? };
+        let config = unsafe { ref_arg(config, "config")? };
+
+        let client = SolClient::new(url_str.to_string(), *config)?;
+        Ok(Box::into_raw(Box::new(client)))
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn get_balance(client: *mut SolClient, pubkey: *mut SolPublicKey) -> u64 {
-    let client = unsafe {
-        assert!(!client.is_null());
-        &mut *client
-    };
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let pubkey = unsafe { ref_arg(pubkey, "pubkey")? };
 
-    let pubkey = unsafe {
-        assert!(!pubkey.is_null());
-        &*pubkey
-    };
+        let pubkey = Pubkey::new_from_array(pubkey.data);
+        Ok(client.rpc(|rpc| rpc.get_balance(&pubkey))?)
+    })
+}
 
-    let pubkey = Pubkey::new_from_array(pubkey.data);
-    client.rpc_client.get_balance(&pubkey).unwrap_or(0)
+// Same as `get_balance`, but reads at the given commitment instead of the client's
+#[no_mangle]
+pub extern "C" fn get_balance_with_commitment(
+    client: *mut SolClient,
+    pubkey: *mut SolPublicKey,
+    commitment: u32,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let pubkey = unsafe { ref_arg(pubkey, "pubkey")? }.to_pubkey();
+
+        let commitment = SolCommitment::try_from(commitment)?.to_commitment_config();
+        let balance = client.rpc(|rpc| rpc.get_balance_with_commitment(&pubkey, commitment))?;
+        Ok(balance.value)
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn request_airdrop(client: *mut SolClient, pubkey: *mut SolPublicKey, lamports: u64) -> bool {
-    let client = unsafe {
-        assert!(!client.is_null());
-        &mut *client
-    };
-
-    let pubkey = unsafe {
-        assert!(!pubkey.is_null());
-        &*pubkey
-    };
-
-    let pubkey = Pubkey::new_from_array(pubkey.data);
-    println!("Requesting airdrop of {} lamports to pubkey: {:?}", lamports, pubkey);
-    match client.rpc_client.request_airdrop(&pubkey, lamports) {
-        Ok(signature) => {
-            println!("Airdrop requested successfully. Signature: {:?}", signature);
-            true
-        },
-        Err(err) => {
-            println!("Failed to request airdrop: {:?}", err);
-            false
-        }
+    ffi_call(false, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let pubkey = unsafe { ref_arg(pubkey, "pubkey")? };
+
+        let pubkey = Pubkey::new_from_array(pubkey.data);
+        log::debug!("Requesting airdrop of {} lamports to {}", lamports, pubkey);
+        let signature = client.rpc(|rpc| rpc.request_airdrop(&pubkey, lamports))?;
+        log::info!("Airdrop requested. Signature: {}", signature);
+        Ok(true)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use std::cell::Cell;
+    use solana_client::client_error::ClientError;
+
+    fn client_with_retries(max_retries: u32) -> SolClient {
+        let config = SolClientConfig {
+            max_retries,
+            retry_backoff_ms: 1,
+            ..SolClientConfig::default()
+        };
+        SolClient::new("http://127.0.0.1:1".to_string(), config).unwrap()
+    }
+
+    #[test]
+    fn transport_errors_are_retried_up_to_max_retries() {
+        let client = client_with_retries(3);
+        let calls = Cell::new(0);
+        let result: ClientResult<()> = client.rpc(|_| {
+            calls.set(calls.get() + 1);
+            Err(std::io::Error::other("connection reset").into())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 4);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn non_transport_errors_are_not_retried() {
+        let client = client_with_retries(3);
+        let calls = Cell::new(0);
+        let result: ClientResult<()> = client.rpc(|_| {
+            calls.set(calls.get() + 1);
+            Err(ClientError::from(ClientErrorKind::Custom("rejected".to_string())))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn config_sets_client_commitment() {
+        let config = SolClientConfig {
+            commitment: SolCommitment::Processed as u32,
+            transaction_commitment: SolCommitment::Processed as u32,
+            ..SolClientConfig::default()
+        };
+        let client = SolClient::new("http://127.0.0.1:1".to_string(), config).unwrap();
+        assert_eq!(client.rpc_client.commitment(), CommitmentConfig::processed());
+        assert_eq!(client.transaction_commitment(), CommitmentConfig::confirmed());
+    }
+
+    #[test]
+    fn unknown_commitments_are_rejected() {
+        let url = c"http://127.0.0.1:1";
+        let config = SolClientConfig {
+            transaction_commitment: 3,
+            ..SolClientConfig::default()
+        };
+        assert!(new_sol_client_with_config(url.as_ptr(), &config).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        let client = new_sol_client(url.as_ptr());
+        let mut pubkey = SolPublicKey::new(Pubkey::new_unique());
+        assert_eq!(get_balance_with_commitment(client, &mut pubkey, 7), 0);
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        crate::smart_contract::free_client(client);
+    }
+}
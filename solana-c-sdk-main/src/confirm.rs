@@ -0,0 +1,489 @@
+use std::ffi::{c_char, c_void, CString};
+use std::ptr::{null, null_mut};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use solana_client::rpc_client::SerializableTransaction;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status::TransactionConfirmationStatus;
+
+use crate::client::SolClient;
+use crate::error::{
+    ffi_call, mut_arg, ref_arg, slice_arg, sol_last_error_code, sol_last_error_program_code,
+    str_arg, SolError, SolErrorCode,
+};
+use crate::fees::SolComputeBudget;
+use crate::wallet::SolPublicKey;
+
+const DEFAULT_CONFIRM_TIMEOUT_MS: u64 = 60_000;
+const DEFAULT_POLL_INTERVAL_MS: u64 = 500;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolConfirmationStatus {
+    Confirmed = 0,
+    Failed = 1,
+    // Not confirmed in time; the transaction may still land
+    TimedOut = 2,
+    // The job was cancelled first; only passed to completion callbacks
+    Cancelled = 3,
+}
+
+// Outcome of waiting for a transaction
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SolConfirmation {
+    pub status: SolConfirmationStatus,
+    pub slot: u64,                // Slot the transaction was confirmed in, 0 otherwise
+    pub error_code: SolErrorCode, // `Ok` when confirmed
+    pub program_error_code: i64,  // Custom program error code, or -1
+}
+
+// Store the outcome of an exported call in its optional `outcome` out-parameter:
+// the slot if the transaction was confirmed, otherwise the last error.
+pub(crate) fn write_outcome(outcome: *mut SolConfirmation, confirmed_slot: Option<u64>) {
+    if outcome.is_null() {
+        return;
+    }
+    let confirmation = match confirmed_slot {
+        Some(slot) => SolConfirmation {
+            status: SolConfirmationStatus::Confirmed,
+            slot,
+            error_code: SolErrorCode::Ok,
+            program_error_code: -1,
+        },
+        None => {
+            let error_code = sol_last_error_code();
+            SolConfirmation {
+                status: match error_code {
+                    SolErrorCode::Timeout => SolConfirmationStatus::TimedOut,
+                    _ => SolConfirmationStatus::Failed,
+                },
+                slot: 0,
+                error_code,
+                program_error_code: sol_last_error_program_code(),
+            }
+        }
+    };
+    unsafe { *outcome = confirmation };
+}
+
+// Per-call options of the calls that send a transaction. NULL options, or a
+// zero-initialized struct, send as the client is configured.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SolSendOptions {
+    pub budget: *const SolComputeBudget, // Compute budget (NULL = the client's); also used for accounts created first
+    pub lookup_tables: *const SolPublicKey, // Address lookup tables; with any, the transaction is sent as v0
+    pub lookup_table_count: usize,
+    pub outcome: *mut SolConfirmation, // Filled with the outcome if not NULL; jobs report theirs instead
+}
+
+impl Default for SolSendOptions {
+    fn default() -> Self {
+        SolSendOptions {
+            budget: null(),
+            lookup_tables: null(),
+            lookup_table_count: 0,
+            outcome: null_mut(),
+        }
+    }
+}
+
+// `SolSendOptions` copied out of the caller's memory, with the client's defaults filled in
+#[derive(Clone, Debug)]
+pub(crate) struct SendOptions {
+    pub budget: SolComputeBudget,
+    pub lookup_tables: Vec<Pubkey>,
+}
+
+impl SendOptions {
+    // The client's defaults
+    pub fn of(client: &SolClient) -> Self {
+        SendOptions {
+            budget: client.config.compute_budget,
+            lookup_tables: Vec::new(),
+        }
+    }
+
+    // Safety: non-null pointers must be valid for the duration of the call.
+    pub unsafe fn from_arg(
+        client: &SolClient,
+        options: *const SolSendOptions,
+    ) -> Result<Self, SolError> {
+        let Some(options) = options.as_ref() else {
+            return Ok(Self::of(client));
+        };
+        let lookup_tables = slice_arg(
+            options.lookup_tables,
+            options.lookup_table_count,
+            "lookup_tables",
+        )?;
+        Ok(SendOptions {
+            budget: options
+                .budget
+                .as_ref()
+                .copied()
+                .unwrap_or(client.config.compute_budget),
+            lookup_tables: lookup_tables.iter().map(SolPublicKey::to_pubkey).collect(),
+        })
+    }
+}
+
+// The `outcome` out-parameter of optional `options`
+// Safety: `options` must be NULL or valid for the duration of the call.
+pub(crate) unsafe fn outcome_arg(options: *const SolSendOptions) -> *mut SolConfirmation {
+    options
+        .as_ref()
+        .map_or(null_mut(), |options| options.outcome)
+}
+
+// Zero-initialized options: the client's budget, no lookup tables, no outcome
+#[no_mangle]
+pub extern "C" fn sol_send_options_default() -> SolSendOptions {
+    SolSendOptions::default()
+}
+
+// How far a transaction has got while it is being confirmed
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolConfirmationStage {
+    NotFound = 0,
+    Processed = 1,
+    Confirmed = 2,
+    Finalized = 3,
+}
+
+#[repr(C)]
+pub struct SolConfirmationProgress {
+    pub stage: SolConfirmationStage,
+    pub slot: u64, // 0 while the transaction has not been seen
+    pub elapsed_ms: u64,
+    pub polls: u32,
+}
+
+// Called after every status poll, on the thread waiting for the confirmation
+pub type SolConfirmationProgressCallback = Option<
+    extern "C" fn(
+        signature: *const c_char,
+        progress: *const SolConfirmationProgress,
+        user_data: *mut c_void,
+    ),
+>;
+
+#[derive(Clone, Copy)]
+pub struct ProgressHook {
+    callback: extern "C" fn(*const c_char, *const SolConfirmationProgress, *mut c_void),
+    user_data: *mut c_void,
+}
+
+// Safety: the host is told the callback may run on SDK worker threads
+unsafe impl Send for ProgressHook {}
+unsafe impl Sync for ProgressHook {}
+
+impl SolClient {
+    // Send a signed transaction and wait until it reaches the client's commitment,
+    // simulating it first if the client asks for it. Returns its signature and
+    // the slot it landed in.
+    pub fn send_and_confirm<T>(&self, transaction: &T) -> Result<(Signature, u64), SolError>
+    where
+        T: SerializableTransaction + Clone + Into<VersionedTransaction>,
+    {
+        self.send_and_confirm_checked(transaction, self.config.simulate_before_send)
+    }
+
+    // `send_and_confirm`, simulating first only if `simulate`
+    pub(crate) fn send_and_confirm_checked<T>(
+        &self,
+        transaction: &T,
+        simulate: bool,
+    ) -> Result<(Signature, u64), SolError>
+    where
+        T: SerializableTransaction + Clone + Into<VersionedTransaction>,
+    {
+        if simulate {
+            self.check_simulation(transaction.clone())?;
+        }
+        let signature = self.rpc(|rpc| rpc.send_transaction(transaction))?;
+        // A nonce transaction does not expire with its blockhash, only once the nonce advances
+        let blockhash =
+            (!transaction.uses_durable_nonce()).then(|| transaction.get_recent_blockhash());
+        let slot = self.confirm(&signature, blockhash)?;
+        Ok((signature, slot))
+    }
+
+    // Poll the status of `signature` until it reaches the client's commitment,
+    // fails, or times out. With its `recent_blockhash`, a transaction that never
+    // showed up times out as soon as that blockhash expires.
+    pub fn confirm(
+        &self,
+        signature: &Signature,
+        recent_blockhash: Option<&Hash>,
+    ) -> Result<u64, SolError> {
+        let commitment = self.rpc_client.commitment();
+        let timeout = Duration::from_millis(match self.config.confirm_transaction_timeout_ms {
+            0 => DEFAULT_CONFIRM_TIMEOUT_MS,
+            ms => ms,
+        });
+        let interval = Duration::from_millis(match self.config.confirm_poll_interval_ms {
+            0 => DEFAULT_POLL_INTERVAL_MS,
+            ms => ms,
+        });
+        let signature_str = CString::new(signature.to_string())?;
+        let start = Instant::now();
+        let mut polls = 0;
+
+        loop {
+            let status = self
+                .rpc(|rpc| rpc.get_signature_statuses(&[*signature]))?
+                .value
+                .pop()
+                .flatten();
+            polls += 1;
+
+            let (stage, slot) = match &status {
+                None => (SolConfirmationStage::NotFound, 0),
+                Some(status) => {
+                    let stage = match status.confirmation_status() {
+                        TransactionConfirmationStatus::Processed => SolConfirmationStage::Processed,
+                        TransactionConfirmationStatus::Confirmed => SolConfirmationStage::Confirmed,
+                        TransactionConfirmationStatus::Finalized => SolConfirmationStage::Finalized,
+                    };
+                    (stage, status.slot)
+                }
+            };
+            if let Some(hook) = &self.confirmation_progress {
+                let progress = SolConfirmationProgress {
+                    stage,
+                    slot,
+                    elapsed_ms: start.elapsed().as_millis() as u64,
+                    polls,
+                };
+                (hook.callback)(signature_str.as_ptr(), &progress, hook.user_data);
+            }
+
+            match status {
+                Some(status) => {
+                    if let Some(err) = status.err {
+                        return Err(err.into());
+                    }
+                    if status.satisfies_commitment(commitment) {
+                        return Ok(status.slot);
+                    }
+                }
+                None => {
+                    if let Some(blockhash) = recent_blockhash {
+                        let valid = self.rpc(|rpc| {
+                            rpc.is_blockhash_valid(blockhash, CommitmentConfig::processed())
+                        })?;
+                        if !valid {
+                            return Err(SolError::Timeout(format!(
+                                "transaction {} was not found before its blockhash expired",
+                                signature
+                            )));
+                        }
+                    }
+                }
+            }
+
+            if start.elapsed() >= timeout {
+                return Err(SolError::Timeout(format!(
+                    "transaction {} did not reach {:?} commitment within {} ms",
+                    signature,
+                    commitment.commitment,
+                    timeout.as_millis()
+                )));
+            }
+            std::thread::sleep(interval);
+        }
+    }
+}
+
+// Report every status poll of this client's confirmations to `callback`.
+// Set it before submitting jobs; pass NULL to remove it.
+#[no_mangle]
+pub extern "C" fn sol_client_set_confirmation_callback(
+    client: *mut SolClient,
+    callback: SolConfirmationProgressCallback,
+    user_data: *mut c_void,
+) -> bool {
+    ffi_call(false, || {
+        let client = unsafe { mut_arg(client, "client")? };
+        client.confirmation_progress = callback.map(|callback| ProgressHook {
+            callback,
+            user_data,
+        });
+        Ok(true)
+    })
+}
+
+// Wait for an already sent transaction, filling `outcome` (optional) either way.
+// Returns false, with the last error set, unless it was confirmed.
+#[no_mangle]
+pub extern "C" fn sol_confirm_transaction(
+    client: *mut SolClient,
+    signature: *const c_char,
+    outcome: *mut SolConfirmation,
+) -> bool {
+    let slot = ffi_call(None, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let signature = unsafe { str_arg(signature, "signature")? };
+        let signature = Signature::from_str(signature)
+            .map_err(|err| SolError::InvalidInput(format!("invalid signature: {}", err)))?;
+
+        Ok(Some(client.confirm(&signature, None)?))
+    });
+    write_outcome(outcome, slot);
+    slot.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{new_sol_client_with_config, SolClientConfig};
+    use crate::mock_rpc::{signature_status, transaction_handler, with_context, MockRpc};
+    use crate::smart_contract::free_client;
+    use crate::token::{free_transaction_signature, transfer_sol_with_options};
+    use crate::wallet::{SolKeyPair, SolPublicKey};
+    use serde_json::{json, Value};
+    use solana_sdk::pubkey::Pubkey;
+    use std::ffi::CStr;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    // A client polling every millisecond against a mock whose signature
+    // status is given by `status` for each successive poll
+    fn client_with_statuses(
+        timeout_ms: u64,
+        status: impl Fn(u32) -> Value + Send + Sync + 'static,
+    ) -> (MockRpc, *mut SolClient) {
+        let polls = Arc::new(AtomicU32::new(0));
+        let mock = MockRpc::start(move |method, params| match method {
+            "getSignatureStatuses" => {
+                let poll = polls.fetch_add(1, Ordering::SeqCst);
+                Ok(with_context(json!([status(poll)])))
+            }
+            _ => transaction_handler(method, params),
+        });
+        let config = SolClientConfig {
+            confirm_transaction_timeout_ms: timeout_ms,
+            confirm_poll_interval_ms: 1,
+            ..SolClientConfig::default()
+        };
+        let client = new_sol_client_with_config(mock.url.as_ptr(), &config);
+        (mock, client)
+    }
+
+    fn transfer(client: *mut SolClient, outcome: &mut SolConfirmation) -> *mut c_char {
+        let mut sender = SolKeyPair::new();
+        let mut recipient = SolPublicKey::new(Pubkey::new_unique());
+        let options = SolSendOptions {
+            outcome,
+            ..SolSendOptions::default()
+        };
+        transfer_sol_with_options(client, &mut sender, &mut recipient, 1000, &options)
+    }
+
+    fn empty_outcome() -> SolConfirmation {
+        SolConfirmation {
+            status: SolConfirmationStatus::Confirmed,
+            slot: 0,
+            error_code: SolErrorCode::Ok,
+            program_error_code: 0,
+        }
+    }
+
+    extern "C" fn record_stage(
+        _signature: *const c_char,
+        progress: *const SolConfirmationProgress,
+        user_data: *mut c_void,
+    ) {
+        let stages = unsafe { &*(user_data as *const Mutex<Vec<SolConfirmationStage>>) };
+        stages.lock().unwrap().push(unsafe { (*progress).stage });
+    }
+
+    #[test]
+    fn reports_progress_until_finalized() {
+        let (_mock, client) = client_with_statuses(0, |poll| match poll {
+            0 => Value::Null,
+            1 => signature_status(41, "processed", None),
+            2 => signature_status(41, "confirmed", None),
+            _ => signature_status(42, "finalized", None),
+        });
+        let stages = Mutex::new(Vec::<SolConfirmationStage>::new());
+        assert!(sol_client_set_confirmation_callback(
+            client,
+            Some(record_stage),
+            &stages as *const _ as *mut c_void,
+        ));
+
+        let mut outcome = empty_outcome();
+        let signature = transfer(client, &mut outcome);
+        assert!(!signature.is_null());
+        assert_eq!(outcome.status, SolConfirmationStatus::Confirmed);
+        assert_eq!(outcome.slot, 42);
+        assert_eq!(outcome.error_code, SolErrorCode::Ok);
+        assert_eq!(
+            *stages.lock().unwrap(),
+            [
+                SolConfirmationStage::NotFound,
+                SolConfirmationStage::Processed,
+                SolConfirmationStage::Confirmed,
+                SolConfirmationStage::Finalized,
+            ]
+        );
+
+        free_transaction_signature(signature);
+        free_client(client);
+    }
+
+    #[test]
+    fn times_out_when_commitment_is_never_reached() {
+        let (_mock, client) = client_with_statuses(50, |_| signature_status(7, "confirmed", None));
+
+        let mut outcome = empty_outcome();
+        assert!(transfer(client, &mut outcome).is_null());
+        assert_eq!(outcome.status, SolConfirmationStatus::TimedOut);
+        assert_eq!(outcome.error_code, SolErrorCode::Timeout);
+        assert_eq!(outcome.slot, 0);
+        free_client(client);
+    }
+
+    #[test]
+    fn failed_transaction_reports_program_error() {
+        let (_mock, client) = client_with_statuses(0, |_| {
+            signature_status(
+                9,
+                "confirmed",
+                Some(json!({"InstructionError": [0, {"Custom": 6001}]})),
+            )
+        });
+
+        let mut outcome = empty_outcome();
+        assert!(transfer(client, &mut outcome).is_null());
+        assert_eq!(outcome.status, SolConfirmationStatus::Failed);
+        assert_eq!(outcome.error_code, SolErrorCode::ProgramError);
+        assert_eq!(outcome.program_error_code, 6001);
+        free_client(client);
+    }
+
+    #[test]
+    fn confirm_rejects_malformed_signature() {
+        let (_mock, client) = client_with_statuses(0, |_| Value::Null);
+
+        let mut outcome = empty_outcome();
+        assert!(!sol_confirm_transaction(
+            client,
+            c"not-a-signature".as_ptr(),
+            &mut outcome
+        ));
+        assert_eq!(outcome.status, SolConfirmationStatus::Failed);
+        assert_eq!(outcome.error_code, SolErrorCode::InvalidInput);
+        let message = unsafe { CStr::from_ptr(crate::error::sol_last_error_message()) };
+        assert!(message.to_str().unwrap().contains("invalid signature"));
+        free_client(client);
+    }
+}
@@ -0,0 +1,504 @@
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use solana_program::program_error::ProgramError;
+use solana_sdk::{
+    instruction::InstructionError, signature::SignerError, transaction::TransactionError,
+};
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString, NulError};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+// Stable numeric error codes exposed to C. Never renumber existing variants.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolErrorCode {
+    Ok = 0,
+    RpcTransport = 1,
+    RpcServer = 2,
+    InvalidInput = 3,
+    Signing = 4,
+    InsufficientFunds = 5,
+    AccountNotFound = 6,
+    ProgramError = 7,
+    TransactionFailed = 8,
+    Internal = 9,
+    Panic = 10,
+    Timeout = 11,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolError {
+    // The RPC node could not be reached (connection, TLS, timeout...)
+    RpcTransport(String),
+    // The RPC node answered with an error
+    RpcServer { code: i64, message: String },
+    InvalidInput(String),
+    Signing(String),
+    InsufficientFunds(String),
+    AccountNotFound(String),
+    // An instruction failed with `InstructionError::Custom(code)`. `logs` are the
+    // program logs of the failed preflight simulation, when the node sent them.
+    Program {
+        instruction_index: u8,
+        code: u32,
+        logs: Vec<String>,
+    },
+    // Any other transaction error reported by the runtime
+    TransactionFailed(String),
+    Internal(String),
+    // A Rust panic caught at the FFI boundary
+    Panic(String),
+    // A sent transaction was not confirmed in time; it may still land
+    Timeout(String),
+}
+
+impl SolError {
+    pub fn code(&self) -> SolErrorCode {
+        match self {
+            SolError::RpcTransport(_) => SolErrorCode::RpcTransport,
+            SolError::RpcServer { .. } => SolErrorCode::RpcServer,
+            SolError::InvalidInput(_) => SolErrorCode::InvalidInput,
+            SolError::Signing(_) => SolErrorCode::Signing,
+            SolError::InsufficientFunds(_) => SolErrorCode::InsufficientFunds,
+            SolError::AccountNotFound(_) => SolErrorCode::AccountNotFound,
+            SolError::Program { .. } => SolErrorCode::ProgramError,
+            SolError::TransactionFailed(_) => SolErrorCode::TransactionFailed,
+            SolError::Internal(_) => SolErrorCode::Internal,
+            SolError::Panic(_) => SolErrorCode::Panic,
+            SolError::Timeout(_) => SolErrorCode::Timeout,
+        }
+    }
+
+    // Custom program error code, or -1 if this is not a program error
+    pub fn program_code(&self) -> i64 {
+        match self {
+            SolError::Program { code, .. } => *code as i64,
+            _ => -1,
+        }
+    }
+}
+
+impl fmt::Display for SolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolError::RpcTransport(msg) => write!(f, "RPC transport error: {}", msg),
+            SolError::RpcServer { code, message } => {
+                write!(f, "RPC server error {}: {}", code, message)
+            }
+            SolError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            SolError::Signing(msg) => write!(f, "Signing error: {}", msg),
+            SolError::InsufficientFunds(msg) => write!(f, "Insufficient funds: {}", msg),
+            SolError::AccountNotFound(msg) => write!(f, "Account not found: {}", msg),
+            SolError::Program {
+                instruction_index,
+                code,
+                ..
+            } => write!(
+                f,
+                "Instruction {} failed with custom program error {:#x}",
+                instruction_index, code
+            ),
+            SolError::TransactionFailed(msg) => write!(f, "Transaction failed: {}", msg),
+            SolError::Internal(msg) => write!(f, "Internal error: {}", msg),
+            SolError::Panic(msg) => write!(f, "Panic in SDK: {}", msg),
+            SolError::Timeout(msg) => write!(f, "Timed out: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SolError {}
+
+impl From<TransactionError> for SolError {
+    fn from(err: TransactionError) -> Self {
+        match err {
+            TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+                SolError::Program {
+                    instruction_index: index,
+                    code,
+                    logs: Vec::new(),
+                }
+            }
+            TransactionError::InsufficientFundsForFee
+            | TransactionError::InsufficientFundsForRent { .. } => {
+                SolError::InsufficientFunds(err.to_string())
+            }
+            TransactionError::AccountNotFound | TransactionError::ProgramAccountNotFound => {
+                SolError::AccountNotFound(err.to_string())
+            }
+            _ => SolError::TransactionFailed(err.to_string()),
+        }
+    }
+}
+
+impl From<ClientError> for SolError {
+    fn from(err: ClientError) -> Self {
+        if let Some(tx_err) = err.get_transaction_error() {
+            let mut error = SolError::from(tx_err);
+            if let SolError::Program { logs, .. } = &mut error {
+                *logs = simulation_logs(&err);
+            }
+            return error;
+        }
+        match err.kind {
+            ClientErrorKind::Io(e) => SolError::RpcTransport(e.to_string()),
+            ClientErrorKind::Reqwest(e) => SolError::RpcTransport(e.to_string()),
+            ClientErrorKind::Middleware(e) => SolError::RpcTransport(e.to_string()),
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. }) => {
+                SolError::RpcServer { code, message }
+            }
+            // RpcClient reports missing accounts as a plain user-facing message
+            ClientErrorKind::RpcError(RpcError::ForUser(message))
+                if message.starts_with("AccountNotFound") =>
+            {
+                SolError::AccountNotFound(message)
+            }
+            // ...and gives up on confirming a transaction the same way
+            ClientErrorKind::RpcError(RpcError::ForUser(message))
+                if message.starts_with("unable to confirm transaction")
+                    || message.starts_with("transaction not finalized") =>
+            {
+                SolError::Timeout(message)
+            }
+            ClientErrorKind::RpcError(e) => SolError::RpcServer {
+                code: 0,
+                message: e.to_string(),
+            },
+            ClientErrorKind::SerdeJson(e) => SolError::RpcServer {
+                code: 0,
+                message: format!("malformed response: {}", e),
+            },
+            ClientErrorKind::SigningError(e) => SolError::Signing(e.to_string()),
+            ClientErrorKind::TransactionError(e) => e.into(),
+            ClientErrorKind::Custom(message) => SolError::RpcServer { code: 0, message },
+        }
+    }
+}
+
+// Program logs of the simulation behind a failed `sendTransaction` preflight
+fn simulation_logs(err: &ClientError) -> Vec<String> {
+    match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.logs.clone().unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+impl From<SignerError> for SolError {
+    fn from(err: SignerError) -> Self {
+        SolError::Signing(err.to_string())
+    }
+}
+
+impl From<ProgramError> for SolError {
+    fn from(err: ProgramError) -> Self {
+        SolError::InvalidInput(err.to_string())
+    }
+}
+
+impl From<NulError> for SolError {
+    fn from(err: NulError) -> Self {
+        SolError::Internal(err.to_string())
+    }
+}
+
+// ==================== Last Error ==================== //
+
+struct LastError {
+    error: SolError,
+    message: CString,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<LastError>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn set_last_error(error: SolError) {
+    // Interior NULs cannot come from our own messages, but never fail here
+    let message = CString::new(error.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(LastError { error, message }));
+}
+
+// The error of the last failed call on this thread
+pub(crate) fn last_error() -> Option<SolError> {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map(|last| last.error.clone()))
+}
+
+pub(crate) fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+// Run `body`, turning a panic into `SolError::Panic`
+pub(crate) fn catch_panic<T>(body: impl FnOnce() -> Result<T, SolError>) -> Result<T, SolError> {
+    panic::catch_unwind(AssertUnwindSafe(body))
+        .unwrap_or_else(|payload| Err(SolError::Panic(panic_message(payload))))
+}
+
+// Run the body of an exported function, recording its outcome in the
+// thread-local last error. `on_error` is what C sees when the call fails or
+// panics; a panic never unwinds into the host.
+pub(crate) fn ffi_call<T>(on_error: T, body: impl FnOnce() -> Result<T, SolError>) -> T {
+    match catch_panic(body) {
+        Ok(value) => {
+            clear_last_error();
+            value
+        }
+        Err(err) => {
+            set_last_error(err);
+            on_error
+        }
+    }
+}
+
+// Panic guard for exported functions that cannot fail (accessors, free
+// functions). The last error is left untouched unless the body panics.
+pub(crate) fn ffi_guard<T>(on_panic: T, body: impl FnOnce() -> T) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => value,
+        Err(payload) => {
+            set_last_error(SolError::Panic(panic_message(payload)));
+            on_panic
+        }
+    }
+}
+
+// ==================== Argument Helpers ==================== //
+
+// Borrow a pointer argument, rejecting NULL.
+// Safety: a non-null `ptr` must point to a valid `T` for the duration of the call.
+pub(crate) unsafe fn ref_arg<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, SolError> {
+    ptr.as_ref()
+        .ok_or_else(|| SolError::InvalidInput(format!("{} pointer is null", name)))
+}
+
+// Mutably borrow a pointer argument, rejecting NULL.
+// Safety: a non-null `ptr` must point to a valid `T` not otherwise borrowed during the call.
+pub(crate) unsafe fn mut_arg<'a, T>(ptr: *mut T, name: &str) -> Result<&'a mut T, SolError> {
+    ptr.as_mut()
+        .ok_or_else(|| SolError::InvalidInput(format!("{} pointer is null", name)))
+}
+
+// Borrow a NUL-terminated UTF-8 string argument, rejecting NULL and invalid UTF-8.
+// Safety: a non-null `ptr` must point to a NUL-terminated string.
+pub(crate) unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, SolError> {
+    if ptr.is_null() {
+        return Err(SolError::InvalidInput(format!("{} pointer is null", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| SolError::InvalidInput(format!("{} is not valid UTF-8", name)))
+}
+
+// Borrow an array argument. NULL is only accepted for an empty array.
+// Safety: a non-null `ptr` must point to `len` valid elements.
+pub(crate) unsafe fn slice_arg<'a, T>(
+    ptr: *const T,
+    len: usize,
+    name: &str,
+) -> Result<&'a [T], SolError> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if ptr.is_null() {
+        return Err(SolError::InvalidInput(format!(
+            "{} pointer is null but its length is {}",
+            name, len
+        )));
+    }
+    Ok(std::slice::from_raw_parts(ptr, len))
+}
+
+// Error code of the last failed call on this thread, or `Ok` if the last call succeeded
+#[no_mangle]
+pub extern "C" fn sol_last_error_code() -> SolErrorCode {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(SolErrorCode::Ok, |last| last.error.code())
+    })
+}
+
+// Message of the last failed call on this thread, or NULL if the last call succeeded.
+// The string is owned by the SDK and stays valid until the next SDK call on this thread.
+#[no_mangle]
+pub extern "C" fn sol_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |last| last.message.as_ptr())
+    })
+}
+
+// Custom program error code of the last failure, or -1 if it was not a program error
+#[no_mangle]
+pub extern "C" fn sol_last_error_program_code() -> i64 {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(-1, |last| last.error.program_code())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn sol_clear_last_error() {
+    clear_last_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{get_balance, new_sol_client, SolClient};
+    use crate::smart_contract::{free_client, send_generic_transaction_c};
+    use crate::wallet::{get_pubkey_from_address, get_wallet_address, SolKeyPair};
+
+    // Nothing listens here, so any RPC call fails fast instead of hanging
+    const UNREACHABLE_RPC: &CStr = c"http://127.0.0.1:1";
+    const SYSTEM_PROGRAM: &CStr = c"11111111111111111111111111111111";
+
+    fn last_message() -> String {
+        let ptr = sol_last_error_message();
+        assert!(!ptr.is_null());
+        unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+    }
+
+    fn test_client() -> *mut SolClient {
+        new_sol_client(UNREACHABLE_RPC.as_ptr())
+    }
+
+    #[test]
+    fn panic_is_reported_instead_of_unwinding() {
+        let result = ffi_call(7u32, || panic!("boom"));
+        assert_eq!(result, 7);
+        assert_eq!(sol_last_error_code(), SolErrorCode::Panic);
+        assert!(last_message().contains("boom"));
+
+        let result = ffi_guard(false, || panic!("guarded"));
+        assert!(!result);
+        assert_eq!(sol_last_error_code(), SolErrorCode::Panic);
+    }
+
+    #[test]
+    fn success_clears_last_error_but_guard_does_not() {
+        set_last_error(SolError::Internal("stale".to_string()));
+        assert!(ffi_guard(true, || true));
+        assert_eq!(sol_last_error_code(), SolErrorCode::Internal);
+
+        assert!(ffi_call(false, || Ok(true)));
+        assert_eq!(sol_last_error_code(), SolErrorCode::Ok);
+        assert!(sol_last_error_message().is_null());
+        assert_eq!(sol_last_error_program_code(), -1);
+    }
+
+    #[test]
+    fn program_error_exposes_custom_code() {
+        let err: SolError =
+            TransactionError::InstructionError(1, InstructionError::Custom(6000)).into();
+        ffi_call((), || Err(err));
+        assert_eq!(sol_last_error_code(), SolErrorCode::ProgramError);
+        assert_eq!(sol_last_error_program_code(), 6000);
+    }
+
+    #[test]
+    fn null_pointers_are_rejected() {
+        assert!(new_sol_client(std::ptr::null()).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        assert_eq!(get_balance(std::ptr::null_mut(), std::ptr::null_mut()), 0);
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        assert!(get_wallet_address(std::ptr::null_mut()).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        let client = test_client();
+        let signature = send_generic_transaction_c(
+            client,
+            SYSTEM_PROGRAM.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+            0,
+        );
+        assert!(signature.is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        free_client(client);
+    }
+
+    #[test]
+    fn null_signer_entry_is_rejected() {
+        let client = test_client();
+        let signers: [*mut SolKeyPair; 1] = [std::ptr::null_mut()];
+        let signature = send_generic_transaction_c(
+            client,
+            SYSTEM_PROGRAM.as_ptr(),
+            c"initialize".as_ptr(),
+            std::ptr::null(),
+            0,
+            signers.as_ptr(),
+            1,
+            std::ptr::null(),
+            0,
+        );
+        assert!(signature.is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        free_client(client);
+    }
+
+    #[test]
+    fn invalid_utf8_is_rejected() {
+        let invalid = c"\xff\xfe";
+        assert!(new_sol_client(invalid.as_ptr()).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        assert!(last_message().contains("UTF-8"));
+
+        assert!(get_pubkey_from_address(invalid.as_ptr()).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn malformed_program_id_is_rejected() {
+        let client = test_client();
+        let mut payer = SolKeyPair::new();
+        let signers: [*mut SolKeyPair; 1] = [&mut payer];
+        let signature = send_generic_transaction_c(
+            client,
+            c"not-a-program-id".as_ptr(),
+            c"initialize".as_ptr(),
+            std::ptr::null(),
+            0,
+            signers.as_ptr(),
+            1,
+            std::ptr::null(),
+            0,
+        );
+        assert!(signature.is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        assert!(last_message().contains("not-a-program-id"));
+        free_client(client);
+    }
+
+    #[test]
+    fn corrupt_keypair_bytes_are_rejected() {
+        let mut wallet = SolKeyPair::new();
+        wallet.bytes[32..].fill(0xff);
+        assert!(get_wallet_address(&mut wallet).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+    }
+}
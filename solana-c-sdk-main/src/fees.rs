@@ -0,0 +1,784 @@
+// Transaction fees: the compute budget (unit limit and price) the SDK adds to
+// the transactions it builds itself, an estimate of the price recently paid to
+// land transactions writing to given accounts, and what a transaction will cost
+// its payer (network fee and rent of the accounts it creates).
+
+use solana_program::{
+    address_lookup_table::AddressLookupTableAccount, program_pack::Pack, pubkey::Pubkey,
+    system_program,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    message::{Message, VersionedMessage},
+    signature::{Keypair, Signature, Signer},
+    system_instruction::SystemInstruction,
+    transaction::VersionedTransaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+
+use crate::{
+    builder::SolTransactionBuilder,
+    client::SolClient,
+    error::{ffi_call, mut_arg, ref_arg, slice_arg, SolError},
+    lookup_table::sign_message,
+    simulate::simulation_error,
+    wallet::SolPublicKey,
+};
+
+// Highest compute unit limit a transaction may request
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+// Compute budget of the transactions sent by `transfer_sol`, `transfer_spl`,
+// `mint_spl` and the program calls. The client's comes from its config;
+// `SolSendOptions` can give one per call instead.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolComputeBudget {
+    pub unit_limit: u32,       // Compute unit limit (0 = runtime default)
+    pub unit_price: u64,       // Priority fee in micro-lamports per compute unit (0 = none)
+    pub auto_unit_limit: bool, // Size the limit from a simulation, with a 10% margin (overrides unit_limit)
+}
+
+// What a transaction costs its fee payer, in lamports
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolFeeEstimate {
+    pub created_accounts: u32, // Number of accounts the transaction creates
+    pub network_fee: u64,      // Signature and priority fees, from `getFeeForMessage`
+    pub rent: u64,             // Lamports deposited into those accounts
+    pub total: u64,            // network_fee + rent
+}
+
+impl SolFeeEstimate {
+    fn add(self, other: SolFeeEstimate) -> SolFeeEstimate {
+        SolFeeEstimate {
+            network_fee: self.network_fee + other.network_fee,
+            rent: self.rent + other.rent,
+            created_accounts: self.created_accounts + other.created_accounts,
+            total: self.total + other.total,
+        }
+    }
+}
+
+impl SolComputeBudget {
+    // The budget instructions as they will be sent, except that an automatic
+    // limit (unknown before simulating) is left at the runtime default
+    fn estimate_instructions(&self, instructions: &[Instruction]) -> Vec<Instruction> {
+        let unit_limit = (!self.auto_unit_limit && self.unit_limit > 0).then_some(self.unit_limit);
+        self.instructions(unit_limit, instructions)
+    }
+
+    // `instructions` preceded by the compute budget instructions, as the runtime expects
+    fn instructions(
+        &self,
+        unit_limit: Option<u32>,
+        instructions: &[Instruction],
+    ) -> Vec<Instruction> {
+        let mut all = Vec::with_capacity(instructions.len() + 2);
+        if let Some(units) = unit_limit {
+            all.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if self.unit_price > 0 {
+            all.push(ComputeBudgetInstruction::set_compute_unit_price(
+                self.unit_price,
+            ));
+        }
+        all.extend_from_slice(instructions);
+        all
+    }
+}
+
+impl SolClient {
+    // Sign `instructions` with `signers` over the latest blockhash, under
+    // `budget`: a legacy transaction, or a v0 one using `tables`
+    pub(crate) fn sign_with_budget(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        signers: &[&Keypair],
+        budget: &SolComputeBudget,
+        tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedTransaction, SolError> {
+        let blockhash = self.rpc(|rpc| rpc.get_latest_blockhash())?;
+        let sign = |unit_limit| {
+            let instructions = budget.instructions(unit_limit, instructions);
+            sign_message(&instructions, payer, signers, tables, blockhash)
+        };
+        let unit_limit = if budget.auto_unit_limit {
+            self.simulated_unit_limit(sign(Some(MAX_COMPUTE_UNIT_LIMIT))?)?
+        } else {
+            (budget.unit_limit > 0).then_some(budget.unit_limit)
+        };
+        sign(unit_limit)
+    }
+
+    // Send and confirm a transaction from `sign_with_budget` under `budget`.
+    // An automatic unit limit already simulated it, so `simulate_before_send`
+    // does not simulate it again.
+    pub(crate) fn send_budgeted(
+        &self,
+        transaction: &VersionedTransaction,
+        budget: &SolComputeBudget,
+    ) -> Result<(Signature, u64), SolError> {
+        let simulate = self.config.simulate_before_send && !budget.auto_unit_limit;
+        self.send_and_confirm_checked(transaction, simulate)
+    }
+
+    // Sign `instructions` with `payer` and `signer`, which may be the same
+    // keypair, and send and confirm them under the client's compute budget
+    pub(crate) fn send_instructions(
+        &self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        signer: &Keypair,
+    ) -> Result<Signature, SolError> {
+        let mut signers = vec![payer];
+        if signer.pubkey() != payer.pubkey() {
+            signers.push(signer);
+        }
+        let transaction = self.sign_with_budget(
+            instructions,
+            &payer.pubkey(),
+            &signers,
+            &self.config.compute_budget,
+            &[],
+        )?;
+        Ok(self
+            .send_budgeted(&transaction, &self.config.compute_budget)?
+            .0)
+    }
+
+    // Units `transaction`, signed under the highest limit, used in a
+    // simulation, plus a margin. None if the node does not report them.
+    fn simulated_unit_limit(
+        &self,
+        transaction: VersionedTransaction,
+    ) -> Result<Option<u32>, SolError> {
+        let result = self.simulate(&transaction, &[])?;
+        if let Some(err) = result.err {
+            log::warn!("Compute unit simulation failed: {}", err);
+            return Err(simulation_error(err, result.logs.unwrap_or_default()));
+        }
+        Ok(result.units_consumed.map(|units| {
+            let units = u32::try_from(units).unwrap_or(MAX_COMPUTE_UNIT_LIMIT);
+            units.saturating_add(units / 10).min(MAX_COMPUTE_UNIT_LIMIT)
+        }))
+    }
+
+    // The compute unit price (micro-lamports) that `percentile` percent of the
+    // recent slots did not exceed for transactions writing to `accounts`
+    pub fn estimate_priority_fee(
+        &self,
+        accounts: &[Pubkey],
+        percentile: u8,
+    ) -> Result<u64, SolError> {
+        if percentile > 100 {
+            return Err(SolError::InvalidInput(format!(
+                "percentile {} is over 100",
+                percentile
+            )));
+        }
+        let mut fees: Vec<u64> = self
+            .rpc(|rpc| rpc.get_recent_prioritization_fees(accounts))?
+            .iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        if fees.is_empty() {
+            return Ok(0);
+        }
+        fees.sort_unstable();
+        let index = (fees.len() - 1) * percentile as usize / 100;
+        Ok(fees[index])
+    }
+
+    // Fee and creation rent of `message`. Its blockhash is replaced by the
+    // latest one, so it may be unsigned and built without a blockhash.
+    pub fn estimate_fee(&self, mut message: VersionedMessage) -> Result<SolFeeEstimate, SolError> {
+        message.set_recent_blockhash(self.rpc(|rpc| rpc.get_latest_blockhash())?);
+        let network_fee = match &message {
+            VersionedMessage::Legacy(message) => {
+                self.rpc(|rpc| rpc.get_fee_for_message(message))?
+            }
+            VersionedMessage::V0(message) => self.rpc(|rpc| rpc.get_fee_for_message(message))?,
+        };
+        let (rent, created_accounts) = self.creation_rent(&message)?;
+        Ok(SolFeeEstimate {
+            network_fee,
+            rent,
+            created_accounts,
+            total: network_fee + rent,
+        })
+    }
+
+    // Lamports the instructions of `message` deposit into new accounts, and
+    // how many: system accounts it creates, and associated token accounts it
+    // creates that do not exist yet. Accounts a v0 message loads from lookup
+    // tables count too.
+    fn creation_rent(&self, message: &VersionedMessage) -> Result<(u64, u32), SolError> {
+        let keys = self.message_account_keys(message)?;
+        let key = |index: u8| keys.get(index as usize);
+        let (mut rent, mut created) = (0, 0);
+        let mut token_accounts = Vec::new();
+        for instruction in message.instructions() {
+            match key(instruction.program_id_index) {
+                Some(program) if system_program::check_id(program) => {
+                    if let Ok(
+                        SystemInstruction::CreateAccount { lamports, .. }
+                        | SystemInstruction::CreateAccountWithSeed { lamports, .. },
+                    ) = bincode::deserialize(&instruction.data)
+                    {
+                        rent += lamports;
+                        created += 1;
+                    }
+                }
+                // `create` (no data or 0) and `create_idempotent` (1); the new account comes second
+                Some(program) if spl_associated_token_account::check_id(program) => {
+                    let address = instruction.accounts.get(1).and_then(|&index| key(index));
+                    if let (Some(address), [] | [0] | [1]) = (address, instruction.data.as_slice())
+                    {
+                        if !token_accounts.contains(address) {
+                            token_accounts.push(*address);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if token_accounts.is_empty() {
+            return Ok((rent, created));
+        }
+
+        let missing = self
+            .rpc(|rpc| {
+                rpc.get_multiple_accounts_with_commitment(&token_accounts, rpc.commitment())
+            })?
+            .value
+            .iter()
+            .filter(|account| account.is_none())
+            .count() as u32;
+        if missing > 0 {
+            let account_rent = self.rpc(|rpc| {
+                rpc.get_minimum_balance_for_rent_exemption(spl_token::state::Account::LEN)
+            })?;
+            rent += account_rent * missing as u64;
+            created += missing;
+        }
+        Ok((rent, created))
+    }
+}
+
+// ==================== C API ==================== //
+
+// Estimate the compute unit price to pay for a transaction writing to
+// `accounts`: the `percentile` (0-100) of the prioritization fees of recent
+// slots, from `getRecentPrioritizationFees`. 0 if no fees were paid.
+#[no_mangle]
+pub extern "C" fn sol_estimate_priority_fee(
+    client: *mut SolClient,
+    accounts: *const SolPublicKey,
+    account_count: usize,
+    percentile: u8,
+    micro_lamports: *mut u64,
+) -> bool {
+    ffi_call(false, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let accounts: Vec<Pubkey> = unsafe { slice_arg(accounts, account_count, "accounts")? }
+            .iter()
+            .map(SolPublicKey::to_pubkey)
+            .collect();
+        let micro_lamports = unsafe { mut_arg(micro_lamports, "micro_lamports")? };
+
+        *micro_lamports = client.estimate_priority_fee(&accounts, percentile)?;
+        Ok(true)
+    })
+}
+
+// Estimate what the serialized message (`len` bytes, legacy or v0) will cost
+// its fee payer: the network fee and the rent of the accounts it creates. The
+// message needs no valid blockhash.
+#[no_mangle]
+pub extern "C" fn sol_estimate_fee(
+    client: *mut SolClient,
+    message: *const u8,
+    len: usize,
+    estimate: *mut SolFeeEstimate,
+) -> bool {
+    ffi_call(false, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let bytes = unsafe { slice_arg(message, len, "message")? };
+        let estimate = unsafe { mut_arg(estimate, "estimate")? };
+
+        let message: VersionedMessage = bincode::deserialize(bytes)
+            .map_err(|err| SolError::InvalidInput(format!("invalid message: {}", err)))?;
+        *estimate = client.estimate_fee(message)?;
+        Ok(true)
+    })
+}
+
+// Same as `sol_estimate_fee`, for the builder's transaction. It need not be
+// signed if a fee payer was set with `sol_tx_builder_set_fee_payer`.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_estimate_fee(
+    builder: *const SolTransactionBuilder,
+    client: *mut SolClient,
+    estimate: *mut SolFeeEstimate,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { ref_arg(builder, "builder")? };
+        let client = unsafe { ref_arg(client, "client")? };
+        let estimate = unsafe { mut_arg(estimate, "estimate")? };
+
+        *estimate = client.estimate_fee(builder.message(client)?)?;
+        Ok(true)
+    })
+}
+
+// What `transfer_spl` will cost `sender` under the client's compute budget:
+// the transfer, plus the creation of the recipient's token account (a separate
+// transaction and its rent) if it does not exist yet
+#[no_mangle]
+pub extern "C" fn sol_estimate_transfer_spl_fee(
+    client: *mut SolClient,
+    sender: *const SolPublicKey,
+    recipient: *const SolPublicKey,
+    mint: *const SolPublicKey,
+    amount: u64,
+    estimate: *mut SolFeeEstimate,
+) -> bool {
+    ffi_call(false, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let sender = unsafe { ref_arg(sender, "sender")? }.to_pubkey();
+        let recipient = unsafe { ref_arg(recipient, "recipient")? }.to_pubkey();
+        let mint = unsafe { ref_arg(mint, "mint")? }.to_pubkey();
+        let estimate = unsafe { mut_arg(estimate, "estimate")? };
+
+        let budget = &client.config.compute_budget;
+        let message = |instruction| {
+            VersionedMessage::Legacy(Message::new(
+                &budget.estimate_instructions(&[instruction]),
+                Some(&sender),
+            ))
+        };
+        let recipient_assoc = get_associated_token_address(&recipient, &mint);
+        let transfer = spl_token::instruction::transfer(
+            &spl_token::id(),
+            &get_associated_token_address(&sender, &mint),
+            &recipient_assoc,
+            &sender,
+            &[&sender],
+            amount,
+        )?;
+        let mut total = client.estimate_fee(message(transfer))?;
+
+        // The creation is only sent (and counted) if the account is missing
+        let create = create_associated_token_account(&sender, &recipient, &mint, &spl_token::id());
+        let creation = client.estimate_fee(message(create))?;
+        if creation.created_accounts > 0 {
+            total = total.add(creation);
+        }
+        *estimate = total;
+        Ok(true)
+    })
+}
+
+// Same as `sol_estimate_priority_fee`, for the accounts the builder's
+// instructions write to
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_estimate_priority_fee(
+    builder: *const SolTransactionBuilder,
+    client: *mut SolClient,
+    percentile: u8,
+    micro_lamports: *mut u64,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { ref_arg(builder, "builder")? };
+        let client = unsafe { ref_arg(client, "client")? };
+        let micro_lamports = unsafe { mut_arg(micro_lamports, "micro_lamports")? };
+
+        let mut accounts = Vec::new();
+        for instruction in builder.build_instructions() {
+            for meta in instruction.accounts.iter().filter(|meta| meta.is_writable) {
+                if !accounts.contains(&meta.pubkey) {
+                    accounts.push(meta.pubkey);
+                }
+            }
+        }
+        *micro_lamports = client.estimate_priority_fee(&accounts, percentile)?;
+        Ok(true)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{
+        sol_tx_builder_add_create_ata, sol_tx_builder_add_transfer, sol_tx_builder_free,
+        sol_tx_builder_new, sol_tx_builder_set_fee_payer,
+    };
+    use crate::client::{new_sol_client_with_config, SolClientConfig};
+    use crate::confirm::SolSendOptions;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use crate::mock_rpc::{sent_transaction, transaction_handler, with_context, MockRpc};
+    use crate::smart_contract::free_client;
+    use crate::token::{free_transaction_signature, transfer_sol, transfer_sol_with_options};
+    use crate::wallet::SolKeyPair;
+    use serde_json::{json, Value};
+    use solana_sdk::transaction::Transaction;
+    use std::sync::{Arc, Mutex};
+
+    // Data of the compute budget instructions, which come first
+    fn budget_data(transaction: &Transaction) -> Vec<Vec<u8>> {
+        let message = &transaction.message;
+        message
+            .instructions
+            .iter()
+            .filter(|ix| *ix.program_id(&message.account_keys) == solana_sdk::compute_budget::id())
+            .map(|ix| ix.data.clone())
+            .collect()
+    }
+
+    fn limit(units: u32) -> Vec<u8> {
+        ComputeBudgetInstruction::set_compute_unit_limit(units).data
+    }
+
+    fn price(micro_lamports: u64) -> Vec<u8> {
+        ComputeBudgetInstruction::set_compute_unit_price(micro_lamports).data
+    }
+
+    #[test]
+    fn transfers_carry_the_compute_budget() {
+        let simulated = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (sim, snd) = (Arc::clone(&simulated), Arc::clone(&sent));
+        let rpc = MockRpc::start(move |method, params| match method {
+            "simulateTransaction" => {
+                sim.lock().unwrap().push(sent_transaction(params));
+                Ok(with_context(json!({
+                    "err": null,
+                    "logs": [],
+                    "accounts": null,
+                    "unitsConsumed": 1_000,
+                    "returnData": null,
+                })))
+            }
+            "sendTransaction" => {
+                snd.lock().unwrap().push(sent_transaction(params));
+                transaction_handler(method, params)
+            }
+            _ => transaction_handler(method, params),
+        });
+        let config = SolClientConfig {
+            compute_budget: SolComputeBudget {
+                unit_limit: 5_000,
+                unit_price: 20,
+                auto_unit_limit: false,
+            },
+            ..SolClientConfig::default()
+        };
+        let client = new_sol_client_with_config(rpc.url.as_ptr(), &config);
+        let payer = Box::into_raw(Box::new(SolKeyPair::new()));
+        let mut recipient = SolPublicKey::new(Pubkey::new_unique());
+
+        let signature = transfer_sol(client, payer, &mut recipient, 10);
+        assert!(!signature.is_null());
+        free_transaction_signature(signature);
+        let transaction = sent.lock().unwrap().pop().unwrap();
+        assert_eq!(budget_data(&transaction), [limit(5_000), price(20)]);
+        assert_eq!(transaction.message.instructions.len(), 3);
+
+        // A per-call budget replaces the client's; the limit is sized from a simulation
+        let budget = SolComputeBudget {
+            unit_limit: 0,
+            unit_price: 7,
+            auto_unit_limit: true,
+        };
+        let options = SolSendOptions {
+            budget: &budget,
+            ..SolSendOptions::default()
+        };
+        let signature = transfer_sol_with_options(client, payer, &mut recipient, 10, &options);
+        assert!(!signature.is_null());
+        free_transaction_signature(signature);
+        let simulation = simulated.lock().unwrap().pop().unwrap();
+        assert_eq!(
+            budget_data(&simulation),
+            [limit(MAX_COMPUTE_UNIT_LIMIT), price(7)]
+        );
+        let transaction = sent.lock().unwrap().pop().unwrap();
+        assert_eq!(budget_data(&transaction), [limit(1_100), price(7)]);
+
+        // No budget, no compute budget instructions
+        let none = SolComputeBudget::default();
+        let options = SolSendOptions {
+            budget: &none,
+            ..SolSendOptions::default()
+        };
+        let signature = transfer_sol_with_options(client, payer, &mut recipient, 10, &options);
+        assert!(!signature.is_null());
+        free_transaction_signature(signature);
+        let transaction = sent.lock().unwrap().pop().unwrap();
+        assert!(budget_data(&transaction).is_empty());
+
+        free_client(client);
+        unsafe { drop(Box::from_raw(payer)) };
+    }
+
+    #[test]
+    fn estimates_fees_from_recent_slots() {
+        let requested = Arc::new(Mutex::new(Value::Null));
+        let seen = Arc::clone(&requested);
+        let rpc = MockRpc::start(move |method, params| match method {
+            "getRecentPrioritizationFees" => {
+                *seen.lock().unwrap() = params[0].clone();
+                let fees: Vec<Value> = [0, 500, 100, 10_000, 200]
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, fee)| json!({"slot": slot, "prioritizationFee": fee}))
+                    .collect();
+                Ok(json!(fees))
+            }
+            _ => transaction_handler(method, params),
+        });
+        let client = new_sol_client_with_config(rpc.url.as_ptr(), &SolClientConfig::default());
+        let account = SolPublicKey::new(Pubkey::new_unique());
+        let mut fee = 0;
+
+        assert!(sol_estimate_priority_fee(client, &account, 1, 50, &mut fee));
+        assert_eq!(fee, 200);
+        assert_eq!(
+            *requested.lock().unwrap(),
+            json!([account.to_pubkey().to_string()])
+        );
+        assert!(sol_estimate_priority_fee(
+            client,
+            std::ptr::null(),
+            0,
+            100,
+            &mut fee
+        ));
+        assert_eq!(fee, 10_000);
+        assert!(!sol_estimate_priority_fee(
+            client,
+            std::ptr::null(),
+            0,
+            101,
+            &mut fee
+        ));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        // The builder asks for the accounts its instructions write to
+        let (from, to) = (
+            SolPublicKey::new(Pubkey::new_unique()),
+            SolPublicKey::new(Pubkey::new_unique()),
+        );
+        let builder = sol_tx_builder_new();
+        assert!(sol_tx_builder_add_transfer(builder, &from, &to, 1));
+        assert!(sol_tx_builder_add_transfer(builder, &to, &from, 1));
+        assert!(sol_tx_builder_estimate_priority_fee(
+            builder, client, 0, &mut fee
+        ));
+        assert_eq!(fee, 0);
+        assert_eq!(
+            *requested.lock().unwrap(),
+            json!([from.to_pubkey().to_string(), to.to_pubkey().to_string()])
+        );
+
+        sol_tx_builder_free(builder);
+        free_client(client);
+    }
+
+    const TOKEN_ACCOUNT_RENT: u64 = 2_039_280;
+
+    // Charges 5000 lamports per message; token accounts exist if `exists` is set
+    fn fee_rpc(exists: Arc<Mutex<bool>>) -> MockRpc {
+        MockRpc::start(move |method, params| match method {
+            "getFeeForMessage" => Ok(with_context(json!(5_000))),
+            "getMinimumBalanceForRentExemption" => {
+                assert_eq!(params[0], json!(spl_token::state::Account::LEN));
+                Ok(json!(TOKEN_ACCOUNT_RENT))
+            }
+            "getMultipleAccounts" => {
+                let account = match *exists.lock().unwrap() {
+                    true => crate::accounts::tests::keyed_account(
+                        &Pubkey::new_unique(),
+                        &spl_token::id(),
+                        TOKEN_ACCOUNT_RENT,
+                        &[],
+                    )["account"]
+                        .clone(),
+                    false => Value::Null,
+                };
+                Ok(with_context(json!([account])))
+            }
+            _ => transaction_handler(method, params),
+        })
+    }
+
+    #[test]
+    fn estimates_network_fee_and_creation_rent() {
+        let exists = Arc::new(Mutex::new(false));
+        let rpc = fee_rpc(Arc::clone(&exists));
+        let client = new_sol_client_with_config(rpc.url.as_ptr(), &SolClientConfig::default());
+        let (payer, owner, mint) = (
+            SolPublicKey::new(Pubkey::new_unique()),
+            SolPublicKey::new(Pubkey::new_unique()),
+            SolPublicKey::new(Pubkey::new_unique()),
+        );
+        let mut estimate = SolFeeEstimate::default();
+
+        // Unsigned builder transactions need a fee payer
+        let builder = sol_tx_builder_new();
+        assert!(sol_tx_builder_add_create_ata(
+            builder, &payer, &owner, &mint
+        ));
+        assert!(!sol_tx_builder_estimate_fee(builder, client, &mut estimate));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        assert!(sol_tx_builder_set_fee_payer(builder, &payer));
+        assert!(sol_tx_builder_estimate_fee(builder, client, &mut estimate));
+        assert_eq!(
+            estimate,
+            SolFeeEstimate {
+                network_fee: 5_000,
+                rent: TOKEN_ACCOUNT_RENT,
+                created_accounts: 1,
+                total: 5_000 + TOKEN_ACCOUNT_RENT,
+            }
+        );
+
+        // Serialized messages; an existing token account costs nothing
+        *exists.lock().unwrap() = true;
+        let new_account = Pubkey::new_unique();
+        let message = Message::new(
+            &[
+                solana_sdk::system_instruction::create_account(
+                    &payer.to_pubkey(),
+                    &new_account,
+                    1_000_000,
+                    64,
+                    &Pubkey::new_unique(),
+                ),
+                create_associated_token_account(
+                    &payer.to_pubkey(),
+                    &owner.to_pubkey(),
+                    &mint.to_pubkey(),
+                    &spl_token::id(),
+                ),
+            ],
+            Some(&payer.to_pubkey()),
+        );
+        let bytes = bincode::serialize(&message).unwrap();
+        assert!(sol_estimate_fee(
+            client,
+            bytes.as_ptr(),
+            bytes.len(),
+            &mut estimate
+        ));
+        assert_eq!((estimate.rent, estimate.created_accounts), (1_000_000, 1));
+        assert_eq!(estimate.total, 1_005_000);
+        assert!(!sol_estimate_fee(client, [7u8].as_ptr(), 1, &mut estimate));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        // `transfer_spl` sends a second transaction when it creates the account
+        let amount = 10;
+        assert!(sol_estimate_transfer_spl_fee(
+            client,
+            &payer,
+            &owner,
+            &mint,
+            amount,
+            &mut estimate
+        ));
+        assert_eq!(
+            (estimate.network_fee, estimate.created_accounts),
+            (5_000, 0)
+        );
+        *exists.lock().unwrap() = false;
+        assert!(sol_estimate_transfer_spl_fee(
+            client,
+            &payer,
+            &owner,
+            &mint,
+            amount,
+            &mut estimate
+        ));
+        assert_eq!(
+            estimate,
+            SolFeeEstimate {
+                network_fee: 10_000,
+                rent: TOKEN_ACCOUNT_RENT,
+                created_accounts: 1,
+                total: 10_000 + TOKEN_ACCOUNT_RENT,
+            }
+        );
+
+        sol_tx_builder_free(builder);
+        free_client(client);
+    }
+
+    #[test]
+    fn counts_accounts_created_through_lookup_tables() {
+        let (payer, owner, mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let ata = get_associated_token_address(&owner, &mint);
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![owner, mint, ata],
+        };
+        let served = table.clone();
+        let rpc = MockRpc::start(move |method, params| match method {
+            "getFeeForMessage" => Ok(with_context(json!(5_000))),
+            "getMinimumBalanceForRentExemption" => Ok(json!(TOKEN_ACCOUNT_RENT)),
+            "getMultipleAccounts" if params[0][0] == json!(served.key.to_string()) => {
+                Ok(with_context(json!([
+                    crate::lookup_table::tests::table_account(&served.addresses)
+                ])))
+            }
+            "getMultipleAccounts" => {
+                assert_eq!(params[0], json!([ata.to_string()]));
+                Ok(with_context(json!([Value::Null])))
+            }
+            _ => transaction_handler(method, params),
+        });
+        let client = new_sol_client_with_config(rpc.url.as_ptr(), &SolClientConfig::default());
+
+        // The new token account is only in the table, not the message keys
+        let message = crate::lookup_table::compile_message(
+            &[create_associated_token_account(
+                &payer,
+                &owner,
+                &mint,
+                &spl_token::id(),
+            )],
+            &payer,
+            &[table],
+            Default::default(),
+        )
+        .unwrap();
+        assert!(!message.static_account_keys().contains(&ata));
+        let bytes = bincode::serialize(&message).unwrap();
+        let mut estimate = SolFeeEstimate::default();
+        assert!(sol_estimate_fee(
+            client,
+            bytes.as_ptr(),
+            bytes.len(),
+            &mut estimate
+        ));
+        assert_eq!(
+            estimate,
+            SolFeeEstimate {
+                network_fee: 5_000,
+                rent: TOKEN_ACCOUNT_RENT,
+                created_accounts: 1,
+                total: 5_000 + TOKEN_ACCOUNT_RENT,
+            }
+        );
+
+        free_client(client);
+    }
+}
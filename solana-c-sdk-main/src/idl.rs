@@ -0,0 +1,1517 @@
+// Anchor IDL support: load an IDL, list its items, Borsh-encode instruction
+// arguments from JSON, send instructions with the accounts the IDL describes and
+// decode program accounts to JSON.
+
+mod codec;
+pub mod codegen;
+mod errors;
+mod events;
+mod pda;
+
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use solana_program::pubkey::Pubkey;
+use std::ffi::{c_char, c_void, CString};
+use std::str::FromStr;
+
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    signature::Keypair,
+};
+
+use crate::{
+    accounts::{
+        fetch_program_accounts, into_account_list, SolProgramAccountList, SolProgramAccountsConfig,
+    },
+    client::SolClient,
+    confirm::{outcome_arg, write_outcome, SendOptions, SolSendOptions},
+    error::{ffi_call, ffi_guard, mut_arg, ref_arg, slice_arg, str_arg, SolError},
+    jobs::{spawn_job, Completion, JobOutput, SolCompletionCallback},
+    smart_contract::{get_discriminator, send_transaction_with, signer_keypairs},
+    wallet::{SolKeyPair, SolPublicKey},
+};
+
+// ==================== IDL Model ==================== //
+
+// The subset of an Anchor IDL the SDK uses. Both the current format (Anchor
+// 0.30+) and the legacy one (`isMut`/`isSigner`, `publicKey`) are accepted.
+#[derive(Debug, Deserialize)]
+pub struct Idl {
+    #[serde(default)]
+    pub address: Option<String>,
+    // Where legacy IDLs keep the program name
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub metadata: IdlMetadata,
+    #[serde(default)]
+    pub instructions: Vec<IdlInstruction>,
+    #[serde(default)]
+    pub accounts: Vec<IdlAccount>,
+    #[serde(default)]
+    pub events: Vec<IdlEvent>,
+    #[serde(default)]
+    pub errors: Vec<IdlErrorCode>,
+    #[serde(default)]
+    pub types: Vec<IdlTypeDef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct IdlMetadata {
+    #[serde(default)]
+    pub name: Option<String>,
+    // Where legacy IDLs keep the program address
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlInstruction {
+    pub name: String,
+    // Missing from legacy IDLs, where it is derived from the name
+    #[serde(default)]
+    pub discriminator: Option<Vec<u8>>,
+    #[serde(default)]
+    pub accounts: Vec<IdlInstructionAccount>,
+    #[serde(default)]
+    pub args: Vec<IdlField>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlInstructionAccount {
+    pub name: String,
+    #[serde(default, alias = "isMut")]
+    pub writable: bool,
+    #[serde(default, alias = "isSigner")]
+    pub signer: bool,
+    // Fixed address, e.g. a program or sysvar
+    #[serde(default)]
+    pub address: Option<String>,
+    // Composite account groups, flattened by `IdlInstruction::flat_accounts`
+    #[serde(default)]
+    pub accounts: Option<Vec<IdlInstructionAccount>>,
+    // Seeds of a program-derived account
+    #[serde(default)]
+    pub pda: Option<IdlPda>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlPda {
+    pub seeds: Vec<IdlSeed>,
+    // Deriving program, when it is not the IDL's own
+    #[serde(default)]
+    pub program: Option<IdlSeed>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlSeed {
+    // Raw bytes, or a typed value in legacy IDLs
+    Const {
+        value: Value,
+        #[serde(default, rename = "type")]
+        ty: Option<IdlType>,
+    },
+    // An instruction argument, or a field of one (`params.id`)
+    Arg {
+        path: String,
+    },
+    // Another account of the instruction
+    Account {
+        path: String,
+    },
+}
+
+// An account type: its layout is in `types`, or inline in legacy IDLs
+#[derive(Debug, Deserialize)]
+pub struct IdlAccount {
+    pub name: String,
+    // Missing from legacy IDLs, where it is derived from the name
+    #[serde(default)]
+    pub discriminator: Option<Vec<u8>>,
+    #[serde(default, rename = "type")]
+    pub ty: Option<IdlTypeDefTy>,
+}
+
+// An event type: its layout is in `types`, or inline in legacy IDLs
+#[derive(Debug, Deserialize)]
+pub struct IdlEvent {
+    pub name: String,
+    // Missing from legacy IDLs, where it is derived from the name
+    #[serde(default)]
+    pub discriminator: Option<Vec<u8>>,
+    #[serde(default)]
+    pub fields: Option<IdlFields>,
+}
+
+// A custom error of the program (Anchor numbers them from 6000)
+#[derive(Debug, Deserialize)]
+pub struct IdlErrorCode {
+    pub code: u32,
+    pub name: String,
+    #[serde(default)]
+    pub msg: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlTypeDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: IdlTypeDefTy,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlTypeDefTy {
+    Struct {
+        #[serde(default)]
+        fields: IdlFields,
+    },
+    Enum {
+        variants: Vec<IdlEnumVariant>,
+    },
+    Type {
+        alias: IdlType,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlEnumVariant {
+    pub name: String,
+    #[serde(default)]
+    pub fields: IdlFields,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum IdlFields {
+    Named(Vec<IdlField>),
+    Tuple(Vec<IdlType>),
+}
+
+impl Default for IdlFields {
+    fn default() -> Self {
+        IdlFields::Named(Vec::new())
+    }
+}
+
+impl IdlFields {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            IdlFields::Named(fields) => fields.is_empty(),
+            IdlFields::Tuple(types) => types.is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdlField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: IdlType,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "Value")]
+pub enum IdlType {
+    Bool,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+    U64,
+    I64,
+    F64,
+    U128,
+    I128,
+    String,
+    Bytes,
+    Pubkey,
+    Option(Box<IdlType>),
+    Vec(Box<IdlType>),
+    Array(Box<IdlType>, usize),
+    Defined(String),
+}
+
+impl TryFrom<Value> for IdlType {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let nested = |value: &Value| IdlType::try_from(value.clone()).map(Box::new);
+        match &value {
+            Value::String(name) => Ok(match name.as_str() {
+                "bool" => IdlType::Bool,
+                "u8" => IdlType::U8,
+                "i8" => IdlType::I8,
+                "u16" => IdlType::U16,
+                "i16" => IdlType::I16,
+                "u32" => IdlType::U32,
+                "i32" => IdlType::I32,
+                "f32" => IdlType::F32,
+                "u64" => IdlType::U64,
+                "i64" => IdlType::I64,
+                "f64" => IdlType::F64,
+                "u128" => IdlType::U128,
+                "i128" => IdlType::I128,
+                "string" => IdlType::String,
+                "bytes" => IdlType::Bytes,
+                "pubkey" | "publicKey" => IdlType::Pubkey,
+                _ => return Err(format!("unsupported IDL type '{}'", name)),
+            }),
+            Value::Object(map) if map.len() == 1 => {
+                let (kind, inner) = map.iter().next().unwrap();
+                match kind.as_str() {
+                    "option" => Ok(IdlType::Option(nested(inner)?)),
+                    "vec" => Ok(IdlType::Vec(nested(inner)?)),
+                    "array" => match inner.as_array().map(Vec::as_slice) {
+                        Some([ty, Value::Number(len)]) if len.is_u64() => {
+                            Ok(IdlType::Array(nested(ty)?, len.as_u64().unwrap() as usize))
+                        }
+                        _ => Err(format!("unsupported IDL array type {}", inner)),
+                    },
+                    // `{"defined": "Name"}` (legacy) or `{"defined": {"name": "Name"}}`
+                    "defined" => match inner {
+                        Value::String(name) => Ok(IdlType::Defined(name.clone())),
+                        Value::Object(defined) => {
+                            match (defined.get("name"), defined.get("generics")) {
+                                (Some(Value::String(name)), None) => {
+                                    Ok(IdlType::Defined(name.clone()))
+                                }
+                                (Some(Value::String(name)), Some(Value::Array(generics)))
+                                    if generics.is_empty() =>
+                                {
+                                    Ok(IdlType::Defined(name.clone()))
+                                }
+                                _ => Err(format!("unsupported IDL defined type {}", inner)),
+                            }
+                        }
+                        _ => Err(format!("unsupported IDL defined type {}", inner)),
+                    },
+                    _ => Err(format!("unsupported IDL type {}", value)),
+                }
+            }
+            _ => Err(format!("unsupported IDL type {}", value)),
+        }
+    }
+}
+
+impl Idl {
+    pub fn parse(json: &str) -> Result<Self, SolError> {
+        serde_json::from_str(json)
+            .map_err(|err| SolError::InvalidInput(format!("invalid IDL: {}", err)))
+    }
+
+    pub fn program_id(&self) -> Result<Pubkey, SolError> {
+        let address = self
+            .address
+            .as_ref()
+            .or(self.metadata.address.as_ref())
+            .ok_or_else(|| SolError::InvalidInput("IDL has no program address".to_string()))?;
+        Pubkey::from_str(address).map_err(|err| {
+            SolError::InvalidInput(format!("invalid IDL address '{}': {}", address, err))
+        })
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.metadata.name.as_deref().or(self.name.as_deref())
+    }
+
+    pub fn instruction(&self, name: &str) -> Result<&IdlInstruction, SolError> {
+        self.instructions
+            .iter()
+            .find(|instruction| instruction.name == name)
+            .ok_or_else(|| SolError::InvalidInput(format!("IDL has no instruction '{}'", name)))
+    }
+
+    pub fn account(&self, name: &str) -> Result<&IdlAccount, SolError> {
+        self.accounts
+            .iter()
+            .find(|account| account.name == name)
+            .ok_or_else(|| SolError::InvalidInput(format!("IDL has no account type '{}'", name)))
+    }
+
+    pub fn type_def(&self, name: &str) -> Option<&IdlTypeDefTy> {
+        self.types
+            .iter()
+            .find(|def| def.name == name)
+            .map(|def| &def.ty)
+            .or_else(|| {
+                self.accounts
+                    .iter()
+                    .find(|account| account.name == name)
+                    .and_then(|account| account.ty.as_ref())
+            })
+    }
+
+    // Borsh encoding of `args`, a JSON object keyed by argument name
+    pub fn encode_args(
+        &self,
+        instruction: &IdlInstruction,
+        args: &Value,
+    ) -> Result<Vec<u8>, SolError> {
+        let args = match args {
+            Value::Object(args) => args,
+            Value::Null if instruction.args.is_empty() => return Ok(Vec::new()),
+            _ => {
+                return Err(SolError::InvalidInput(
+                    "instruction args must be a JSON object".to_string(),
+                ))
+            }
+        };
+        let mut data = Vec::new();
+        for arg in &instruction.args {
+            let value = args.get(&arg.name).ok_or_else(|| {
+                SolError::InvalidInput(format!(
+                    "missing argument '{}' of '{}'",
+                    arg.name, instruction.name
+                ))
+            })?;
+            codec::encode(self, &arg.ty, value, &arg.name, &mut data, 0)?;
+        }
+        Ok(data)
+    }
+
+    // JSON value of the data of a `name` account, after checking its discriminator
+    pub fn decode_account(&self, name: &str, data: &[u8]) -> Result<Value, SolError> {
+        let account = self.account(name)?;
+        let discriminator = account.discriminator();
+        if !data.starts_with(&discriminator) {
+            return Err(SolError::InvalidInput(format!(
+                "account data is not a '{}' (discriminator mismatch)",
+                name
+            )));
+        }
+        let def = self
+            .type_def(name)
+            .ok_or_else(|| SolError::InvalidInput(format!("IDL has no layout for '{}'", name)))?;
+        // Anything after the layout is unused space of the account
+        codec::decode_defined(self, def, &mut &data[discriminator.len()..], name, 0)
+    }
+
+    // The event whose discriminator starts `data` and its decoded fields, or
+    // `None` if the data is not one of the IDL events
+    pub fn decode_event(&self, data: &[u8]) -> Result<Option<(&str, Value)>, SolError> {
+        for event in &self.events {
+            let Some(mut fields) = data.strip_prefix(event.discriminator().as_slice()) else {
+                continue;
+            };
+            let value = match &event.fields {
+                Some(inline) => codec::decode_fields(self, inline, &mut fields, &event.name, 0)?,
+                None => {
+                    let def = self.type_def(&event.name).ok_or_else(|| {
+                        SolError::InvalidInput(format!("IDL has no layout for '{}'", event.name))
+                    })?;
+                    codec::decode_defined(self, def, &mut fields, &event.name, 0)?
+                }
+            };
+            return Ok(Some((&event.name, value)));
+        }
+        Ok(None)
+    }
+}
+
+impl IdlInstruction {
+    pub fn discriminator(&self) -> Vec<u8> {
+        self.discriminator
+            .clone()
+            .unwrap_or_else(|| get_discriminator(&self.name))
+    }
+
+    // Accounts in the order the program expects them, with groups expanded
+    pub fn flat_accounts(&self) -> Vec<&IdlInstructionAccount> {
+        fn flatten<'a>(
+            accounts: &'a [IdlInstructionAccount],
+            out: &mut Vec<&'a IdlInstructionAccount>,
+        ) {
+            for account in accounts {
+                match &account.accounts {
+                    Some(group) => flatten(group, out),
+                    None => out.push(account),
+                }
+            }
+        }
+        let mut out = Vec::new();
+        flatten(&self.accounts, &mut out);
+        out
+    }
+
+    // Account metas with the IDL's signer/writable flags. `accounts` lists
+    // either every account in IDL order, only those without a fixed address,
+    // or only those the IDL cannot resolve: accounts with `pda` seeds are then
+    // derived too, from the other accounts and `args`.
+    pub fn account_metas(
+        &self,
+        idl: &Idl,
+        accounts: &[Pubkey],
+        args: &Value,
+    ) -> Result<Vec<AccountMeta>, SolError> {
+        let idl_accounts = self.flat_accounts();
+        let fixed = idl_accounts
+            .iter()
+            .map(|account| {
+                account
+                    .address
+                    .as_ref()
+                    .map(|address| {
+                        Pubkey::from_str(address).map_err(|err| {
+                            SolError::InvalidInput(format!(
+                                "invalid address '{}' of account '{}': {}",
+                                address, account.name, err
+                            ))
+                        })
+                    })
+                    .transpose()
+            })
+            .collect::<Result<Vec<Option<Pubkey>>, SolError>>()?;
+        let fixed_count = fixed.iter().filter(|address| address.is_some()).count();
+        let derived = |i: usize| fixed[i].is_none() && idl_accounts[i].pda.is_some();
+        let derived_count = (0..idl_accounts.len()).filter(|&i| derived(i)).count();
+        let unresolved_count = idl_accounts.len() - fixed_count - derived_count;
+
+        let mut given = accounts.iter();
+        let pubkeys = if accounts.len() == idl_accounts.len() {
+            accounts.to_vec()
+        } else if accounts.len() == idl_accounts.len() - fixed_count {
+            fixed
+                .iter()
+                .map(|address| address.unwrap_or_else(|| *given.next().unwrap()))
+                .collect()
+        } else if derived_count > 0 && accounts.len() == unresolved_count {
+            let mut pubkeys = (0..idl_accounts.len())
+                .map(|i| match fixed[i] {
+                    Some(address) => Some(address),
+                    None if derived(i) => None,
+                    None => Some(*given.next().unwrap()),
+                })
+                .collect::<Vec<Option<Pubkey>>>();
+            pda::derive_accounts(idl, self, &idl_accounts, &mut pubkeys, args)?;
+            pubkeys.into_iter().flatten().collect()
+        } else {
+            return Err(SolError::InvalidInput(format!(
+                "'{}' takes {} accounts ({} without fixed addresses, {} without derived ones), got {}",
+                self.name,
+                idl_accounts.len(),
+                idl_accounts.len() - fixed_count,
+                unresolved_count,
+                accounts.len()
+            )));
+        };
+
+        Ok(idl_accounts
+            .iter()
+            .zip(pubkeys)
+            .map(|(account, pubkey)| AccountMeta {
+                pubkey,
+                is_signer: account.signer,
+                is_writable: account.writable,
+            })
+            .collect())
+    }
+}
+
+impl IdlAccount {
+    pub fn discriminator(&self) -> Vec<u8> {
+        self.discriminator
+            .clone()
+            .unwrap_or_else(|| sighash("account", &self.name))
+    }
+}
+
+impl IdlEvent {
+    pub fn discriminator(&self) -> Vec<u8> {
+        self.discriminator
+            .clone()
+            .unwrap_or_else(|| sighash("event", &self.name))
+    }
+}
+
+// Anchor's 8-byte `<namespace>:<name>` hash, used by legacy IDLs
+fn sighash(namespace: &str, name: &str) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}:{}", namespace, name).as_bytes());
+    hasher.finalize()[..8].to_vec()
+}
+
+// Parse an optional JSON argument string; NULL means no arguments
+// Safety: see `str_arg`.
+unsafe fn json_arg(ptr: *const c_char, name: &str) -> Result<Value, SolError> {
+    if ptr.is_null() {
+        return Ok(Value::Null);
+    }
+    let json = str_arg(ptr, name)?;
+    serde_json::from_str(json)
+        .map_err(|err| SolError::InvalidInput(format!("{} is not valid JSON: {}", name, err)))
+}
+
+// ==================== C API ==================== //
+
+// A loaded IDL. Opaque to C: create it with `sol_idl_load` or `sol_idl_load_file`,
+// release it with `sol_idl_free`.
+pub struct SolIdl {
+    idl: Idl,
+    // Names handed out by `sol_idl_item_name`, owned by the IDL
+    instruction_names: Vec<CString>,
+    account_names: Vec<CString>,
+    type_names: Vec<CString>,
+}
+
+impl SolIdl {
+    fn new(idl: Idl) -> Result<Self, SolError> {
+        let names = |names: Vec<&String>| {
+            names
+                .into_iter()
+                .map(|name| CString::new(name.as_str()))
+                .collect::<Result<Vec<CString>, _>>()
+        };
+        Ok(SolIdl {
+            instruction_names: names(idl.instructions.iter().map(|i| &i.name).collect())?,
+            account_names: names(idl.accounts.iter().map(|a| &a.name).collect())?,
+            type_names: names(idl.types.iter().map(|t| &t.name).collect())?,
+            idl,
+        })
+    }
+
+    fn names(&self, kind: SolIdlItemKind) -> &[CString] {
+        match kind {
+            SolIdlItemKind::Instruction => &self.instruction_names,
+            SolIdlItemKind::Account => &self.account_names,
+            SolIdlItemKind::Type => &self.type_names,
+        }
+    }
+}
+
+// Kinds of IDL items. C passes them as `uint32_t`, so unknown values are
+// rejected instead of becoming an invalid enum.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolIdlItemKind {
+    Instruction = 0,
+    Account = 1,
+    Type = 2,
+}
+
+impl TryFrom<u32> for SolIdlItemKind {
+    type Error = SolError;
+
+    fn try_from(value: u32) -> Result<Self, SolError> {
+        match value {
+            0 => Ok(SolIdlItemKind::Instruction),
+            1 => Ok(SolIdlItemKind::Account),
+            2 => Ok(SolIdlItemKind::Type),
+            _ => Err(SolError::InvalidInput(format!(
+                "unknown IDL item kind {}",
+                value
+            ))),
+        }
+    }
+}
+
+// Load an IDL from its JSON text
+#[no_mangle]
+pub extern "C" fn sol_idl_load(json: *const c_char) -> *mut SolIdl {
+    ffi_call(std::ptr::null_mut(), || {
+        let json = unsafe { str_arg(json, "IDL JSON")? };
+        Ok(Box::into_raw(Box::new(SolIdl::new(Idl::parse(json)?)?)))
+    })
+}
+
+// Load an IDL from a JSON file (`~` is expanded)
+#[no_mangle]
+pub extern "C" fn sol_idl_load_file(path: *const c_char) -> *mut SolIdl {
+    ffi_call(std::ptr::null_mut(), || {
+        let path = unsafe { str_arg(path, "IDL path")? };
+        let path = shellexpand::tilde(path).into_owned();
+        let json = std::fs::read_to_string(&path).map_err(|err| {
+            SolError::InvalidInput(format!("cannot read IDL file '{}': {}", path, err))
+        })?;
+        Ok(Box::into_raw(Box::new(SolIdl::new(Idl::parse(&json)?)?)))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn sol_idl_free(idl: *mut SolIdl) {
+    ffi_guard((), || {
+        if idl.is_null() {
+            return;
+        }
+        unsafe {
+            drop(Box::from_raw(idl));
+        }
+    })
+}
+
+// Program address declared by the IDL
+#[no_mangle]
+pub extern "C" fn sol_idl_program_id(idl: *const SolIdl, program_id: *mut SolPublicKey) -> bool {
+    ffi_call(false, || {
+        let idl = unsafe { ref_arg(idl, "idl")? };
+        let program_id = unsafe { mut_arg(program_id, "program_id")? };
+        *program_id = SolPublicKey::new(idl.idl.program_id()?);
+        Ok(true)
+    })
+}
+
+// Number of instructions, account types or types (`kind`, a `SolIdlItemKind`)
+// declared by the IDL; 0 for an unknown kind
+#[no_mangle]
+pub extern "C" fn sol_idl_item_count(idl: *const SolIdl, kind: u32) -> usize {
+    ffi_call(0, || {
+        let kind = SolIdlItemKind::try_from(kind)?;
+        Ok(unsafe { idl.as_ref() }.map_or(0, |idl| idl.names(kind).len()))
+    })
+}
+
+// Name of the `index`-th item of `kind`, or NULL if out of range. The string is
+// owned by the IDL and valid until `sol_idl_free`.
+#[no_mangle]
+pub extern "C" fn sol_idl_item_name(idl: *const SolIdl, kind: u32, index: usize) -> *const c_char {
+    ffi_call(std::ptr::null(), || {
+        let idl = unsafe { ref_arg(idl, "idl")? };
+        let kind = SolIdlItemKind::try_from(kind)?;
+        let name = idl.names(kind).get(index).ok_or_else(|| {
+            SolError::InvalidInput(format!("{:?} index {} is out of range", kind, index))
+        })?;
+        Ok(name.as_ptr())
+    })
+}
+
+// Instruction data for `method_name`: its discriminator followed by the Borsh
+// encoding of `args_json`, a JSON object keyed by argument name (NULL when the
+// instruction takes no arguments). Free with `free_instruction_data`.
+#[no_mangle]
+pub extern "C" fn sol_idl_encode_instruction(
+    idl: *const SolIdl,
+    method_name: *const c_char,
+    args_json: *const c_char,
+    out_len: *mut usize,
+) -> *mut u8 {
+    ffi_call(std::ptr::null_mut(), || {
+        let idl = &unsafe { ref_arg(idl, "idl")? }.idl;
+        let method_name = unsafe { str_arg(method_name, "method name")? };
+        let args = unsafe { json_arg(args_json, "args")? };
+        let out_len = unsafe { mut_arg(out_len, "out_len")? };
+
+        let instruction = idl.instruction(method_name)?;
+        let mut data = instruction.discriminator();
+        data.extend(idl.encode_args(instruction, &args)?);
+        *out_len = data.len();
+        Ok(Box::into_raw(data.into_boxed_slice()) as *mut u8)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn free_instruction_data(data: *mut u8, len: usize) {
+    ffi_guard((), || {
+        if data.is_null() {
+            return;
+        }
+        unsafe {
+            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(data, len)));
+        }
+    })
+}
+
+// Fetch `pubkey` and decode it as an `account_type` account of the IDL. Returns
+// its fields as a JSON object (free with `free_idl_json`), or NULL on failure.
+#[no_mangle]
+pub extern "C" fn sol_idl_decode_account(
+    client: *mut SolClient,
+    idl: *const SolIdl,
+    account_type: *const c_char,
+    pubkey: *const SolPublicKey,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let idl = &unsafe { ref_arg(idl, "idl")? }.idl;
+        let account_type = unsafe { str_arg(account_type, "account type")? };
+        let pubkey = unsafe { ref_arg(pubkey, "pubkey")? }.to_pubkey();
+
+        let account = client.rpc(|rpc| rpc.get_account(&pubkey))?;
+        let json = idl.decode_account(account_type, &account.data)?.to_string();
+        Ok(CString::new(json)?.into_raw())
+    })
+}
+
+// Same as `sol_idl_decode_account` for account data the caller already has
+#[no_mangle]
+pub extern "C" fn sol_idl_decode_account_data(
+    idl: *const SolIdl,
+    account_type: *const c_char,
+    data_ptr: *const u8,
+    data_len: usize,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let idl = &unsafe { ref_arg(idl, "idl")? }.idl;
+        let account_type = unsafe { str_arg(account_type, "account type")? };
+        let data = unsafe { slice_arg(data_ptr, data_len, "data")? };
+
+        let json = idl.decode_account(account_type, data)?.to_string();
+        Ok(CString::new(json)?.into_raw())
+    })
+}
+
+// Every `account_type` account of the IDL's program: the accounts starting with
+// its discriminator that also match the `config` filters (NULL: none), each
+// with `json` set to its decoded fields. Fails if one does not decode, e.g.
+// because of a data slice. Free with `free_program_account_list`.
+#[no_mangle]
+pub extern "C" fn sol_idl_get_program_accounts(
+    client: *mut SolClient,
+    idl: *const SolIdl,
+    account_type: *const c_char,
+    config: *const SolProgramAccountsConfig,
+) -> *mut SolProgramAccountList {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let idl = &unsafe { ref_arg(idl, "idl")? }.idl;
+        let account_type = unsafe { str_arg(account_type, "account type")? };
+
+        let discriminator = idl.account(account_type)?.discriminator();
+        let filter = RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, discriminator));
+        let accounts =
+            unsafe { fetch_program_accounts(client, &idl.program_id()?, config, vec![filter])? };
+        let json = accounts
+            .iter()
+            .map(|(_, account)| {
+                let json = idl.decode_account(account_type, &account.data)?.to_string();
+                Ok(CString::new(json)?)
+            })
+            .collect::<Result<Vec<CString>, SolError>>()?;
+        Ok(into_account_list(accounts, json))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn free_idl_json(json: *mut c_char) {
+    ffi_guard((), || {
+        if json.is_null() {
+            return;
+        }
+        unsafe {
+            drop(CString::from_raw(json));
+        }
+    })
+}
+
+// Call `method_name` of the IDL's program with arguments encoded from `args_json`.
+// `accounts` follow the IDL's order, and may leave out accounts with a fixed
+// address, or those and the ones the IDL gives `pda` seeds for, which are then
+// derived; their signer/writable flags come from the IDL. The first signer pays the fee.
+// `options` may be NULL for the client's defaults.
+#[no_mangle]
+pub extern "C" fn sol_idl_send_instruction(
+    client: *mut SolClient,
+    idl: *const SolIdl,
+    method_name: *const c_char,
+    accounts: *const SolPublicKey,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    args_json: *const c_char,
+    options: *const SolSendOptions,
+) -> *mut c_char {
+    let (signature, slot) = ffi_call((std::ptr::null_mut(), None), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let (instruction, signer_keypairs) = unsafe {
+            idl_instruction_args(
+                idl,
+                method_name,
+                accounts,
+                account_count,
+                signers,
+                signer_count,
+                args_json,
+            )?
+        };
+        let options = unsafe { SendOptions::from_arg(client, options)? };
+
+        let (sig, slot) = send_transaction_with(client, instruction, &signer_keypairs, &options)?;
+        Ok((CString::new(sig.to_string())?.into_raw(), Some(slot)))
+    });
+    write_outcome(unsafe { outcome_arg(options) }, slot);
+    signature
+}
+
+#[no_mangle]
+pub extern "C" fn sol_idl_send_instruction_async(
+    client: *mut SolClient,
+    idl: *const SolIdl,
+    method_name: *const c_char,
+    accounts: *const SolPublicKey,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    args_json: *const c_char,
+    options: *const SolSendOptions,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let task = unsafe {
+            idl_instruction_task(
+                client,
+                idl,
+                method_name,
+                accounts,
+                account_count,
+                signers,
+                signer_count,
+                args_json,
+                options,
+            )?
+        };
+        spawn_job(client, None, task)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn sol_idl_send_instruction_with_callback(
+    client: *mut SolClient,
+    idl: *const SolIdl,
+    method_name: *const c_char,
+    accounts: *const SolPublicKey,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    args_json: *const c_char,
+    options: *const SolSendOptions,
+    callback: SolCompletionCallback,
+    user_data: *mut c_void,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let completion = Completion::new(callback, user_data)?;
+        let task = unsafe {
+            idl_instruction_task(
+                client,
+                idl,
+                method_name,
+                accounts,
+                account_count,
+                signers,
+                signer_count,
+                args_json,
+                options,
+            )?
+        };
+        spawn_job(client, Some(completion), task)
+    })
+}
+
+// Copy the arguments of `sol_idl_send_instruction` into a job.
+// Safety: the pointer arguments must be valid as described by that function.
+#[allow(clippy::too_many_arguments)]
+unsafe fn idl_instruction_task(
+    client: &SolClient,
+    idl: *const SolIdl,
+    method_name: *const c_char,
+    accounts: *const SolPublicKey,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    args_json: *const c_char,
+    options: *const SolSendOptions,
+) -> Result<impl FnOnce(&SolClient) -> Result<JobOutput, SolError>, SolError> {
+    let (instruction, signer_keypairs) = idl_instruction_args(
+        idl,
+        method_name,
+        accounts,
+        account_count,
+        signers,
+        signer_count,
+        args_json,
+    )?;
+    let options = SendOptions::from_arg(client, options)?;
+
+    Ok(move |client: &SolClient| {
+        let (sig, _) = send_transaction_with(client, instruction, &signer_keypairs, &options)?;
+        Ok(JobOutput::Signature(sig))
+    })
+}
+
+// Build the instruction and signer list of an IDL call.
+// Safety: the pointer arguments must be valid as described by `sol_idl_send_instruction`.
+unsafe fn idl_instruction_args(
+    idl: *const SolIdl,
+    method_name: *const c_char,
+    accounts: *const SolPublicKey,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    args_json: *const c_char,
+) -> Result<(Instruction, Vec<Keypair>), SolError> {
+    let idl = &ref_arg(idl, "idl")?.idl;
+    let method_name = str_arg(method_name, "method name")?;
+    let accounts = slice_arg(accounts, account_count, "accounts")?
+        .iter()
+        .map(SolPublicKey::to_pubkey)
+        .collect::<Vec<Pubkey>>();
+    let signer_keypairs = signer_keypairs(signers, signer_count)?;
+    let args = json_arg(args_json, "args")?;
+
+    let instruction = idl.instruction(method_name)?;
+    let mut data = instruction.discriminator();
+    data.extend(idl.encode_args(instruction, &args)?);
+    let metas = instruction.account_metas(idl, &accounts, &args)?;
+
+    Ok((
+        Instruction::new_with_bytes(idl.program_id()?, &data, metas),
+        signer_keypairs,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::free_program_account_list;
+    use crate::accounts::tests::{keyed_account, list_accounts};
+    use crate::client::new_sol_client;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use crate::mock_rpc::{recording_handler, with_context, MockRpc, SentTransaction};
+    use crate::smart_contract::free_client;
+    use crate::token::free_transaction_signature;
+    use base64::Engine;
+    use solana_sdk::transaction::Transaction;
+    use std::ffi::CStr;
+    use std::sync::{Arc, Mutex};
+
+    pub(super) const COUNTER_IDL: &str = include_str!("../anchor_counter.json");
+
+    // Exercises every kind of type the encoder supports
+    pub(super) const GAME_IDL: &str = r#"{
+        "address": "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy",
+        "metadata": {"name": "game", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{
+            "name": "submit_score",
+            "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+            "accounts": [
+                {"name": "player", "writable": true, "signer": true},
+                {"name": "board", "writable": true}
+            ],
+            "args": [
+                {"name": "score", "type": "u64"},
+                {"name": "delta", "type": "i16"},
+                {"name": "tag", "type": "string"},
+                {"name": "referrer", "type": {"option": "pubkey"}},
+                {"name": "items", "type": {"vec": {"defined": {"name": "Item"}}}},
+                {"name": "grid", "type": {"array": ["u8", 3]}},
+                {"name": "mode", "type": {"defined": {"name": "Mode"}}},
+                {"name": "big", "type": "u128"}
+            ]
+        }],
+        "accounts": [{"name": "Player", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9]}],
+        "types": [
+            {"name": "Item", "type": {"kind": "struct", "fields": [
+                {"name": "id", "type": "u32"},
+                {"name": "rare", "type": "bool"}
+            ]}},
+            {"name": "Mode", "type": {"kind": "enum", "variants": [
+                {"name": "Casual"},
+                {"name": "Ranked", "fields": [{"name": "tier", "type": "u8"}]},
+                {"name": "Custom", "fields": ["string", "u16"]}
+            ]}},
+            {"name": "Player", "type": {"kind": "struct", "fields": [
+                {"name": "name", "type": "string"},
+                {"name": "owner", "type": "pubkey"},
+                {"name": "best", "type": {"option": "u64"}},
+                {"name": "items", "type": {"vec": {"defined": {"name": "Item"}}}},
+                {"name": "modes", "type": {"array": [{"defined": {"name": "Mode"}}, 3]}},
+                {"name": "rating", "type": "i128"},
+                {"name": "ratio", "type": "f32"}
+            ]}}
+        ]
+    }"#;
+
+    #[derive(borsh::BorshSerialize)]
+    struct Item {
+        id: u32,
+        rare: bool,
+    }
+
+    #[derive(borsh::BorshSerialize)]
+    #[allow(dead_code)]
+    enum Mode {
+        Casual,
+        Ranked { tier: u8 },
+        Custom(String, u16),
+    }
+
+    #[derive(borsh::BorshSerialize)]
+    struct SubmitScore {
+        score: u64,
+        delta: i16,
+        tag: String,
+        referrer: Option<[u8; 32]>,
+        items: Vec<Item>,
+        grid: [u8; 3],
+        mode: Mode,
+        big: u128,
+    }
+
+    #[derive(borsh::BorshSerialize)]
+    struct Player {
+        name: String,
+        owner: [u8; 32],
+        best: Option<u64>,
+        items: Vec<Item>,
+        modes: [Mode; 3],
+        rating: i128,
+        ratio: f32,
+    }
+
+    fn load(json: &str) -> *mut SolIdl {
+        let json = CString::new(json).unwrap();
+        let idl = sol_idl_load(json.as_ptr());
+        assert!(!idl.is_null());
+        idl
+    }
+
+    fn encode(idl: *const SolIdl, method: &CStr, args: &str) -> Option<Vec<u8>> {
+        let args = CString::new(args).unwrap();
+        let mut len = 0;
+        let data = sol_idl_encode_instruction(idl, method.as_ptr(), args.as_ptr(), &mut len);
+        if data.is_null() {
+            return None;
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(data, len) }.to_vec();
+        free_instruction_data(data, len);
+        Some(bytes)
+    }
+
+    #[test]
+    fn loads_items_and_program_id() {
+        let idl = load(COUNTER_IDL);
+        let names = |kind: SolIdlItemKind| {
+            let kind = kind as u32;
+            (0..sol_idl_item_count(idl, kind))
+                .map(|i| {
+                    unsafe { CStr::from_ptr(sol_idl_item_name(idl, kind, i)) }
+                        .to_str()
+                        .unwrap()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(SolIdlItemKind::Instruction),
+            ["decrement", "increment", "initialize"]
+        );
+        assert_eq!(names(SolIdlItemKind::Account), ["Counter"]);
+        assert_eq!(names(SolIdlItemKind::Type), ["Counter"]);
+        assert!(sol_idl_item_name(idl, SolIdlItemKind::Account as u32, 1).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        assert_eq!(sol_idl_item_count(idl, 3), 0);
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        assert!(sol_idl_item_name(idl, 3, 0).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        let mut program_id = SolPublicKey { data: [0; 32] };
+        assert!(sol_idl_program_id(idl, &mut program_id));
+        assert_eq!(
+            program_id.to_pubkey().to_string(),
+            "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy"
+        );
+        sol_idl_free(idl);
+
+        assert!(sol_idl_load(c"{\"instructions\": 3}".as_ptr()).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn encodes_args_like_borsh() {
+        let idl = load(GAME_IDL);
+        let referrer = Pubkey::new_unique();
+        let args = serde_json::json!({
+            "score": 1_000_000_000_000u64,
+            "delta": -5,
+            "tag": "speedrun",
+            "referrer": referrer.to_string(),
+            "items": [{"id": 7, "rare": true}, {"id": 9, "rare": false}],
+            "grid": [1, 2, 3],
+            "mode": {"Ranked": {"tier": 4}},
+            "big": "340282366920938463463374607431768211455",
+        });
+        let expected = SubmitScore {
+            score: 1_000_000_000_000,
+            delta: -5,
+            tag: "speedrun".to_string(),
+            referrer: Some(referrer.to_bytes()),
+            items: vec![Item { id: 7, rare: true }, Item { id: 9, rare: false }],
+            grid: [1, 2, 3],
+            mode: Mode::Ranked { tier: 4 },
+            big: u128::MAX,
+        };
+        let mut expected_data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        expected_data.extend(borsh::to_vec(&expected).unwrap());
+        assert_eq!(
+            encode(idl, c"submit_score", &args.to_string()),
+            Some(expected_data)
+        );
+
+        // Unit and tuple variants, and a missing option
+        let mut args = args;
+        args["referrer"] = Value::Null;
+        args["mode"] = serde_json::json!("Casual");
+        let data = encode(idl, c"submit_score", &args.to_string()).unwrap();
+        assert_eq!(data[data.len() - 17], 0);
+        args["mode"] = serde_json::json!({"Custom": ["x", 2]});
+        let data = encode(idl, c"submit_score", &args.to_string()).unwrap();
+        assert_eq!(
+            &data[data.len() - 24..data.len() - 16],
+            [2, 1, 0, 0, 0, b'x', 2, 0]
+        );
+
+        sol_idl_free(idl);
+    }
+
+    #[test]
+    fn rejects_args_that_do_not_match_the_idl() {
+        let idl = load(GAME_IDL);
+        let cases = [
+            "{}",
+            r#"{"score": -1}"#,
+            r#"{"score": 1, "delta": 40000}"#,
+            r#"{"score": 1, "delta": 1, "tag": 5}"#,
+        ];
+        for args in cases {
+            assert_eq!(encode(idl, c"submit_score", args), None, "{}", args);
+            assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        }
+        assert_eq!(encode(idl, c"no_such_method", "{}"), None);
+        sol_idl_free(idl);
+    }
+
+    // (pubkey, is_signer, is_writable) of each account of the first instruction
+    fn account_flags(transaction: &Transaction) -> Vec<(Pubkey, bool, bool)> {
+        let message = &transaction.message;
+        message.instructions[0]
+            .accounts
+            .iter()
+            .map(|&i| {
+                let i = i as usize;
+                (
+                    message.account_keys[i],
+                    message.is_signer(i),
+                    message.is_maybe_writable(i, None),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sends_with_the_idl_accounts_and_discriminator() {
+        let sent = SentTransaction::<Transaction>::default();
+        let rpc = MockRpc::start(recording_handler(&sent));
+        let client = new_sol_client(rpc.url.as_ptr());
+        let idl = load(COUNTER_IDL);
+        let mut payer = SolKeyPair::new();
+        let mut user = SolKeyPair::new();
+        let mut counter = SolKeyPair::new();
+        let send = |method: &CStr, accounts: &[SolPublicKey], signers: &[*mut SolKeyPair]| {
+            let signature = sol_idl_send_instruction(
+                client,
+                idl,
+                method.as_ptr(),
+                accounts.as_ptr(),
+                accounts.len(),
+                signers.as_ptr(),
+                signers.len(),
+                std::ptr::null(),
+                std::ptr::null(),
+            );
+            if signature.is_null() {
+                return None;
+            }
+            free_transaction_signature(signature);
+            sent.lock().unwrap().take()
+        };
+
+        let accounts = [
+            SolPublicKey::new(counter.get_pubkey()),
+            SolPublicKey::new(user.get_pubkey()),
+        ];
+        let transaction = send(c"increment", &accounts, &[&mut payer, &mut user]).unwrap();
+        let instruction = &transaction.message.instructions[0];
+        assert_eq!(
+            instruction
+                .program_id(&transaction.message.account_keys)
+                .to_string(),
+            "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy"
+        );
+        assert_eq!(instruction.data, [11, 18, 104, 9, 104, 174, 59, 33]);
+        assert_eq!(
+            account_flags(&transaction),
+            [
+                (counter.get_pubkey(), false, true),
+                (user.get_pubkey(), true, false),
+            ]
+        );
+
+        // `system_program` has a fixed address and is filled in
+        let transaction = send(c"initialize", &accounts, &[&mut user, &mut counter]).unwrap();
+        assert_eq!(
+            transaction.message.instructions[0].data,
+            [175, 175, 109, 31, 13, 152, 155, 237]
+        );
+        assert_eq!(
+            account_flags(&transaction),
+            [
+                (counter.get_pubkey(), true, true),
+                (user.get_pubkey(), true, true),
+                (solana_sdk::system_program::id(), false, false),
+            ]
+        );
+
+        assert!(send(c"increment", &accounts[..1], &[&mut payer, &mut user]).is_none());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        assert!(sent.lock().unwrap().is_none());
+
+        sol_idl_free(idl);
+        free_client(client);
+    }
+
+    fn decode(idl: *const SolIdl, account_type: &CStr, data: &[u8]) -> Option<Value> {
+        let json =
+            sol_idl_decode_account_data(idl, account_type.as_ptr(), data.as_ptr(), data.len());
+        if json.is_null() {
+            return None;
+        }
+        let value =
+            serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        free_idl_json(json);
+        Some(value)
+    }
+
+    #[test]
+    fn decodes_account_data_to_json() {
+        let idl = load(GAME_IDL);
+        let owner = Pubkey::new_unique();
+        let player = Player {
+            name: "ana".to_string(),
+            owner: owner.to_bytes(),
+            best: Some(u64::MAX),
+            items: vec![Item { id: 3, rare: true }],
+            modes: [
+                Mode::Casual,
+                Mode::Ranked { tier: 2 },
+                Mode::Custom("x".to_string(), 7),
+            ],
+            rating: -1,
+            ratio: 0.5,
+        };
+        let mut data = vec![9; 8];
+        data.extend(borsh::to_vec(&player).unwrap());
+        // Accounts are often allocated larger than their layout
+        data.extend([0; 16]);
+
+        assert_eq!(
+            decode(idl, c"Player", &data),
+            Some(serde_json::json!({
+                "name": "ana",
+                "owner": owner.to_string(),
+                "best": u64::MAX,
+                "items": [{"id": 3, "rare": true}],
+                "modes": ["Casual", {"Ranked": {"tier": 2}}, {"Custom": ["x", 7]}],
+                "rating": "-1",
+                "ratio": 0.5,
+            }))
+        );
+
+        // Wrong discriminator, truncated data, unknown account type
+        data[0] = 8;
+        assert_eq!(decode(idl, c"Player", &data), None);
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        assert_eq!(decode(idl, c"Player", &[9; 12]), None);
+        assert_eq!(decode(idl, c"Item", &[9; 12]), None);
+
+        sol_idl_free(idl);
+    }
+
+    #[test]
+    fn fetches_and_decodes_an_account() {
+        let count: u64 = 42;
+        let rpc = MockRpc::start(move |method, _params| match method {
+            "getAccountInfo" => {
+                let mut data = vec![255, 176, 4, 245, 188, 253, 124, 25];
+                data.extend(count.to_le_bytes());
+                Ok(with_context(serde_json::json!({
+                    "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                    "executable": false,
+                    "lamports": 1_000_000,
+                    "owner": "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy",
+                    "rentEpoch": 0,
+                    "space": 16,
+                })))
+            }
+            _ => Err(format!("mock does not implement {}", method)),
+        });
+        let client = new_sol_client(rpc.url.as_ptr());
+        let idl = load(COUNTER_IDL);
+        let counter = SolPublicKey::new(Pubkey::new_unique());
+
+        let json = sol_idl_decode_account(client, idl, c"Counter".as_ptr(), &counter);
+        assert!(!json.is_null());
+        assert_eq!(
+            unsafe { CStr::from_ptr(json) }.to_str().unwrap(),
+            r#"{"count":42}"#
+        );
+        free_idl_json(json);
+
+        sol_idl_free(idl);
+        free_client(client);
+    }
+
+    #[test]
+    fn lists_and_decodes_program_accounts() {
+        let program: Pubkey = "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy"
+            .parse()
+            .unwrap();
+        let counters = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let filters = Arc::new(Mutex::new(Value::Null));
+        let seen = Arc::clone(&filters);
+        let rpc = MockRpc::start(move |method, params| match method {
+            "getProgramAccounts" => {
+                *seen.lock().unwrap() = params[1]["filters"].clone();
+                let mut data = vec![255, 176, 4, 245, 188, 253, 124, 25];
+                let accounts = counters
+                    .iter()
+                    .zip([3u64, 4])
+                    .map(|(pubkey, count)| {
+                        data.truncate(8);
+                        data.extend(count.to_le_bytes());
+                        keyed_account(pubkey, &program, 1_000_000, &data)
+                    })
+                    .collect::<Vec<Value>>();
+                Ok(Value::Array(accounts))
+            }
+            _ => Err(format!("mock does not implement {}", method)),
+        });
+        let client = new_sol_client(rpc.url.as_ptr());
+        let idl = load(COUNTER_IDL);
+
+        let list = sol_idl_get_program_accounts(client, idl, c"Counter".as_ptr(), std::ptr::null());
+        let json = list_accounts(list)
+            .iter()
+            .map(|account| {
+                let json = unsafe { CStr::from_ptr(account.json) }.to_str().unwrap();
+                (account.pubkey.to_pubkey(), json.to_string())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            json,
+            [
+                (counters[0], r#"{"count":3}"#.to_string()),
+                (counters[1], r#"{"count":4}"#.to_string()),
+            ]
+        );
+        free_program_account_list(list);
+        // Only accounts with the discriminator
+        let filters = filters.lock().unwrap().clone();
+        assert_eq!(filters.as_array().map(Vec::len), Some(1));
+        assert_eq!(filters[0]["memcmp"]["offset"], 0);
+
+        let list = sol_idl_get_program_accounts(client, idl, c"Missing".as_ptr(), std::ptr::null());
+        assert!(list.is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        sol_idl_free(idl);
+        free_client(client);
+    }
+
+    #[test]
+    fn legacy_idl_flags_and_derived_discriminator() {
+        let idl = Idl::parse(
+            r#"{
+                "name": "legacy",
+                "instructions": [{
+                    "name": "stake",
+                    "accounts": [
+                        {"name": "owner", "isMut": true, "isSigner": true},
+                        {"name": "pool", "accounts": [
+                            {"name": "vault", "isMut": true, "isSigner": false},
+                            {"name": "mint", "isMut": false, "isSigner": false}
+                        ]}
+                    ],
+                    "args": [{"name": "amount", "type": "u64"}]
+                }],
+                "metadata": {"address": "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy"}
+            }"#,
+        )
+        .unwrap();
+        let instruction = idl.instruction("stake").unwrap();
+        assert_eq!(instruction.discriminator(), get_discriminator("stake"));
+        assert!(idl.program_id().is_ok());
+
+        let keys = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let metas = instruction
+            .account_metas(&idl, &keys, &Value::Null)
+            .unwrap();
+        assert_eq!(
+            metas,
+            [
+                AccountMeta::new(keys[0], true),
+                AccountMeta::new(keys[1], false),
+                AccountMeta::new_readonly(keys[2], false),
+            ]
+        );
+        assert!(instruction
+            .account_metas(&idl, &keys[..2], &Value::Null)
+            .is_err());
+
+        let idl = Idl::parse(
+            r#"{"accounts": [{"name": "Pool", "type": {"kind": "struct", "fields": [
+                {"name": "total", "type": "u64"}
+            ]}}]}"#,
+        )
+        .unwrap();
+        let mut data = sighash("account", "Pool");
+        data.extend(7u64.to_le_bytes());
+        assert_eq!(
+            idl.decode_account("Pool", &data).unwrap(),
+            serde_json::json!({"total": 7})
+        );
+    }
+
+    #[test]
+    fn decodes_zero_sized_and_recursive_types() {
+        let idl = Idl::parse(
+            r#"{
+                "accounts": [{"name": "Node", "type": {"kind": "struct", "fields": [
+                    {"name": "markers", "type": {"vec": {"defined": "Marker"}}},
+                    {"name": "next", "type": {"option": {"defined": "Node"}}}
+                ]}}],
+                "types": [{"name": "Marker", "type": {"kind": "struct", "fields": []}}]
+            }"#,
+        )
+        .unwrap();
+        let discriminator = sighash("account", "Node");
+
+        // Zero-sized elements take no bytes, so the length may exceed the data left
+        let mut data = discriminator.clone();
+        data.extend(3u32.to_le_bytes());
+        data.push(0);
+        assert_eq!(
+            idl.decode_account("Node", &data).unwrap(),
+            serde_json::json!({"markers": [{}, {}, {}], "next": null})
+        );
+
+        // A chain of nodes nested past the depth limit fails instead of
+        // overflowing the stack
+        let mut data = discriminator;
+        for _ in 0..100 {
+            data.extend([0, 0, 0, 0, 1]);
+        }
+        match idl.decode_account("Node", &data) {
+            Err(SolError::InvalidInput(message)) => assert!(message.contains("levels deep")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
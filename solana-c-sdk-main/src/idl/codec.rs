@@ -0,0 +1,433 @@
+// Borsh encoding and decoding of JSON values following IDL types.
+//
+// JSON mapping: integers are numbers, or decimal strings for values beyond what
+// JSON numbers hold exactly (u64/i64/u128/i128); pubkeys are base58 strings;
+// `bytes` is an array of numbers; options are `null` or the value; structs are
+// objects (or arrays for tuple structs); enum values are `"Variant"` or
+// `{"Variant": fields}`.
+
+use serde_json::Value;
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+
+use super::{Idl, IdlFields, IdlType, IdlTypeDefTy};
+use crate::error::SolError;
+
+fn mismatch(path: &str, expected: &str, value: &Value) -> SolError {
+    SolError::InvalidInput(format!("{}: expected {}, got {}", path, expected, value))
+}
+
+fn unsigned(value: &Value, path: &str, bits: u32) -> Result<u128, SolError> {
+    let number = match value {
+        Value::Number(n) => n.as_u64().map(u128::from),
+        Value::String(s) => s.parse::<u128>().ok(),
+        _ => None,
+    };
+    number
+        .filter(|n| bits == 128 || *n >> bits == 0)
+        .ok_or_else(|| mismatch(path, &format!("u{}", bits), value))
+}
+
+fn signed(value: &Value, path: &str, bits: u32) -> Result<i128, SolError> {
+    let number = match value {
+        Value::Number(n) => n.as_i64().map(i128::from),
+        Value::String(s) => s.parse::<i128>().ok(),
+        _ => None,
+    };
+    let (min, max) = match bits {
+        128 => (i128::MIN, i128::MAX),
+        _ => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+    };
+    number
+        .filter(|n| (min..=max).contains(n))
+        .ok_or_else(|| mismatch(path, &format!("i{}", bits), value))
+}
+
+// How deeply types may nest, which bounds the recursion through `defined`
+// types that refer to themselves
+const MAX_DEPTH: usize = 64;
+
+// The depth one level below `depth`, or an error past `MAX_DEPTH`
+fn descend(depth: usize, path: &str) -> Result<usize, SolError> {
+    if depth >= MAX_DEPTH {
+        return Err(SolError::InvalidInput(format!(
+            "{}: types nest more than {} levels deep",
+            path, MAX_DEPTH
+        )));
+    }
+    Ok(depth + 1)
+}
+
+fn length(len: usize, out: &mut Vec<u8>) {
+    out.extend_from_slice(&(len as u32).to_le_bytes());
+}
+
+// Append the Borsh encoding of `value` as a `ty` to `out`. `path` names the
+// value in error messages and `depth` is how deeply it is nested (0 at the top).
+pub(super) fn encode(
+    idl: &Idl,
+    ty: &IdlType,
+    value: &Value,
+    path: &str,
+    out: &mut Vec<u8>,
+    depth: usize,
+) -> Result<(), SolError> {
+    let depth = descend(depth, path)?;
+    match ty {
+        IdlType::Bool => out.push(
+            value
+                .as_bool()
+                .ok_or_else(|| mismatch(path, "bool", value))? as u8,
+        ),
+        IdlType::U8 => out.push(unsigned(value, path, 8)? as u8),
+        IdlType::U16 => out.extend_from_slice(&(unsigned(value, path, 16)? as u16).to_le_bytes()),
+        IdlType::U32 => out.extend_from_slice(&(unsigned(value, path, 32)? as u32).to_le_bytes()),
+        IdlType::U64 => out.extend_from_slice(&(unsigned(value, path, 64)? as u64).to_le_bytes()),
+        IdlType::U128 => out.extend_from_slice(&unsigned(value, path, 128)?.to_le_bytes()),
+        IdlType::I8 => out.push(signed(value, path, 8)? as i8 as u8),
+        IdlType::I16 => out.extend_from_slice(&(signed(value, path, 16)? as i16).to_le_bytes()),
+        IdlType::I32 => out.extend_from_slice(&(signed(value, path, 32)? as i32).to_le_bytes()),
+        IdlType::I64 => out.extend_from_slice(&(signed(value, path, 64)? as i64).to_le_bytes()),
+        IdlType::I128 => out.extend_from_slice(&signed(value, path, 128)?.to_le_bytes()),
+        IdlType::F32 => {
+            let number = value.as_f64().ok_or_else(|| mismatch(path, "f32", value))?;
+            out.extend_from_slice(&(number as f32).to_le_bytes())
+        }
+        IdlType::F64 => {
+            let number = value.as_f64().ok_or_else(|| mismatch(path, "f64", value))?;
+            out.extend_from_slice(&number.to_le_bytes())
+        }
+        IdlType::String => {
+            let string = value
+                .as_str()
+                .ok_or_else(|| mismatch(path, "string", value))?;
+            length(string.len(), out);
+            out.extend_from_slice(string.as_bytes());
+        }
+        IdlType::Bytes => {
+            let items = value
+                .as_array()
+                .ok_or_else(|| mismatch(path, "array of bytes", value))?;
+            length(items.len(), out);
+            for (i, item) in items.iter().enumerate() {
+                out.push(unsigned(item, &format!("{}[{}]", path, i), 8)? as u8);
+            }
+        }
+        IdlType::Pubkey => {
+            let pubkey = value
+                .as_str()
+                .and_then(|s| Pubkey::from_str(s).ok())
+                .ok_or_else(|| mismatch(path, "base58 pubkey", value))?;
+            out.extend_from_slice(pubkey.as_ref());
+        }
+        IdlType::Option(inner) => match value {
+            Value::Null => out.push(0),
+            value => {
+                out.push(1);
+                encode(idl, inner, value, path, out, depth)?;
+            }
+        },
+        IdlType::Vec(inner) => {
+            let items = value
+                .as_array()
+                .ok_or_else(|| mismatch(path, "array", value))?;
+            length(items.len(), out);
+            for (i, item) in items.iter().enumerate() {
+                encode(idl, inner, item, &format!("{}[{}]", path, i), out, depth)?;
+            }
+        }
+        IdlType::Array(inner, len) => {
+            let items = value
+                .as_array()
+                .filter(|items| items.len() == *len)
+                .ok_or_else(|| mismatch(path, &format!("array of {} items", len), value))?;
+            for (i, item) in items.iter().enumerate() {
+                encode(idl, inner, item, &format!("{}[{}]", path, i), out, depth)?;
+            }
+        }
+        IdlType::Defined(name) => {
+            let def = idl.type_def(name).ok_or_else(|| {
+                SolError::InvalidInput(format!("{}: IDL has no type '{}'", path, name))
+            })?;
+            encode_defined(idl, def, value, path, out, depth)?;
+        }
+    }
+    Ok(())
+}
+
+fn encode_defined(
+    idl: &Idl,
+    def: &IdlTypeDefTy,
+    value: &Value,
+    path: &str,
+    out: &mut Vec<u8>,
+    depth: usize,
+) -> Result<(), SolError> {
+    match def {
+        IdlTypeDefTy::Struct { fields } => encode_fields(idl, fields, value, path, out, depth),
+        IdlTypeDefTy::Enum { variants } => {
+            // `"Variant"` for variants without fields, `{"Variant": fields}` otherwise
+            let (name, fields_value) = match value {
+                Value::String(name) => (name.as_str(), &Value::Null),
+                Value::Object(map) if map.len() == 1 => {
+                    let (name, fields) = map.iter().next().unwrap();
+                    (name.as_str(), fields)
+                }
+                _ => return Err(mismatch(path, "enum variant", value)),
+            };
+            let (index, variant) = variants
+                .iter()
+                .enumerate()
+                .find(|(_, variant)| variant.name == name)
+                .ok_or_else(|| {
+                    SolError::InvalidInput(format!("{}: unknown enum variant '{}'", path, name))
+                })?;
+            out.push(index as u8);
+            if variant.fields.is_empty() {
+                return Ok(());
+            }
+            encode_fields(
+                idl,
+                &variant.fields,
+                fields_value,
+                &format!("{}.{}", path, name),
+                out,
+                depth,
+            )
+        }
+        IdlTypeDefTy::Type { alias } => encode(idl, alias, value, path, out, depth),
+    }
+}
+
+fn encode_fields(
+    idl: &Idl,
+    fields: &IdlFields,
+    value: &Value,
+    path: &str,
+    out: &mut Vec<u8>,
+    depth: usize,
+) -> Result<(), SolError> {
+    match fields {
+        IdlFields::Named(fields) => {
+            let object = value
+                .as_object()
+                .ok_or_else(|| mismatch(path, "object", value))?;
+            for field in fields {
+                let path = format!("{}.{}", path, field.name);
+                let value = object
+                    .get(&field.name)
+                    .ok_or_else(|| SolError::InvalidInput(format!("{}: missing field", path)))?;
+                encode(idl, &field.ty, value, &path, out, depth)?;
+            }
+        }
+        IdlFields::Tuple(types) => {
+            let items = value
+                .as_array()
+                .filter(|items| items.len() == types.len())
+                .ok_or_else(|| mismatch(path, &format!("array of {} items", types.len()), value))?;
+            for (i, (ty, item)) in types.iter().zip(items).enumerate() {
+                encode(idl, ty, item, &format!("{}[{}]", path, i), out, depth)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// ==================== Decoding ==================== //
+
+fn truncated(path: &str) -> SolError {
+    SolError::InvalidInput(format!("{}: data ends early", path))
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize, path: &str) -> Result<&'a [u8], SolError> {
+    if data.len() < len {
+        return Err(truncated(path));
+    }
+    let (head, rest) = data.split_at(len);
+    *data = rest;
+    Ok(head)
+}
+
+fn take_array<const N: usize>(data: &mut &[u8], path: &str) -> Result<[u8; N], SolError> {
+    Ok(take(data, N, path)?.try_into().unwrap())
+}
+
+fn take_len(data: &mut &[u8], path: &str) -> Result<usize, SolError> {
+    Ok(u32::from_le_bytes(take_array(data, path)?) as usize)
+}
+
+fn float(number: f64) -> Value {
+    serde_json::Number::from_f64(number).map_or(Value::Null, Value::Number)
+}
+
+// Whether values of `ty` encode to no bytes at all, like empty structs and
+// zero-length arrays
+pub(super) fn zero_sized(
+    idl: &Idl,
+    ty: &IdlType,
+    path: &str,
+    depth: usize,
+) -> Result<bool, SolError> {
+    let depth = descend(depth, path)?;
+    Ok(match ty {
+        IdlType::Array(inner, len) => *len == 0 || zero_sized(idl, inner, path, depth)?,
+        IdlType::Defined(name) => match idl.type_def(name) {
+            Some(IdlTypeDefTy::Struct { fields }) => {
+                let types: Vec<&IdlType> = match fields {
+                    IdlFields::Named(fields) => fields.iter().map(|field| &field.ty).collect(),
+                    IdlFields::Tuple(types) => types.iter().collect(),
+                };
+                for ty in types {
+                    if !zero_sized(idl, ty, path, depth)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            Some(IdlTypeDefTy::Type { alias }) => zero_sized(idl, alias, path, depth)?,
+            Some(IdlTypeDefTy::Enum { .. }) | None => false,
+        },
+        _ => false,
+    })
+}
+
+// Decode a `ty` from the front of `data`, advancing it, into the JSON mapping
+// `encode` accepts. 128-bit integers are returned as decimal strings.
+pub(super) fn decode(
+    idl: &Idl,
+    ty: &IdlType,
+    data: &mut &[u8],
+    path: &str,
+    depth: usize,
+) -> Result<Value, SolError> {
+    let depth = descend(depth, path)?;
+    Ok(match ty {
+        IdlType::Bool => match take_array::<1>(data, path)?[0] {
+            0 => Value::Bool(false),
+            1 => Value::Bool(true),
+            byte => {
+                return Err(SolError::InvalidInput(format!(
+                    "{}: invalid bool {}",
+                    path, byte
+                )))
+            }
+        },
+        IdlType::U8 => Value::from(take_array::<1>(data, path)?[0]),
+        IdlType::U16 => Value::from(u16::from_le_bytes(take_array(data, path)?)),
+        IdlType::U32 => Value::from(u32::from_le_bytes(take_array(data, path)?)),
+        IdlType::U64 => Value::from(u64::from_le_bytes(take_array(data, path)?)),
+        IdlType::U128 => Value::from(u128::from_le_bytes(take_array(data, path)?).to_string()),
+        IdlType::I8 => Value::from(i8::from_le_bytes(take_array(data, path)?)),
+        IdlType::I16 => Value::from(i16::from_le_bytes(take_array(data, path)?)),
+        IdlType::I32 => Value::from(i32::from_le_bytes(take_array(data, path)?)),
+        IdlType::I64 => Value::from(i64::from_le_bytes(take_array(data, path)?)),
+        IdlType::I128 => Value::from(i128::from_le_bytes(take_array(data, path)?).to_string()),
+        IdlType::F32 => float(f32::from_le_bytes(take_array(data, path)?) as f64),
+        IdlType::F64 => float(f64::from_le_bytes(take_array(data, path)?)),
+        IdlType::String => {
+            let len = take_len(data, path)?;
+            let bytes = take(data, len, path)?;
+            let string = std::str::from_utf8(bytes)
+                .map_err(|_| SolError::InvalidInput(format!("{}: invalid UTF-8", path)))?;
+            Value::from(string)
+        }
+        IdlType::Bytes => {
+            let len = take_len(data, path)?;
+            Value::from(take(data, len, path)?.to_vec())
+        }
+        IdlType::Pubkey => Value::from(Pubkey::new_from_array(take_array(data, path)?).to_string()),
+        IdlType::Option(inner) => match take_array::<1>(data, path)?[0] {
+            0 => Value::Null,
+            1 => decode(idl, inner, data, path, depth)?,
+            tag => {
+                return Err(SolError::InvalidInput(format!(
+                    "{}: invalid option tag {}",
+                    path, tag
+                )))
+            }
+        },
+        IdlType::Vec(inner) => {
+            let len = take_len(data, path)?;
+            // Unless the elements are zero-sized, each takes at least one
+            // byte, so a bogus length fails here
+            if len > data.len() && !zero_sized(idl, inner, path, depth)? {
+                return Err(truncated(path));
+            }
+            (0..len)
+                .map(|i| decode(idl, inner, data, &format!("{}[{}]", path, i), depth))
+                .collect::<Result<Vec<Value>, SolError>>()?
+                .into()
+        }
+        IdlType::Array(inner, len) => (0..*len)
+            .map(|i| decode(idl, inner, data, &format!("{}[{}]", path, i), depth))
+            .collect::<Result<Vec<Value>, SolError>>()?
+            .into(),
+        IdlType::Defined(name) => {
+            let def = idl.type_def(name).ok_or_else(|| {
+                SolError::InvalidInput(format!("{}: IDL has no type '{}'", path, name))
+            })?;
+            decode_defined(idl, def, data, path, depth)?
+        }
+    })
+}
+
+pub(super) fn decode_defined(
+    idl: &Idl,
+    def: &IdlTypeDefTy,
+    data: &mut &[u8],
+    path: &str,
+    depth: usize,
+) -> Result<Value, SolError> {
+    match def {
+        IdlTypeDefTy::Struct { fields } => decode_fields(idl, fields, data, path, depth),
+        IdlTypeDefTy::Enum { variants } => {
+            let index = take_array::<1>(data, path)?[0];
+            let variant = variants.get(index as usize).ok_or_else(|| {
+                SolError::InvalidInput(format!("{}: invalid enum variant {}", path, index))
+            })?;
+            if variant.fields.is_empty() {
+                return Ok(Value::from(variant.name.as_str()));
+            }
+            let fields = decode_fields(
+                idl,
+                &variant.fields,
+                data,
+                &format!("{}.{}", path, variant.name),
+                depth,
+            )?;
+            Ok(serde_json::json!({ variant.name.as_str(): fields }))
+        }
+        IdlTypeDefTy::Type { alias } => decode(idl, alias, data, path, depth),
+    }
+}
+
+pub(super) fn decode_fields(
+    idl: &Idl,
+    fields: &IdlFields,
+    data: &mut &[u8],
+    path: &str,
+    depth: usize,
+) -> Result<Value, SolError> {
+    match fields {
+        IdlFields::Named(fields) => {
+            let mut object = serde_json::Map::new();
+            for field in fields {
+                let value = decode(
+                    idl,
+                    &field.ty,
+                    data,
+                    &format!("{}.{}", path, field.name),
+                    depth,
+                )?;
+                object.insert(field.name.clone(), value);
+            }
+            Ok(Value::Object(object))
+        }
+        IdlFields::Tuple(types) => Ok(types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| decode(idl, ty, data, &format!("{}[{}]", path, i), depth))
+            .collect::<Result<Vec<Value>, SolError>>()?
+            .into()),
+    }
+}
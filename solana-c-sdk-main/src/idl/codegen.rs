@@ -0,0 +1,1495 @@
+// C header generation from an Anchor IDL: one C type per IDL type with Borsh
+// write/read/free functions, one send function per instruction and a
+// decode/fetch pair per account type. Driven by the `idl_codegen` binary; the
+// output only depends on `solana_sdk.h` and the C standard library.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use solana_program::pubkey::Pubkey;
+
+use super::{
+    codec, Idl, IdlEnumVariant, IdlFields, IdlInstruction, IdlInstructionAccount, IdlType,
+    IdlTypeDefTy,
+};
+use crate::error::SolError;
+
+// Buffer used to fetch accounts whose layout has no maximum size
+const FETCH_BUFFER_SIZE: usize = 10240;
+
+// Parameters every instruction function takes
+const RESERVED_PARAMS: &[&str] = &["client", "program_id", "signers", "signer_count"];
+
+// C and C++ keywords that cannot be used as identifiers
+const KEYWORDS: &[&str] = &[
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "operator",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "union",
+    "unsigned",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+// Borsh helpers shared by every generated header
+const RUNTIME: &str = r#"#ifndef SOL_IDL_RUNTIME
+#define SOL_IDL_RUNTIME
+
+// Growable buffer instruction data is Borsh-encoded into
+typedef struct SolIdlWriter {
+  uint8_t *data;
+  uintptr_t len;
+  uintptr_t cap;
+  bool failed;
+} SolIdlWriter;
+
+// Cursor over Borsh-encoded account data
+typedef struct SolIdlReader {
+  const uint8_t *data;
+  uintptr_t len;
+  bool failed;
+} SolIdlReader;
+
+static inline void sol_idl_write(SolIdlWriter *w, const void *bytes, uintptr_t len) {
+  if (w->failed || len == 0) {
+    return;
+  }
+  if (w->cap - w->len < len) {
+    uintptr_t cap = w->cap ? w->cap : 64;
+    while (cap - w->len < len) {
+      cap *= 2;
+    }
+    uint8_t *data = (uint8_t *)realloc(w->data, cap);
+    if (!data) {
+      w->failed = true;
+      return;
+    }
+    w->data = data;
+    w->cap = cap;
+  }
+  memcpy(w->data + w->len, bytes, len);
+  w->len += len;
+}
+
+// Little-endian integer of `size` bytes
+static inline void sol_idl_write_uint(SolIdlWriter *w, uint64_t value, int size) {
+  uint8_t bytes[8];
+  for (int i = 0; i < size; i++) {
+    bytes[i] = (uint8_t)(value >> (8 * i));
+  }
+  sol_idl_write(w, bytes, (uintptr_t)size);
+}
+
+static inline void sol_idl_write_f32(SolIdlWriter *w, float value) {
+  uint32_t bits;
+  memcpy(&bits, &value, sizeof(bits));
+  sol_idl_write_uint(w, bits, 4);
+}
+
+static inline void sol_idl_write_f64(SolIdlWriter *w, double value) {
+  uint64_t bits;
+  memcpy(&bits, &value, sizeof(bits));
+  sol_idl_write_uint(w, bits, 8);
+}
+
+static inline void sol_idl_write_string(SolIdlWriter *w, const char *value) {
+  if (!value) {
+    w->failed = true;
+    return;
+  }
+  uintptr_t len = strlen(value);
+  sol_idl_write_uint(w, len, 4);
+  sol_idl_write(w, value, len);
+}
+
+static inline const uint8_t *sol_idl_read(SolIdlReader *r, uintptr_t len) {
+  if (r->failed || r->len < len) {
+    r->failed = true;
+    return NULL;
+  }
+  const uint8_t *bytes = r->data;
+  r->data += len;
+  r->len -= len;
+  return bytes;
+}
+
+static inline void sol_idl_read_into(SolIdlReader *r, void *out, uintptr_t len) {
+  const uint8_t *bytes = sol_idl_read(r, len);
+  if (bytes) {
+    memcpy(out, bytes, len);
+  }
+}
+
+static inline uint64_t sol_idl_read_uint(SolIdlReader *r, int size) {
+  const uint8_t *bytes = sol_idl_read(r, (uintptr_t)size);
+  uint64_t value = 0;
+  for (int i = 0; bytes && i < size; i++) {
+    value |= (uint64_t)bytes[i] << (8 * i);
+  }
+  return value;
+}
+
+static inline float sol_idl_read_f32(SolIdlReader *r) {
+  uint32_t bits = (uint32_t)sol_idl_read_uint(r, 4);
+  float value;
+  memcpy(&value, &bits, sizeof(value));
+  return value;
+}
+
+static inline double sol_idl_read_f64(SolIdlReader *r) {
+  uint64_t bits = sol_idl_read_uint(r, 8);
+  double value;
+  memcpy(&value, &bits, sizeof(value));
+  return value;
+}
+
+static inline char *sol_idl_read_string(SolIdlReader *r) {
+  uintptr_t len = (uintptr_t)sol_idl_read_uint(r, 4);
+  const uint8_t *bytes = sol_idl_read(r, len);
+  char *value = bytes ? (char *)malloc(len + 1) : NULL;
+  if (!value) {
+    r->failed = true;
+    return NULL;
+  }
+  memcpy(value, bytes, len);
+  value[len] = '\0';
+  return value;
+}
+
+static inline uint8_t *sol_idl_read_bytes(SolIdlReader *r, uintptr_t *len) {
+  *len = 0;
+  uintptr_t count = (uintptr_t)sol_idl_read_uint(r, 4);
+  const uint8_t *bytes = sol_idl_read(r, count);
+  uint8_t *value = bytes ? (uint8_t *)malloc(count ? count : 1) : NULL;
+  if (!value) {
+    r->failed = true;
+    return NULL;
+  }
+  memcpy(value, bytes, count);
+  *len = count;
+  return value;
+}
+
+// Zeroed storage for a Borsh vector. Unless the elements encode to zero bytes,
+// each takes at least one, so a count beyond the bytes left is bogus
+static inline void *sol_idl_read_vec(SolIdlReader *r,
+                                     uintptr_t size,
+                                     bool zero_sized,
+                                     uintptr_t *len) {
+  *len = 0;
+  uintptr_t count = (uintptr_t)sol_idl_read_uint(r, 4);
+  if (r->failed || (!zero_sized && count > r->len)) {
+    r->failed = true;
+    return NULL;
+  }
+  void *items = calloc(count ? count : 1, size);
+  if (!items) {
+    r->failed = true;
+    return NULL;
+  }
+  *len = count;
+  return items;
+}
+
+static inline SolAccountMeta sol_idl_account_meta(const SolPublicKey *pubkey,
+                                                  bool is_signer,
+                                                  bool is_writable) {
+  SolAccountMeta meta;
+  meta.pubkey = *pubkey;
+  meta.is_signer = is_signer;
+  meta.is_writable = is_writable;
+  return meta;
+}
+
+#endif
+"#;
+
+// Generate the C header for `idl_json`. `name` prefixes every generated
+// identifier and defaults to the program name recorded in the IDL.
+pub fn generate_c_header(idl_json: &str, name: Option<&str>) -> Result<String, SolError> {
+    let idl = Idl::parse(idl_json)?;
+    let name = name
+        .or_else(|| idl.name())
+        .ok_or_else(|| SolError::InvalidInput("IDL has no program name".to_string()))?;
+    if words(name).is_empty() {
+        return Err(SolError::InvalidInput(format!(
+            "invalid program name '{}'",
+            name
+        )));
+    }
+    Generator::new(&idl, name).header()
+}
+
+fn unsupported(ty: &IdlType) -> SolError {
+    SolError::InvalidInput(format!("unsupported IDL type for C: {:?}", ty))
+}
+
+// ==================== Naming ==================== //
+
+// Lowercase words of a camelCase, PascalCase or snake_case name
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev: Option<char> = None;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev = None;
+            continue;
+        }
+        let boundary = c.is_ascii_uppercase()
+            && prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit());
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c.to_ascii_lowercase());
+        prev = Some(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn snake_case(name: &str) -> String {
+    words(name).join("_")
+}
+
+fn pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+// A snake_case C identifier for an IDL field, argument or account name
+fn ident(name: &str) -> String {
+    let mut ident = snake_case(name);
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert_str(0, "field_");
+    }
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    ident
+}
+
+// `type name`, without a space after a pointer type
+fn declare(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{}{}", ty, name)
+    } else {
+        format!("{} {}", ty, name)
+    }
+}
+
+fn byte_list(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn indent(lines: Vec<String>) -> Vec<String> {
+    lines
+        .into_iter()
+        .map(|line| {
+            if line.is_empty() {
+                line
+            } else {
+                format!("  {}", line)
+            }
+        })
+        .collect()
+}
+
+// `name(params) {`, one parameter per line like the SDK header
+fn signature(prefix: &str, params: &[String], end: &str) -> String {
+    let align = " ".repeat(prefix.len() + 1);
+    format!(
+        "{}({}){}",
+        prefix,
+        params.join(&format!(",\n{}", align)),
+        end
+    )
+}
+
+// C type and Borsh size of fixed-width IDL types
+fn scalar(ty: &IdlType) -> Option<(&'static str, usize)> {
+    Some(match ty {
+        IdlType::Bool => ("bool", 1),
+        IdlType::U8 => ("uint8_t", 1),
+        IdlType::I8 => ("int8_t", 1),
+        IdlType::U16 => ("uint16_t", 2),
+        IdlType::I16 => ("int16_t", 2),
+        IdlType::U32 => ("uint32_t", 4),
+        IdlType::I32 => ("int32_t", 4),
+        IdlType::F32 => ("float", 4),
+        IdlType::U64 => ("uint64_t", 8),
+        IdlType::I64 => ("int64_t", 8),
+        IdlType::F64 => ("double", 8),
+        _ => return None,
+    })
+}
+
+// Named fields keep their (sanitized) names; tuple fields become `_0`, `_1`...
+fn field_list(fields: &IdlFields) -> Vec<(String, &IdlType)> {
+    match fields {
+        IdlFields::Named(fields) => fields
+            .iter()
+            .map(|field| (ident(&field.name), &field.ty))
+            .collect(),
+        IdlFields::Tuple(types) => types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| (format!("_{}", i), ty))
+            .collect(),
+    }
+}
+
+fn is_unit_enum(def: &IdlTypeDefTy) -> bool {
+    matches!(def, IdlTypeDefTy::Enum { variants } if variants.iter().all(|v| v.fields.is_empty()))
+}
+
+// Where a struct field or argument lives, with the companion `has_`/`_len`
+// values optional, vector and bytes fields carry
+struct Place {
+    value: String,
+    has: String,
+    len: String,
+}
+
+impl Place {
+    fn new(prefix: &str, name: &str) -> Self {
+        Place {
+            value: format!("{}{}", prefix, name),
+            has: format!("{}has_{}", prefix, name),
+            len: format!("{}{}_len", prefix, name),
+        }
+    }
+}
+
+// ==================== Generator ==================== //
+
+struct Generator<'a> {
+    idl: &'a Idl,
+    // snake_case prefix for functions, PascalCase for types, UPPER_CASE for macros
+    prefix: String,
+    type_prefix: String,
+    macro_prefix: String,
+}
+
+impl<'a> Generator<'a> {
+    fn new(idl: &'a Idl, name: &str) -> Self {
+        Generator {
+            idl,
+            prefix: snake_case(name),
+            type_prefix: pascal_case(name),
+            macro_prefix: snake_case(name).to_ascii_uppercase(),
+        }
+    }
+
+    // `types`, then account layouts only declared inline (legacy IDLs)
+    fn definitions(&self) -> Vec<(&'a str, &'a IdlTypeDefTy)> {
+        let mut defs: Vec<_> = self
+            .idl
+            .types
+            .iter()
+            .map(|def| (def.name.as_str(), &def.ty))
+            .collect();
+        for account in &self.idl.accounts {
+            if let Some(ty) = &account.ty {
+                if !defs.iter().any(|(name, _)| *name == account.name) {
+                    defs.push((account.name.as_str(), ty));
+                }
+            }
+        }
+        defs
+    }
+
+    fn def(&self, name: &str) -> Result<&'a IdlTypeDefTy, SolError> {
+        self.idl
+            .type_def(name)
+            .ok_or_else(|| SolError::InvalidInput(format!("IDL has no type '{}'", name)))
+    }
+
+    fn type_name(&self, name: &str) -> String {
+        format!("{}{}", self.type_prefix, pascal_case(name))
+    }
+
+    fn function(&self, action: &str, name: &str) -> String {
+        format!("{}_{}_{}", self.prefix, action, snake_case(name))
+    }
+
+    fn program_id_macro(&self) -> String {
+        format!("{}_PROGRAM_ID", self.macro_prefix)
+    }
+
+    fn header(&self) -> Result<String, SolError> {
+        let guard = format!("{}_IDL_H", self.macro_prefix);
+        let mut out = vec![
+            format!(
+                "// Generated by idl_codegen from the `{}` IDL. Do not edit.",
+                self.prefix
+            ),
+            String::new(),
+            format!("#ifndef {}", guard),
+            format!("#define {}", guard),
+            String::new(),
+            "#include <stdbool.h>".to_string(),
+            "#include <stdint.h>".to_string(),
+            "#include <stdlib.h>".to_string(),
+            "#include <string.h>".to_string(),
+            String::new(),
+            "#include \"solana_sdk.h\"".to_string(),
+            String::new(),
+        ];
+        if let Ok(program_id) = self.idl.program_id() {
+            out.push(format!(
+                "#define {} \"{}\"",
+                self.program_id_macro(),
+                program_id
+            ));
+            out.push(String::new());
+        }
+        out.push(RUNTIME.to_string());
+        out.extend(self.types()?);
+        out.extend(self.type_functions()?);
+        for instruction in &self.idl.instructions {
+            out.extend(self.instruction(instruction)?);
+        }
+        for account in &self.idl.accounts {
+            out.extend(self.account(&account.name, &account.discriminator())?);
+        }
+        out.push(format!("#endif /* {} */", guard));
+        Ok(out.join("\n") + "\n")
+    }
+
+    // ==================== Declarations ==================== //
+
+    // C type of a vector or array element
+    fn element_type(&self, ty: &IdlType) -> Result<String, SolError> {
+        if let Some((c_type, _)) = scalar(ty) {
+            return Ok(c_type.to_string());
+        }
+        match ty {
+            IdlType::String => Ok("char *".to_string()),
+            IdlType::Pubkey => Ok("SolPublicKey".to_string()),
+            IdlType::Defined(name) => {
+                self.def(name)?;
+                Ok(self.type_name(name))
+            }
+            _ => Err(unsupported(ty)),
+        }
+    }
+
+    // Element type as seen through a `const` argument
+    fn const_element_type(&self, ty: &IdlType) -> Result<String, SolError> {
+        match ty {
+            IdlType::String => Ok("const char *const".to_string()),
+            _ => Ok(format!("const {}", self.element_type(ty)?)),
+        }
+    }
+
+    // Struct member or function parameter declarations for one field
+    fn declarations(&self, ty: &IdlType, name: &str, param: bool) -> Result<Vec<String>, SolError> {
+        let qualifier = if param { "const " } else { "" };
+        let element = |ty: &IdlType| {
+            if param {
+                self.const_element_type(ty)
+            } else {
+                self.element_type(ty)
+            }
+        };
+        Ok(match ty {
+            IdlType::U128 | IdlType::I128 => vec![format!("{}uint8_t {}[16]", qualifier, name)],
+            IdlType::String => vec![format!("{}char *{}", qualifier, name)],
+            IdlType::Bytes => vec![
+                format!("{}uint8_t *{}", qualifier, name),
+                format!("uintptr_t {}_len", name),
+            ],
+            IdlType::Option(inner) => {
+                if matches!(
+                    **inner,
+                    IdlType::Option(_) | IdlType::Vec(_) | IdlType::Bytes
+                ) {
+                    return Err(unsupported(ty));
+                }
+                let mut declarations = vec![format!("bool has_{}", name)];
+                declarations.extend(self.declarations(inner, name, param)?);
+                declarations
+            }
+            IdlType::Vec(inner) => vec![
+                declare(&element(inner)?, &format!("*{}", name)),
+                format!("uintptr_t {}_len", name),
+            ],
+            IdlType::Array(inner, len) => {
+                vec![declare(&element(inner)?, &format!("{}[{}]", name, len))]
+            }
+            _ => vec![declare(&self.element_type(ty)?, name)],
+        })
+    }
+
+    // Enums first, then forward declarations so pointers can refer to any
+    // struct, then the definitions in dependency order
+    fn types(&self) -> Result<Vec<String>, SolError> {
+        let defs = self.definitions();
+        let mut out = Vec::new();
+        for (name, def) in &defs {
+            if let IdlTypeDefTy::Enum { variants } = def {
+                let (enum_name, what) = if is_unit_enum(def) {
+                    (self.type_name(name), "")
+                } else {
+                    (format!("{}Kind", self.type_name(name)), "Kind")
+                };
+                out.push(format!("typedef enum {} {{", enum_name));
+                for (i, variant) in variants.iter().enumerate() {
+                    out.push(format!(
+                        "  {}{}_{} = {},",
+                        self.type_name(name),
+                        what,
+                        pascal_case(&variant.name),
+                        i
+                    ));
+                }
+                out.push(format!("}} {};", enum_name));
+                out.push(String::new());
+            }
+        }
+        let mut forward = false;
+        for (name, def) in &defs {
+            if !matches!(def, IdlTypeDefTy::Type { .. }) && !is_unit_enum(def) {
+                let type_name = self.type_name(name);
+                out.push(format!("typedef struct {} {};", type_name, type_name));
+                forward = true;
+            }
+        }
+        if forward {
+            out.push(String::new());
+        }
+
+        let mut done = HashSet::new();
+        let mut visiting = HashSet::new();
+        for (name, _) in &defs {
+            self.define(name, &mut done, &mut visiting, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    fn define(
+        &self,
+        name: &str,
+        done: &mut HashSet<String>,
+        visiting: &mut HashSet<String>,
+        out: &mut Vec<String>,
+    ) -> Result<(), SolError> {
+        if done.contains(name) || !visiting.insert(name.to_string()) {
+            return Ok(());
+        }
+        let def = self.def(name)?;
+        let mut deps = Vec::new();
+        match def {
+            IdlTypeDefTy::Struct { fields } => {
+                for (_, ty) in field_list(fields) {
+                    self.dependencies(ty, true, &mut deps)?;
+                }
+            }
+            IdlTypeDefTy::Enum { variants } => {
+                for variant in variants {
+                    for (_, ty) in field_list(&variant.fields) {
+                        self.dependencies(ty, true, &mut deps)?;
+                    }
+                }
+            }
+            IdlTypeDefTy::Type { alias } => self.dependencies(alias, true, &mut deps)?,
+        }
+        for dep in deps {
+            self.define(&dep, done, visiting, out)?;
+        }
+
+        let type_name = self.type_name(name);
+        match def {
+            IdlTypeDefTy::Struct { fields } => {
+                out.push(format!("struct {} {{", type_name));
+                out.extend(indent(self.members(fields)?));
+                out.push("};".to_string());
+            }
+            IdlTypeDefTy::Enum { variants } if !is_unit_enum(def) => {
+                out.push(format!("struct {} {{", type_name));
+                out.push(format!("  {}Kind kind;", type_name));
+                out.push("  union {".to_string());
+                for variant in variants.iter().filter(|v| !v.fields.is_empty()) {
+                    out.push("    struct {".to_string());
+                    out.extend(indent(indent(indent(self.members(&variant.fields)?))));
+                    out.push(format!("    }} {};", ident(&variant.name)));
+                }
+                out.push("  } value;".to_string());
+                out.push("};".to_string());
+            }
+            IdlTypeDefTy::Enum { .. } => {}
+            IdlTypeDefTy::Type { alias } => {
+                if matches!(alias, IdlType::Option(_) | IdlType::Vec(_) | IdlType::Bytes) {
+                    return Err(unsupported(alias));
+                }
+                let declaration = self.declarations(alias, &type_name, false)?.remove(0);
+                out.push(format!("typedef {};", declaration));
+            }
+        }
+        if !is_unit_enum(def) {
+            out.push(String::new());
+        }
+        visiting.remove(name);
+        done.insert(name.to_string());
+        Ok(())
+    }
+
+    // Types that must be complete before a member of type `ty`: anything held
+    // by value, and aliases even behind a pointer
+    fn dependencies(
+        &self,
+        ty: &IdlType,
+        by_value: bool,
+        deps: &mut Vec<String>,
+    ) -> Result<(), SolError> {
+        match ty {
+            IdlType::Defined(name)
+                if by_value || matches!(self.def(name)?, IdlTypeDefTy::Type { .. }) =>
+            {
+                deps.push(name.clone())
+            }
+            IdlType::Option(inner) | IdlType::Array(inner, _) => {
+                self.dependencies(inner, by_value, deps)?
+            }
+            IdlType::Vec(inner) => self.dependencies(inner, false, deps)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn members(&self, fields: &IdlFields) -> Result<Vec<String>, SolError> {
+        let fields = field_list(fields);
+        if fields.is_empty() {
+            // Empty structs are not valid C
+            return Ok(vec!["uint8_t _unused;".to_string()]);
+        }
+        let mut out = Vec::new();
+        for (name, ty) in fields {
+            for declaration in self.declarations(ty, &name, false)? {
+                out.push(format!("{};", declaration));
+            }
+        }
+        Ok(out)
+    }
+
+    // ==================== Borsh Functions ==================== //
+
+    fn for_each(&self, count: &str, depth: usize, body: Vec<String>) -> Vec<String> {
+        let mut out = vec![format!(
+            "for (uintptr_t _i{d} = 0; _i{d} < {}; _i{d}++) {{",
+            count,
+            d = depth
+        )];
+        out.extend(indent(body));
+        out.push("}".to_string());
+        out
+    }
+
+    fn write_value(
+        &self,
+        ty: &IdlType,
+        value: &str,
+        depth: usize,
+    ) -> Result<Vec<String>, SolError> {
+        if let Some((_, size)) = scalar(ty) {
+            return Ok(vec![match ty {
+                IdlType::Bool => format!("sol_idl_write_uint(_w, {} ? 1 : 0, 1);", value),
+                IdlType::F32 => format!("sol_idl_write_f32(_w, {});", value),
+                IdlType::F64 => format!("sol_idl_write_f64(_w, {});", value),
+                _ => format!("sol_idl_write_uint(_w, (uint64_t){}, {});", value, size),
+            }]);
+        }
+        Ok(match ty {
+            IdlType::U128 | IdlType::I128 => vec![format!("sol_idl_write(_w, {}, 16);", value)],
+            IdlType::String => vec![format!("sol_idl_write_string(_w, {});", value)],
+            IdlType::Pubkey => vec![format!("sol_idl_write(_w, {}.data, 32);", value)],
+            IdlType::Defined(name) => match self.def(name)? {
+                IdlTypeDefTy::Type { alias } => self.write_value(alias, value, depth)?,
+                _ => vec![format!("{}(_w, &{});", self.function("write", name), value)],
+            },
+            IdlType::Array(inner, len) => {
+                let element = format!("{}[_i{}]", value, depth);
+                let body = self.write_value(inner, &element, depth + 1)?;
+                self.for_each(&len.to_string(), depth, body)
+            }
+            _ => return Err(unsupported(ty)),
+        })
+    }
+
+    fn write_field(&self, ty: &IdlType, place: &Place) -> Result<Vec<String>, SolError> {
+        Ok(match ty {
+            IdlType::Option(inner) => {
+                let mut out = vec![
+                    format!("sol_idl_write_uint(_w, {} ? 1 : 0, 1);", place.has),
+                    format!("if ({}) {{", place.has),
+                ];
+                out.extend(indent(self.write_value(inner, &place.value, 0)?));
+                out.push("}".to_string());
+                out
+            }
+            IdlType::Vec(inner) => {
+                let mut out = vec![format!(
+                    "sol_idl_write_uint(_w, (uint64_t){}, 4);",
+                    place.len
+                )];
+                let body = self.write_value(inner, &format!("{}[_i0]", place.value), 1)?;
+                out.extend(self.for_each(&place.len, 0, body));
+                out
+            }
+            IdlType::Bytes => vec![
+                format!("sol_idl_write_uint(_w, (uint64_t){}, 4);", place.len),
+                format!("sol_idl_write(_w, {}, {});", place.value, place.len),
+            ],
+            _ => self.write_value(ty, &place.value, 0)?,
+        })
+    }
+
+    fn read_value(&self, ty: &IdlType, value: &str, depth: usize) -> Result<Vec<String>, SolError> {
+        if let Some((c_type, size)) = scalar(ty) {
+            return Ok(vec![match ty {
+                IdlType::Bool => format!("{} = sol_idl_read_uint(_r, 1) != 0;", value),
+                IdlType::F32 => format!("{} = sol_idl_read_f32(_r);", value),
+                IdlType::F64 => format!("{} = sol_idl_read_f64(_r);", value),
+                _ => format!("{} = ({})sol_idl_read_uint(_r, {});", value, c_type, size),
+            }]);
+        }
+        Ok(match ty {
+            IdlType::U128 | IdlType::I128 => {
+                vec![format!("sol_idl_read_into(_r, {}, 16);", value)]
+            }
+            IdlType::String => vec![format!("{} = sol_idl_read_string(_r);", value)],
+            IdlType::Pubkey => vec![format!("sol_idl_read_into(_r, {}.data, 32);", value)],
+            IdlType::Defined(name) => match self.def(name)? {
+                IdlTypeDefTy::Type { alias } => self.read_value(alias, value, depth)?,
+                _ => vec![format!("{}(_r, &{});", self.function("read", name), value)],
+            },
+            IdlType::Array(inner, len) => {
+                let element = format!("{}[_i{}]", value, depth);
+                let body = self.read_value(inner, &element, depth + 1)?;
+                self.for_each(&len.to_string(), depth, body)
+            }
+            _ => return Err(unsupported(ty)),
+        })
+    }
+
+    fn read_field(&self, ty: &IdlType, place: &Place) -> Result<Vec<String>, SolError> {
+        Ok(match ty {
+            IdlType::Option(inner) => {
+                let mut out = vec![
+                    format!("{} = sol_idl_read_uint(_r, 1) != 0;", place.has),
+                    format!("if ({}) {{", place.has),
+                ];
+                out.extend(indent(self.read_value(inner, &place.value, 0)?));
+                out.push("}".to_string());
+                out
+            }
+            IdlType::Vec(inner) => {
+                let mut out = vec![format!(
+                    "{} = ({})sol_idl_read_vec(_r, sizeof(*{}), {}, &{});",
+                    place.value,
+                    declare(&self.element_type(inner)?, "*"),
+                    place.value,
+                    codec::zero_sized(self.idl, inner, &place.value, 0)?,
+                    place.len
+                )];
+                let body = self.read_value(inner, &format!("{}[_i0]", place.value), 1)?;
+                out.extend(self.for_each(&place.len, 0, body));
+                out
+            }
+            IdlType::Bytes => vec![format!(
+                "{} = sol_idl_read_bytes(_r, &{});",
+                place.value, place.len
+            )],
+            _ => self.read_value(ty, &place.value, 0)?,
+        })
+    }
+
+    // Whether values of `ty` own heap memory the free functions release
+    fn owns_memory(&self, ty: &IdlType, seen: &mut HashSet<String>) -> bool {
+        match ty {
+            IdlType::String | IdlType::Bytes | IdlType::Vec(_) => true,
+            IdlType::Option(inner) | IdlType::Array(inner, _) => self.owns_memory(inner, seen),
+            IdlType::Defined(name) => {
+                if !seen.insert(name.clone()) {
+                    return false;
+                }
+                match self.idl.type_def(name) {
+                    Some(IdlTypeDefTy::Struct { fields }) => field_list(fields)
+                        .into_iter()
+                        .any(|(_, ty)| self.owns_memory(ty, seen)),
+                    Some(IdlTypeDefTy::Enum { variants }) => variants.iter().any(|variant| {
+                        field_list(&variant.fields)
+                            .into_iter()
+                            .any(|(_, ty)| self.owns_memory(ty, seen))
+                    }),
+                    Some(IdlTypeDefTy::Type { alias }) => self.owns_memory(alias, seen),
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn free_value(&self, ty: &IdlType, value: &str, depth: usize) -> Result<Vec<String>, SolError> {
+        Ok(match ty {
+            IdlType::String => vec![format!("free({});", value)],
+            IdlType::Defined(name) => match self.def(name)? {
+                IdlTypeDefTy::Type { alias } => self.free_value(alias, value, depth)?,
+                _ if self.owns_memory(ty, &mut HashSet::new()) => {
+                    vec![format!("{}(&{});", self.function("free", name), value)]
+                }
+                _ => Vec::new(),
+            },
+            IdlType::Array(inner, len) => {
+                let element = format!("{}[_i{}]", value, depth);
+                let body = self.free_value(inner, &element, depth + 1)?;
+                if body.is_empty() {
+                    body
+                } else {
+                    self.for_each(&len.to_string(), depth, body)
+                }
+            }
+            _ => Vec::new(),
+        })
+    }
+
+    fn free_field(&self, ty: &IdlType, place: &Place) -> Result<Vec<String>, SolError> {
+        Ok(match ty {
+            IdlType::Option(inner) => {
+                let body = self.free_value(inner, &place.value, 0)?;
+                if body.is_empty() {
+                    body
+                } else {
+                    let mut out = vec![format!("if ({}) {{", place.has)];
+                    out.extend(indent(body));
+                    out.push("}".to_string());
+                    out
+                }
+            }
+            IdlType::Vec(inner) => {
+                let body = self.free_value(inner, &format!("{}[_i0]", place.value), 1)?;
+                let mut out = if body.is_empty() {
+                    body
+                } else {
+                    vec![format!("if ({}) {{", place.value)]
+                        .into_iter()
+                        .chain(indent(self.for_each(&place.len, 0, body)))
+                        .chain(["}".to_string()])
+                        .collect()
+                };
+                out.push(format!("free({});", place.value));
+                out
+            }
+            IdlType::Bytes => vec![format!("free({});", place.value)],
+            _ => self.free_value(ty, &place.value, 0)?,
+        })
+    }
+
+    fn fields_code(
+        &self,
+        fields: &IdlFields,
+        prefix: &str,
+        code: impl Fn(&IdlType, &Place) -> Result<Vec<String>, SolError>,
+    ) -> Result<Vec<String>, SolError> {
+        let mut out = Vec::new();
+        for (name, ty) in field_list(fields) {
+            out.extend(code(ty, &Place::new(prefix, &name))?);
+        }
+        Ok(out)
+    }
+
+    // `switch` over the variants of a data-carrying enum, skipping variants
+    // with nothing to do
+    fn switch(
+        &self,
+        name: &str,
+        variants: &[IdlEnumVariant],
+        code: impl Fn(&IdlFields, &str) -> Result<Vec<String>, SolError>,
+    ) -> Result<Vec<String>, SolError> {
+        let mut out = vec!["switch (_v->kind) {".to_string()];
+        for variant in variants {
+            let prefix = format!("_v->value.{}.", ident(&variant.name));
+            let body = code(&variant.fields, &prefix)?;
+            if body.is_empty() {
+                continue;
+            }
+            out.push(format!(
+                "  case {}Kind_{}:",
+                self.type_name(name),
+                pascal_case(&variant.name)
+            ));
+            out.extend(indent(indent(body)));
+            out.push("    break;".to_string());
+        }
+        out.push("  default:".to_string());
+        out.push("    break;".to_string());
+        out.push("}".to_string());
+        Ok(out)
+    }
+
+    // Enum tag, rejecting unknown variants
+    fn read_tag(&self, count: usize) -> Vec<String> {
+        vec![
+            "uint64_t _tag = sol_idl_read_uint(_r, 1);".to_string(),
+            format!("if (_tag >= {}) {{", count),
+            "  _r->failed = true;".to_string(),
+            "  return;".to_string(),
+            "}".to_string(),
+        ]
+    }
+
+    fn type_functions(&self) -> Result<Vec<String>, SolError> {
+        let defs: Vec<_> = self
+            .definitions()
+            .into_iter()
+            .filter(|(_, def)| !matches!(def, IdlTypeDefTy::Type { .. }))
+            .collect();
+        let prototypes = |name: &str| {
+            let type_name = self.type_name(name);
+            [
+                format!(
+                    "static inline void {}(SolIdlWriter *_w, const {} *_v)",
+                    self.function("write", name),
+                    type_name
+                ),
+                format!(
+                    "static inline void {}(SolIdlReader *_r, {} *_v)",
+                    self.function("read", name),
+                    type_name
+                ),
+                format!(
+                    "static inline void {}({} *_v)",
+                    self.function("free", name),
+                    type_name
+                ),
+            ]
+        };
+
+        let mut out = Vec::new();
+        for (name, _) in &defs {
+            out.extend(prototypes(name).map(|prototype| format!("{};", prototype)));
+        }
+        if !defs.is_empty() {
+            out.push(String::new());
+        }
+        for (name, def) in &defs {
+            let (write, read, free) = match def {
+                IdlTypeDefTy::Struct { fields } => (
+                    self.fields_code(fields, "_v->", |ty, place| self.write_field(ty, place))?,
+                    self.fields_code(fields, "_v->", |ty, place| self.read_field(ty, place))?,
+                    self.fields_code(fields, "_v->", |ty, place| self.free_field(ty, place))?,
+                ),
+                IdlTypeDefTy::Enum { variants } if is_unit_enum(def) => {
+                    let mut read = self.read_tag(variants.len());
+                    read.push(format!("*_v = ({})_tag;", self.type_name(name)));
+                    (
+                        vec!["sol_idl_write_uint(_w, (uint64_t)*_v, 1);".to_string()],
+                        read,
+                        Vec::new(),
+                    )
+                }
+                IdlTypeDefTy::Enum { variants } => {
+                    let mut write =
+                        vec!["sol_idl_write_uint(_w, (uint64_t)_v->kind, 1);".to_string()];
+                    write.extend(self.switch(name, variants, |fields, prefix| {
+                        self.fields_code(fields, prefix, |ty, place| self.write_field(ty, place))
+                    })?);
+                    let mut read = self.read_tag(variants.len());
+                    read.push(format!("_v->kind = ({}Kind)_tag;", self.type_name(name)));
+                    read.extend(self.switch(name, variants, |fields, prefix| {
+                        self.fields_code(fields, prefix, |ty, place| self.read_field(ty, place))
+                    })?);
+                    let free = if self
+                        .owns_memory(&IdlType::Defined(name.to_string()), &mut HashSet::new())
+                    {
+                        self.switch(name, variants, |fields, prefix| {
+                            self.fields_code(fields, prefix, |ty, place| self.free_field(ty, place))
+                        })?
+                    } else {
+                        Vec::new()
+                    };
+                    (write, read, free)
+                }
+                IdlTypeDefTy::Type { .. } => unreachable!(),
+            };
+            let [write_prototype, read_prototype, free_prototype] = prototypes(name);
+            for (prototype, body, params) in [
+                (write_prototype, write, &["_w", "_v"][..]),
+                (read_prototype, read, &["_r", "_v"][..]),
+                (free_prototype, free, &["_v"][..]),
+            ] {
+                out.push(format!("{} {{", prototype));
+                if body.is_empty() {
+                    out.extend(params.iter().map(|param| format!("  (void){};", param)));
+                } else {
+                    out.extend(indent(body));
+                }
+                out.push("}".to_string());
+                out.push(String::new());
+            }
+        }
+        Ok(out)
+    }
+
+    // ==================== Instructions and Accounts ==================== //
+
+    // `<prefix>_<instruction>`: named account parameters (accounts with a
+    // fixed address are filled in), typed arguments, then the signers
+    fn instruction(&self, instruction: &IdlInstruction) -> Result<Vec<String>, SolError> {
+        let function = format!("{}_{}", self.prefix, snake_case(&instruction.name));
+        let accounts = instruction.flat_accounts();
+        let mut account_names: Vec<String> = Vec::new();
+        for account in &accounts {
+            let mut name = ident(&account.name);
+            while RESERVED_PARAMS.contains(&name.as_str()) || account_names.contains(&name) {
+                name.push('_');
+            }
+            account_names.push(name);
+        }
+
+        let mut params = vec![
+            "SolClient *client".to_string(),
+            "const char *program_id".to_string(),
+        ];
+        for (account, name) in accounts.iter().zip(&account_names) {
+            if account.address.is_none() {
+                params.push(format!("const SolPublicKey *{}", name));
+            }
+        }
+        let mut body = vec![
+            format!(
+                "static const uint8_t _discriminator[] = {{{}}};",
+                byte_list(&instruction.discriminator())
+            ),
+            "SolIdlWriter _buffer = {NULL, 0, 0, false};".to_string(),
+            "SolIdlWriter *_w = &_buffer;".to_string(),
+            "sol_idl_write(_w, _discriminator, sizeof(_discriminator));".to_string(),
+        ];
+        for arg in &instruction.args {
+            let mut name = ident(&arg.name);
+            while RESERVED_PARAMS.contains(&name.as_str()) || account_names.contains(&name) {
+                name.push_str("_arg");
+            }
+            params.extend(self.declarations(&arg.ty, &name, true)?);
+            body.extend(self.write_field(&arg.ty, &Place::new("", &name))?);
+        }
+        params.push("SolKeyPair *const *signers".to_string());
+        params.push("uintptr_t signer_count".to_string());
+
+        let accounts_ptr = if accounts.is_empty() {
+            "NULL".to_string()
+        } else {
+            body.push(format!("SolAccountMeta _accounts[{}];", accounts.len()));
+            for (i, (account, name)) in accounts.iter().zip(&account_names).enumerate() {
+                let pubkey = match &account.address {
+                    Some(address) => {
+                        let pubkey = Pubkey::from_str(address).map_err(|err| {
+                            SolError::InvalidInput(format!(
+                                "invalid address '{}' for account '{}': {}",
+                                address, account.name, err
+                            ))
+                        })?;
+                        body.push(format!(
+                            "static const SolPublicKey _fixed_{} = {{{{{}}}}};",
+                            name,
+                            byte_list(&pubkey.to_bytes())
+                        ));
+                        format!("&_fixed_{}", name)
+                    }
+                    None => name.clone(),
+                };
+                body.push(format!(
+                    "_accounts[{}] = sol_idl_account_meta({}, {}, {});",
+                    i, pubkey, account.signer, account.writable
+                ));
+            }
+            "_accounts".to_string()
+        };
+        let program_id = if self.idl.program_id().is_ok() {
+            format!("program_id ? program_id : {}", self.program_id_macro())
+        } else {
+            "program_id".to_string()
+        };
+        body.extend([
+            "char *_signature = NULL;".to_string(),
+            "if (!_buffer.failed) {".to_string(),
+            format!(
+                "  _signature = send_raw_instruction_c(client, {}, {}, {}, signers, signer_count, _buffer.data, _buffer.len, NULL);",
+                program_id,
+                accounts_ptr,
+                accounts.len()
+            ),
+            "}".to_string(),
+            "free(_buffer.data);".to_string(),
+            "return _signature;".to_string(),
+        ]);
+
+        let flags = |account: &IdlInstructionAccount| match (account.signer, account.writable) {
+            (true, true) => " (signer, writable)",
+            (true, false) => " (signer)",
+            (false, true) => " (writable)",
+            (false, false) => "",
+        };
+        let account_list = accounts
+            .iter()
+            .filter(|account| account.address.is_none())
+            .map(|account| format!("{}{}", account.name, flags(account)))
+            .collect::<Vec<_>>();
+        let mut out = vec![format!(
+            "// Send `{}`. Accounts: {}.",
+            instruction.name,
+            if account_list.is_empty() {
+                "none".to_string()
+            } else {
+                account_list.join(", ")
+            }
+        )];
+        if self.idl.program_id().is_ok() {
+            out.push(format!(
+                "// `program_id` may be NULL to use {}.",
+                self.program_id_macro()
+            ));
+        }
+        out.push(
+            "// Returns the signature (free with free_transaction_signature) or NULL on error."
+                .to_string(),
+        );
+        out.push(signature(
+            &format!("static inline char *{}", function),
+            &params,
+            " {",
+        ));
+        out.extend(indent(body));
+        out.push("}".to_string());
+        out.push(String::new());
+        Ok(out)
+    }
+
+    // Largest Borsh encoding of `ty`, if bounded
+    fn max_size(&self, ty: &IdlType, seen: &mut HashSet<String>) -> Option<usize> {
+        if let Some((_, size)) = scalar(ty) {
+            return Some(size);
+        }
+        match ty {
+            IdlType::U128 | IdlType::I128 => Some(16),
+            IdlType::Pubkey => Some(32),
+            IdlType::Option(inner) => Some(1 + self.max_size(inner, seen)?),
+            IdlType::Array(inner, len) => self.max_size(inner, seen)?.checked_mul(*len),
+            IdlType::Defined(name) => {
+                if !seen.insert(name.clone()) {
+                    return None;
+                }
+                let mut fields_size = |fields: &IdlFields| {
+                    field_list(fields)
+                        .into_iter()
+                        .try_fold(0usize, |total, (_, ty)| {
+                            total.checked_add(self.max_size(ty, seen)?)
+                        })
+                };
+                let size = match self.idl.type_def(name)? {
+                    IdlTypeDefTy::Struct { fields } => fields_size(fields),
+                    IdlTypeDefTy::Enum { variants } => variants
+                        .iter()
+                        .map(|variant| fields_size(&variant.fields))
+                        .try_fold(0usize, |max, size| Some(max.max(size?)))
+                        .map(|max| max + 1),
+                    IdlTypeDefTy::Type { alias } => self.max_size(alias, seen),
+                };
+                seen.remove(name);
+                size
+            }
+            _ => None,
+        }
+    }
+
+    // `<prefix>_decode_<account>` over raw account data and
+    // `<prefix>_fetch_<account>` reading it from the cluster
+    fn account(&self, name: &str, discriminator: &[u8]) -> Result<Vec<String>, SolError> {
+        let Some(def) = self.idl.type_def(name) else {
+            return Ok(Vec::new());
+        };
+        if matches!(def, IdlTypeDefTy::Type { .. }) {
+            return Ok(Vec::new());
+        }
+        let type_name = self.type_name(name);
+        let decode = self.function("decode", name);
+        let free = self.function("free", name);
+        let fetch_size = self
+            .max_size(&IdlType::Defined(name.to_string()), &mut HashSet::new())
+            .map(|size| size + discriminator.len())
+            .unwrap_or(FETCH_BUFFER_SIZE);
+
+        let mut out = vec![
+            format!(
+                "// Decode a `{}` account, discriminator included. Release it with {}.",
+                name, free
+            ),
+            signature(
+                &format!("static inline bool {}", decode),
+                &[
+                    "const uint8_t *data".to_string(),
+                    "uintptr_t len".to_string(),
+                    format!("{} *out", type_name),
+                ],
+                " {",
+            ),
+        ];
+        out.extend(indent(vec![
+            format!(
+                "static const uint8_t _discriminator[] = {{{}}};",
+                byte_list(discriminator)
+            ),
+            "if (len < sizeof(_discriminator) ||".to_string(),
+            "    memcmp(data, _discriminator, sizeof(_discriminator)) != 0) {".to_string(),
+            "  return false;".to_string(),
+            "}".to_string(),
+            "SolIdlReader _reader = {data + sizeof(_discriminator), len - sizeof(_discriminator), false};".to_string(),
+            "memset(out, 0, sizeof(*out));".to_string(),
+            format!("{}(&_reader, out);", self.function("read", name)),
+            "if (_reader.failed) {".to_string(),
+            format!("  {}(out);", free),
+            "  return false;".to_string(),
+            "}".to_string(),
+            "return true;".to_string(),
+        ]));
+        out.push("}".to_string());
+        out.push(String::new());
+
+        out.push(format!(
+            "// Fetch `pubkey` and decode it as a `{}` account",
+            name
+        ));
+        out.push(signature(
+            &format!("static inline bool {}", self.function("fetch", name)),
+            &[
+                "SolClient *client".to_string(),
+                "const SolPublicKey *pubkey".to_string(),
+                format!("{} *out", type_name),
+            ],
+            " {",
+        ));
+        out.extend(indent(vec![
+            format!("uint8_t *_data = (uint8_t *)malloc({});", fetch_size),
+            "if (!_data) {".to_string(),
+            "  return false;".to_string(),
+            "}".to_string(),
+            format!(
+                "uintptr_t _len = get_account_data_c(client, (SolPublicKey *)pubkey, _data, {}, 0);",
+                fetch_size
+            ),
+            format!("bool _ok = _len > 0 && {}(_data, _len, out);", decode),
+            "free(_data);".to_string(),
+            "return _ok;".to_string(),
+        ]));
+        out.push("}".to_string());
+        out.push(String::new());
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::tests::{COUNTER_IDL, GAME_IDL};
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Write `header` and `source` to a fresh directory and compile them
+    // against the SDK header. Needs `cc` and `c++` on the PATH
+    fn compile(name: &str, header: &str, source: &str, compiler: &str, args: &[&str]) -> PathBuf {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "idl_codegen_{}_{}_{}",
+            name,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(format!("{}.h", name)), header).unwrap();
+        let source_path = dir.join(format!(
+            "main.{}",
+            if compiler == "c++" { "cpp" } else { "c" }
+        ));
+        std::fs::write(&source_path, source).unwrap();
+        let sdk_headers = Path::new(env!("CARGO_MANIFEST_DIR")).join("header");
+        let output = Command::new(compiler)
+            .args(["-Wall", "-Wextra", "-Werror", "-I"])
+            .arg(&sdk_headers)
+            .arg("-I")
+            .arg(&dir)
+            .args(args)
+            .arg(&source_path)
+            .current_dir(&dir)
+            .output()
+            .unwrap_or_else(|err| panic!("{} is required to run this test: {}", compiler, err));
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        dir
+    }
+
+    #[test]
+    fn names_follow_c_conventions() {
+        assert_eq!(snake_case("submitScore"), "submit_score");
+        assert_eq!(snake_case("anchor_counter"), "anchor_counter");
+        assert_eq!(pascal_case("anchor_counter"), "AnchorCounter");
+        assert_eq!(pascal_case("Player2Stats"), "Player2Stats");
+        assert_eq!(ident("default"), "default_");
+        assert_eq!(ident("2fa"), "field_2fa");
+    }
+
+    #[test]
+    fn generated_headers_compile_as_c_and_cpp() {
+        for (name, idl) in [("anchor_counter", COUNTER_IDL), ("game", GAME_IDL)] {
+            let header = generate_c_header(idl, None).unwrap();
+            let source = format!("#include \"{}.h\"\n", name);
+            for (compiler, args) in [
+                ("cc", &["-std=c99", "-pedantic", "-fsyntax-only"][..]),
+                ("c++", &["-fsyntax-only"][..]),
+            ] {
+                let dir = compile(name, &header, &source, compiler, args);
+                std::fs::remove_dir_all(dir).unwrap();
+            }
+        }
+
+        let header = generate_c_header(GAME_IDL, None).unwrap();
+        assert!(header
+            .contains("#define GAME_PROGRAM_ID \"DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy\""));
+        assert!(header.contains("typedef struct GameItem GameItem;"));
+        assert!(header.contains("GameModeKind_Ranked = 1,"));
+        assert!(header.contains("static inline char *game_submit_score(SolClient *client,"));
+        assert!(header.contains("const GameItem *items,"));
+        assert!(header.contains("static inline bool game_decode_player("));
+    }
+
+    #[test]
+    fn generated_code_round_trips_account_data() {
+        let idl = Idl::parse(GAME_IDL).unwrap();
+        let player = serde_json::json!({
+            "name": "ana",
+            "owner": Pubkey::new_unique().to_string(),
+            "best": 42,
+            "items": [{"id": 3, "rare": true}, {"id": 4, "rare": false}],
+            "modes": ["Casual", {"Ranked": {"tier": 2}}, {"Custom": ["x", 7]}],
+            "rating": "-1",
+            "ratio": 0.5,
+        });
+        let mut data = vec![9; 8];
+        codec::encode(
+            &idl,
+            &IdlType::Defined("Player".to_string()),
+            &player,
+            "",
+            &mut data,
+            0,
+        )
+        .unwrap();
+
+        // Decode with the generated reader, print a few fields, then encode the
+        // decoded value again with the generated writer
+        let source = format!(
+            r#"#include <stdio.h>
+#include "game.h"
+
+static const uint8_t DATA[] = {{{}}};
+
+int main(void) {{
+  GamePlayer player;
+  if (!game_decode_player(DATA, sizeof(DATA), &player)) {{
+    return 1;
+  }}
+  printf("%s %d %u %u %d %s\n", player.name, (int)player.best, (unsigned)player.items_len,
+         (unsigned)player.items[1].id, (int)player.modes[1].value.ranked.tier,
+         player.modes[2].value.custom._0);
+  SolIdlWriter writer = {{NULL, 0, 0, false}};
+  sol_idl_write(&writer, DATA, 8);
+  game_write_player(&writer, &player);
+  for (uintptr_t i = 0; i < writer.len; i++) {{
+    printf("%02x", writer.data[i]);
+  }}
+  free(writer.data);
+  game_free_player(&player);
+  return game_decode_player(DATA, 12, &player) ? 1 : 0;
+}}
+"#,
+            byte_list(&data)
+        );
+        let header = generate_c_header(GAME_IDL, None).unwrap();
+        let dir = compile("game", &header, &source, "cc", &["-std=c99", "-o", "main"]);
+        let output = Command::new(dir.join("main")).output().unwrap();
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let hex: String = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(stdout, format!("ana 42 2 4 2 x\n{}", hex));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
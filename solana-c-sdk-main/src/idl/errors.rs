@@ -0,0 +1,542 @@
+// Program error decoding: a custom program error code is given a name and a
+// message from the IDL `errors`, from the `AnchorError` line Anchor logs, or
+// from Anchor's own framework error codes.
+
+use std::ffi::{c_char, CString};
+
+use super::{Idl, SolIdl};
+use crate::error::{ffi_guard, last_error, SolError};
+
+// `anchor_lang::error::ErrorCode`: instruction (100), IDL (1000), event (1500),
+// constraint (2000), require (2500), account (3000) and misc (4100) errors
+const ANCHOR_ERRORS: &[(u32, &str, &str)] = &[
+    (
+        100,
+        "InstructionMissing",
+        "8 byte instruction identifier not provided",
+    ),
+    (
+        101,
+        "InstructionFallbackNotFound",
+        "Fallback functions are not supported",
+    ),
+    (
+        102,
+        "InstructionDidNotDeserialize",
+        "The program could not deserialize the given instruction",
+    ),
+    (
+        103,
+        "InstructionDidNotSerialize",
+        "The program could not serialize the given instruction",
+    ),
+    (
+        1000,
+        "IdlInstructionStub",
+        "The program was compiled without idl instructions",
+    ),
+    (
+        1001,
+        "IdlInstructionInvalidProgram",
+        "Invalid program given to the IDL instruction",
+    ),
+    (
+        1002,
+        "IdlAccountNotEmpty",
+        "IDL account must be empty in order to resize, try closing first",
+    ),
+    (
+        1500,
+        "EventInstructionStub",
+        "The program was compiled without `event-cpi` feature",
+    ),
+    (2000, "ConstraintMut", "A mut constraint was violated"),
+    (
+        2001,
+        "ConstraintHasOne",
+        "A has one constraint was violated",
+    ),
+    (2002, "ConstraintSigner", "A signer constraint was violated"),
+    (2003, "ConstraintRaw", "A raw constraint was violated"),
+    (2004, "ConstraintOwner", "An owner constraint was violated"),
+    (
+        2005,
+        "ConstraintRentExempt",
+        "A rent exemption constraint was violated",
+    ),
+    (2006, "ConstraintSeeds", "A seeds constraint was violated"),
+    (
+        2007,
+        "ConstraintExecutable",
+        "An executable constraint was violated",
+    ),
+    (
+        2008,
+        "ConstraintState",
+        "Deprecated Error, feel free to replace with something else",
+    ),
+    (
+        2009,
+        "ConstraintAssociated",
+        "An associated constraint was violated",
+    ),
+    (
+        2010,
+        "ConstraintAssociatedInit",
+        "An associated init constraint was violated",
+    ),
+    (2011, "ConstraintClose", "A close constraint was violated"),
+    (
+        2012,
+        "ConstraintAddress",
+        "An address constraint was violated",
+    ),
+    (2013, "ConstraintZero", "Expected zero account discriminant"),
+    (
+        2014,
+        "ConstraintTokenMint",
+        "A token mint constraint was violated",
+    ),
+    (
+        2015,
+        "ConstraintTokenOwner",
+        "A token owner constraint was violated",
+    ),
+    (
+        2016,
+        "ConstraintMintMintAuthority",
+        "A mint mint authority constraint was violated",
+    ),
+    (
+        2017,
+        "ConstraintMintFreezeAuthority",
+        "A mint freeze authority constraint was violated",
+    ),
+    (
+        2018,
+        "ConstraintMintDecimals",
+        "A mint decimals constraint was violated",
+    ),
+    (2019, "ConstraintSpace", "A space constraint was violated"),
+    (
+        2020,
+        "ConstraintAccountIsNone",
+        "A required account for the constraint is None",
+    ),
+    (
+        2021,
+        "ConstraintTokenTokenProgram",
+        "A token account token program constraint was violated",
+    ),
+    (
+        2022,
+        "ConstraintMintTokenProgram",
+        "A mint token program constraint was violated",
+    ),
+    (
+        2023,
+        "ConstraintAssociatedTokenTokenProgram",
+        "An associated token account token program constraint was violated",
+    ),
+    (2500, "RequireViolated", "A require expression was violated"),
+    (
+        2501,
+        "RequireEqViolated",
+        "A require_eq expression was violated",
+    ),
+    (
+        2502,
+        "RequireKeysEqViolated",
+        "A require_keys_eq expression was violated",
+    ),
+    (
+        2503,
+        "RequireNeqViolated",
+        "A require_neq expression was violated",
+    ),
+    (
+        2504,
+        "RequireKeysNeqViolated",
+        "A require_keys_neq expression was violated",
+    ),
+    (
+        2505,
+        "RequireGtViolated",
+        "A require_gt expression was violated",
+    ),
+    (
+        2506,
+        "RequireGteViolated",
+        "A require_gte expression was violated",
+    ),
+    (
+        3000,
+        "AccountDiscriminatorAlreadySet",
+        "The account discriminator was already set on this account",
+    ),
+    (
+        3001,
+        "AccountDiscriminatorNotFound",
+        "No 8 byte discriminator was found on the account",
+    ),
+    (
+        3002,
+        "AccountDiscriminatorMismatch",
+        "8 byte discriminator did not match what was expected",
+    ),
+    (
+        3003,
+        "AccountDidNotDeserialize",
+        "Failed to deserialize the account",
+    ),
+    (
+        3004,
+        "AccountDidNotSerialize",
+        "Failed to serialize the account",
+    ),
+    (
+        3005,
+        "AccountNotEnoughKeys",
+        "Not enough account keys given to the instruction",
+    ),
+    (
+        3006,
+        "AccountNotMutable",
+        "The given account is not mutable",
+    ),
+    (
+        3007,
+        "AccountOwnedByWrongProgram",
+        "The given account is owned by a different program than expected",
+    ),
+    (3008, "InvalidProgramId", "Program ID was not as expected"),
+    (
+        3009,
+        "InvalidProgramExecutable",
+        "Program account is not executable",
+    ),
+    (3010, "AccountNotSigner", "The given account did not sign"),
+    (
+        3011,
+        "AccountNotSystemOwned",
+        "The given account is not owned by the system program",
+    ),
+    (
+        3012,
+        "AccountNotInitialized",
+        "The program expected this account to be already initialized",
+    ),
+    (
+        3013,
+        "AccountNotProgramData",
+        "The given account is not a program data account",
+    ),
+    (
+        3014,
+        "AccountNotAssociatedTokenAccount",
+        "The given account is not the associated token account",
+    ),
+    (
+        3015,
+        "AccountSysvarMismatch",
+        "The given public key does not match the required sysvar",
+    ),
+    (
+        3016,
+        "AccountReallocExceedsLimit",
+        "The account reallocation exceeds the MAX_PERMITTED_DATA_INCREASE limit",
+    ),
+    (
+        3017,
+        "AccountDuplicateReallocs",
+        "The account was duplicated for more than one reallocation",
+    ),
+    (
+        4100,
+        "DeclaredProgramIdMismatch",
+        "The declared program id does not match the actual program id",
+    ),
+    (
+        5000,
+        "Deprecated",
+        "The API being used is deprecated and should no longer be used",
+    ),
+];
+
+fn anchor_error(code: u32) -> Option<(&'static str, &'static str)> {
+    ANCHOR_ERRORS
+        .iter()
+        .find(|(known, _, _)| *known == code)
+        .map(|(_, name, message)| (*name, *message))
+}
+
+// Name and message from an Anchor log line such as `Program log: AnchorError
+// occurred. Error Code: ConstraintMut. Error Number: 2000. Error Message: A mut
+// constraint was violated.`
+fn logged_error(logs: &[String], code: u32) -> Option<(String, String)> {
+    logs.iter().find_map(|log| {
+        let (_, rest) = log.split_once("AnchorError")?;
+        let (_, rest) = rest.split_once("Error Code: ")?;
+        let (name, rest) = rest.split_once(". Error Number: ")?;
+        let (number, message) = rest.split_once(". Error Message: ")?;
+        (number.parse::<u32>().ok()? == code).then(|| {
+            let message = message.strip_suffix('.').unwrap_or(message);
+            (name.to_string(), message.to_string())
+        })
+    })
+}
+
+// Name and message of custom error `code`. Anchor's framework codes are only
+// assumed for Anchor programs, i.e. when an IDL is given.
+pub(super) fn describe(
+    idl: Option<&Idl>,
+    code: u32,
+    logs: &[String],
+) -> Option<(String, Option<String>)> {
+    if let Some(error) = idl.and_then(|idl| idl.errors.iter().find(|error| error.code == code)) {
+        return Some((error.name.clone(), error.msg.clone()));
+    }
+    if let Some((name, message)) = logged_error(logs, code) {
+        return Some((name, Some(message)));
+    }
+    idl.and(anchor_error(code))
+        .map(|(name, message)| (name.to_string(), Some(message.to_string())))
+}
+
+// ==================== C API ==================== //
+
+// A custom program error, decoded. Filled by `sol_last_program_error`; release
+// its strings with `free_program_error`.
+#[repr(C)]
+pub struct SolProgramError {
+    pub instruction_index: u8, // Index of the failed instruction in the transaction
+    pub code: u32,             // Custom program error code
+    pub name: *mut c_char,     // e.g. "ConstraintMut", NULL if the code is unknown
+    pub message: *mut c_char,  // NULL if unknown
+    pub logs: *mut *mut c_char, // Program logs of the failed preflight simulation
+    pub log_count: usize,
+}
+
+fn c_string(text: &str) -> *mut c_char {
+    CString::new(text.replace('\0', " "))
+        .unwrap_or_default()
+        .into_raw()
+}
+
+// Decode the last error on this thread if it is `SolErrorCode_ProgramError`.
+// Names come from `idl` (may be NULL) `errors`, from the `AnchorError` program
+// log, then from Anchor's framework codes when an IDL is given. Returns false
+// and leaves `out` untouched for any other error. The last error is kept.
+#[no_mangle]
+pub extern "C" fn sol_last_program_error(idl: *const SolIdl, out: *mut SolProgramError) -> bool {
+    ffi_guard(false, || {
+        let Some(SolError::Program {
+            instruction_index,
+            code,
+            logs,
+        }) = last_error()
+        else {
+            return false;
+        };
+        let Some(out) = (unsafe { out.as_mut() }) else {
+            return false;
+        };
+        let idl = unsafe { idl.as_ref() }.map(|idl| &idl.idl);
+        let (name, message) = match describe(idl, code, &logs) {
+            Some((name, message)) => (
+                c_string(&name),
+                message.map_or(std::ptr::null_mut(), |message| c_string(&message)),
+            ),
+            None => (std::ptr::null_mut(), std::ptr::null_mut()),
+        };
+        let logs: Box<[*mut c_char]> = logs.iter().map(|log| c_string(log)).collect();
+        *out = SolProgramError {
+            instruction_index,
+            code,
+            name,
+            message,
+            log_count: logs.len(),
+            logs: Box::into_raw(logs).cast(),
+        };
+        true
+    })
+}
+
+// Release the strings of a `SolProgramError` filled by `sol_last_program_error`
+#[no_mangle]
+pub extern "C" fn free_program_error(error: *mut SolProgramError) {
+    ffi_guard((), || {
+        let Some(error) = (unsafe { error.as_mut() }) else {
+            return;
+        };
+        unsafe {
+            for text in [error.name, error.message] {
+                if !text.is_null() {
+                    drop(CString::from_raw(text));
+                }
+            }
+            if !error.logs.is_null() {
+                let logs = Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                    error.logs,
+                    error.log_count,
+                ));
+                for &log in logs.iter() {
+                    drop(CString::from_raw(log));
+                }
+            }
+        }
+        error.name = std::ptr::null_mut();
+        error.message = std::ptr::null_mut();
+        error.logs = std::ptr::null_mut();
+        error.log_count = 0;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::new_sol_client;
+    use crate::error::{set_last_error, sol_last_error_code, SolErrorCode};
+    use crate::idl::tests::COUNTER_IDL;
+    use crate::idl::{sol_idl_free, sol_idl_load, sol_idl_send_instruction};
+    use crate::mock_rpc::{rpc_error, transaction_handler, MockRpc};
+    use crate::smart_contract::free_client;
+    use crate::wallet::{new_keypair, SolPublicKey};
+    use solana_program::pubkey::Pubkey;
+    use std::ffi::CStr;
+
+    const ERRORS_IDL: &str = r#"{
+        "address": "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy",
+        "metadata": {"name": "game", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [],
+        "errors": [
+            {"code": 6000, "name": "NotOwner", "msg": "Only the owner can do this"},
+            {"code": 6001, "name": "Paused"}
+        ]
+    }"#;
+
+    fn text(ptr: *const c_char) -> Option<String> {
+        (!ptr.is_null()).then(|| unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string())
+    }
+
+    fn described(name: &str, message: Option<&str>) -> Option<(String, Option<String>)> {
+        Some((name.to_string(), message.map(str::to_string)))
+    }
+
+    #[test]
+    fn describes_idl_logged_and_framework_errors() {
+        let idl = Idl::parse(ERRORS_IDL).unwrap();
+        let logs = vec![
+            "Program log: AnchorError thrown in programs/game/src/lib.rs:12. Error Code: \
+             TooLate. Error Number: 6002. Error Message: The round is over."
+                .to_string(),
+        ];
+
+        assert_eq!(
+            describe(Some(&idl), 6000, &[]),
+            described("NotOwner", Some("Only the owner can do this"))
+        );
+        assert_eq!(describe(Some(&idl), 6001, &[]), described("Paused", None));
+        assert_eq!(
+            describe(None, 6002, &logs),
+            described("TooLate", Some("The round is over"))
+        );
+        assert_eq!(
+            describe(Some(&idl), 2000, &[]),
+            described("ConstraintMut", Some("A mut constraint was violated"))
+        );
+        assert_eq!(
+            describe(Some(&idl), 3012, &[]),
+            described(
+                "AccountNotInitialized",
+                Some("The program expected this account to be already initialized")
+            )
+        );
+        // Without an IDL the program may not be an Anchor program
+        assert_eq!(describe(None, 2000, &[]), None);
+        assert_eq!(describe(Some(&idl), 6003, &logs), None);
+    }
+
+    #[test]
+    fn last_program_error_has_the_simulation_logs() {
+        let logs = [
+            "Program DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy invoke [1]",
+            "Program log: Instruction: Increment",
+            "Program log: AnchorError caused by account: counter. Error Code: ConstraintMut. \
+             Error Number: 2000. Error Message: A mut constraint was violated.",
+            "Program DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy failed: custom program error: 0x7d0",
+        ];
+        let rpc = MockRpc::start(move |method, params| {
+            match method {
+            "sendTransaction" => Err(rpc_error(
+                -32002,
+                "Transaction simulation failed: Error processing Instruction 0: custom program error: 0x7d0",
+                serde_json::json!({
+                    "err": {"InstructionError": [0, {"Custom": 2000}]},
+                    "logs": logs,
+                    "accounts": null,
+                    "unitsConsumed": 1500,
+                    "returnData": null,
+                }),
+            )),
+            _ => transaction_handler(method, params),
+        }
+        });
+        let client = new_sol_client(rpc.url.as_ptr());
+        let idl_json = CString::new(COUNTER_IDL).unwrap();
+        let idl = sol_idl_load(idl_json.as_ptr());
+        let user = new_keypair();
+        let counter = SolPublicKey::new(Pubkey::new_unique());
+        let accounts = [
+            counter,
+            SolPublicKey::new(unsafe { &*user }.pubkey.to_pubkey()),
+        ];
+        let signers = [user];
+
+        let signature = sol_idl_send_instruction(
+            client,
+            idl,
+            c"increment".as_ptr(),
+            accounts.as_ptr(),
+            accounts.len(),
+            signers.as_ptr(),
+            signers.len(),
+            std::ptr::null(),
+            std::ptr::null(),
+        );
+        assert!(signature.is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::ProgramError);
+
+        let mut error = SolProgramError {
+            instruction_index: 9,
+            code: 0,
+            name: std::ptr::null_mut(),
+            message: std::ptr::null_mut(),
+            logs: std::ptr::null_mut(),
+            log_count: 0,
+        };
+        assert!(sol_last_program_error(idl, &mut error));
+        assert_eq!(error.instruction_index, 0);
+        assert_eq!(error.code, 2000);
+        assert_eq!(text(error.name).as_deref(), Some("ConstraintMut"));
+        assert_eq!(
+            text(error.message).as_deref(),
+            Some("A mut constraint was violated")
+        );
+        let returned: Vec<String> = (0..error.log_count)
+            .map(|i| text(unsafe { *error.logs.add(i) }).unwrap())
+            .collect();
+        assert_eq!(returned, logs);
+        // Reading it does not clear the last error
+        assert_eq!(sol_last_error_code(), SolErrorCode::ProgramError);
+        free_program_error(&mut error);
+        assert!(error.name.is_null() && error.logs.is_null());
+
+        // Other errors are not program errors
+        set_last_error(SolError::Internal("nope".to_string()));
+        assert!(!sol_last_program_error(idl, &mut error));
+
+        sol_idl_free(idl);
+        free_client(client);
+    }
+}
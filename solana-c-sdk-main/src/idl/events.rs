@@ -0,0 +1,294 @@
+// Anchor events: a program emits them as base64 `Program data:` log lines while
+// it is executing. They are extracted for one program from a transaction's logs
+// (or a raw log list) and decoded with the IDL `events` and `types`.
+
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use std::ffi::{c_char, CString};
+
+use super::{Idl, SolIdl};
+use crate::{
+    client::SolClient,
+    error::{ffi_call, ref_arg, slice_arg, str_arg, SolError},
+    token::fetch_transaction,
+    wallet::SolPublicKey,
+};
+
+// Events `program_id` emitted in `logs`, as `[{"name", "data"}]`. Data logged
+// by other programs (e.g. during a CPI) and data that is not an IDL event are
+// skipped.
+pub(super) fn parse_events<S: AsRef<str>>(
+    idl: &Idl,
+    program_id: &Pubkey,
+    logs: &[S],
+) -> Result<Value, SolError> {
+    let program_id = program_id.to_string();
+    // Programs currently executing, innermost last
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        let Some(log) = log.as_ref().strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = log.strip_prefix("data: ") {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            let Ok(data) = base64::engine::general_purpose::STANDARD.decode(data.trim()) else {
+                continue;
+            };
+            if let Some((name, data)) = idl.decode_event(&data)? {
+                events.push(json!({"name": name, "data": data}));
+            }
+            continue;
+        }
+        let mut words = log.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some(program), Some("invoke")) => stack.push(program),
+            (Some(_), Some("success" | "failed:")) => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    Ok(Value::Array(events))
+}
+
+// The program whose events are wanted; NULL means the IDL's program
+// Safety: see `ref_arg`.
+unsafe fn event_program(idl: &Idl, program_id: *const SolPublicKey) -> Result<Pubkey, SolError> {
+    if program_id.is_null() {
+        idl.program_id()
+    } else {
+        Ok(ref_arg(program_id, "program id")?.to_pubkey())
+    }
+}
+
+// ==================== C API ==================== //
+
+// Events emitted by `program_id` (NULL: the IDL's program) in the transaction
+// `signature`, as a JSON array of `{"name", "data"}` objects in emission order.
+// Free with `free_idl_json`.
+#[no_mangle]
+pub extern "C" fn sol_idl_parse_events(
+    client: *mut SolClient,
+    idl: *const SolIdl,
+    signature: *const c_char,
+    program_id: *const SolPublicKey,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let idl = &unsafe { ref_arg(idl, "idl")? }.idl;
+        let program_id = unsafe { event_program(idl, program_id)? };
+
+        let transaction = fetch_transaction(client, signature, client.transaction_commitment())?;
+        let logs: Vec<String> = transaction
+            .transaction
+            .meta
+            .and_then(|meta| meta.log_messages.into())
+            .unwrap_or_default();
+        let json = parse_events(idl, &program_id, &logs)?.to_string();
+        Ok(CString::new(json)?.into_raw())
+    })
+}
+
+// Same as `sol_idl_parse_events` for log lines the caller already has, e.g.
+// from `get_transaction_details_json` or a logs subscription
+#[no_mangle]
+pub extern "C" fn sol_idl_parse_event_logs(
+    idl: *const SolIdl,
+    logs: *const *const c_char,
+    log_count: usize,
+    program_id: *const SolPublicKey,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let idl = &unsafe { ref_arg(idl, "idl")? }.idl;
+        let program_id = unsafe { event_program(idl, program_id)? };
+        let logs = unsafe { slice_arg(logs, log_count, "logs")? }
+            .iter()
+            .map(|&log| unsafe { str_arg(log, "log") })
+            .collect::<Result<Vec<&str>, SolError>>()?;
+
+        let json = parse_events(idl, &program_id, &logs)?.to_string();
+        Ok(CString::new(json)?.into_raw())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::new_sol_client;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use crate::idl::{free_idl_json, sol_idl_free, sol_idl_load};
+    use crate::mock_rpc::MockRpc;
+    use crate::smart_contract::free_client;
+    use std::ffi::CStr;
+
+    const PROGRAM: &str = "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy";
+
+    const EVENTS_IDL: &str = r#"{
+        "address": "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy",
+        "metadata": {"name": "game", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [],
+        "events": [
+            {"name": "ScoreSubmitted", "discriminator": [1, 1, 1, 1, 1, 1, 1, 1]},
+            {"name": "GameOver", "discriminator": [2, 2, 2, 2, 2, 2, 2, 2]}
+        ],
+        "types": [
+            {"name": "ScoreSubmitted", "type": {"kind": "struct", "fields": [
+                {"name": "player", "type": "pubkey"},
+                {"name": "score", "type": "u64"}
+            ]}},
+            {"name": "GameOver", "type": {"kind": "struct", "fields": []}}
+        ]
+    }"#;
+
+    fn program_data(discriminator: u8, fields: &[u8]) -> String {
+        let mut data = vec![discriminator; 8];
+        data.extend(fields);
+        format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode(data)
+        )
+    }
+
+    fn sample_logs(player: &Pubkey) -> Vec<String> {
+        let other = Pubkey::new_unique();
+        let mut score = player.to_bytes().to_vec();
+        score.extend(7u64.to_le_bytes());
+        vec![
+            format!("Program {} invoke [1]", PROGRAM),
+            "Program log: Instruction: SubmitScore".to_string(),
+            program_data(1, &score),
+            // Data logged by a CPI callee belongs to that program
+            format!("Program {} invoke [2]", other),
+            program_data(2, &[]),
+            format!("Program {} consumed 100 of 200000 compute units", other),
+            format!("Program {} success", other),
+            // Back in the caller; not an IDL event
+            program_data(9, &[]),
+            program_data(2, &[]),
+            format!("Program {} success", PROGRAM),
+            program_data(2, &[]),
+        ]
+    }
+
+    fn take_json(json: *mut c_char) -> Option<Value> {
+        if json.is_null() {
+            return None;
+        }
+        let value = serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).ok();
+        free_idl_json(json);
+        value
+    }
+
+    #[test]
+    fn parses_events_of_the_program_from_logs() {
+        let idl = Idl::parse(EVENTS_IDL).unwrap();
+        let player = Pubkey::new_unique();
+        let events = parse_events(&idl, &idl.program_id().unwrap(), &sample_logs(&player)).unwrap();
+        assert_eq!(
+            events,
+            json!([
+                {"name": "ScoreSubmitted", "data": {"player": player.to_string(), "score": 7}},
+                {"name": "GameOver", "data": {}},
+            ])
+        );
+
+        // Another program id only sees its own data
+        let other = Pubkey::new_unique();
+        let logs = [
+            format!("Program {} invoke [1]", other),
+            program_data(2, &[]),
+            format!("Program {} failed: custom program error: 0x1", other),
+        ];
+        assert_eq!(
+            parse_events(&idl, &other, &logs).unwrap(),
+            json!([{"name": "GameOver", "data": {}}])
+        );
+    }
+
+    #[test]
+    fn legacy_events_use_inline_fields() {
+        let idl = Idl::parse(
+            r#"{
+                "name": "legacy",
+                "instructions": [],
+                "events": [{"name": "Staked", "fields": [
+                    {"name": "amount", "type": "u64", "index": false}
+                ]}]
+            }"#,
+        )
+        .unwrap();
+        let program = Pubkey::new_unique();
+        let mut data = idl.events[0].discriminator();
+        data.extend(5u64.to_le_bytes());
+        let logs = [
+            format!("Program {} invoke [1]", program),
+            format!(
+                "Program data: {}",
+                base64::engine::general_purpose::STANDARD.encode(data)
+            ),
+        ];
+        assert_eq!(
+            parse_events(&idl, &program, &logs).unwrap(),
+            json!([{"name": "Staked", "data": {"amount": 5}}])
+        );
+    }
+
+    #[test]
+    fn parses_events_from_log_lines_and_transactions() {
+        let player = Pubkey::new_unique();
+        let logs = sample_logs(&player);
+        let expected = json!([
+            {"name": "ScoreSubmitted", "data": {"player": player.to_string(), "score": 7}},
+            {"name": "GameOver", "data": {}},
+        ]);
+        let idl_json = CString::new(EVENTS_IDL).unwrap();
+        let idl = sol_idl_load(idl_json.as_ptr());
+
+        let lines: Vec<CString> = logs
+            .iter()
+            .map(|log| CString::new(log.as_str()).unwrap())
+            .collect();
+        let pointers: Vec<*const c_char> = lines.iter().map(|line| line.as_ptr()).collect();
+        let json =
+            sol_idl_parse_event_logs(idl, pointers.as_ptr(), pointers.len(), std::ptr::null());
+        assert_eq!(take_json(json), Some(expected.clone()));
+
+        let rpc = MockRpc::start(move |method, _params| match method {
+            "getTransaction" => Ok(json!({
+                "slot": 5,
+                "blockTime": null,
+                "transaction": ["", "base64"],
+                "meta": {
+                    "err": null,
+                    "status": {"Ok": null},
+                    "fee": 5000,
+                    "preBalances": [],
+                    "postBalances": [],
+                    "logMessages": logs,
+                },
+            })),
+            _ => Err(format!("mock does not implement {}", method)),
+        });
+        let client = new_sol_client(rpc.url.as_ptr());
+        let signature =
+            CString::new(solana_sdk::signature::Signature::default().to_string()).unwrap();
+        let json = sol_idl_parse_events(client, idl, signature.as_ptr(), std::ptr::null());
+        assert_eq!(take_json(json), Some(expected));
+
+        // Events of a program that emitted nothing
+        let other = SolPublicKey::new(Pubkey::new_unique());
+        let json = sol_idl_parse_events(client, idl, signature.as_ptr(), &other);
+        assert_eq!(take_json(json), Some(json!([])));
+
+        let json = sol_idl_parse_events(client, idl, c"not a signature".as_ptr(), std::ptr::null());
+        assert!(json.is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        free_client(client);
+        sol_idl_free(idl);
+    }
+}
@@ -0,0 +1,333 @@
+// Program-derived instruction accounts: Anchor IDLs describe the seeds of such
+// accounts (`pda`), made of constants, instruction arguments and other accounts
+// of the instruction, so the SDK can derive them instead of the caller.
+
+use serde_json::Value;
+use solana_program::pubkey::Pubkey;
+
+use super::{
+    codec, Idl, IdlFields, IdlInstruction, IdlInstructionAccount, IdlSeed, IdlType, IdlTypeDefTy,
+};
+use crate::{error::SolError, pda::find_program_address};
+
+// Fill in the missing (`None`) entries of `pubkeys`, the addresses of
+// `accounts` in order, from their seeds. Accounts may be derived from other
+// derived accounts, in any order.
+pub(super) fn derive_accounts(
+    idl: &Idl,
+    instruction: &IdlInstruction,
+    accounts: &[&IdlInstructionAccount],
+    pubkeys: &mut [Option<Pubkey>],
+    args: &Value,
+) -> Result<(), SolError> {
+    loop {
+        let mut waiting = None;
+        let mut progress = false;
+        for i in 0..accounts.len() {
+            if pubkeys[i].is_some() {
+                continue;
+            }
+            let seeds = Seeds {
+                idl,
+                instruction,
+                accounts,
+                pubkeys,
+                args,
+                account: &accounts[i].name,
+            };
+            match seeds.address(accounts[i])? {
+                Some(address) => {
+                    pubkeys[i] = Some(address);
+                    progress = true;
+                }
+                None => waiting = Some(i),
+            }
+        }
+        match waiting {
+            None => return Ok(()),
+            Some(i) if !progress => {
+                return Err(SolError::InvalidInput(format!(
+                    "cannot derive account '{}': its seeds depend on accounts that are not given",
+                    accounts[i].name
+                )))
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+// What the seeds of `account` can read
+struct Seeds<'a> {
+    idl: &'a Idl,
+    instruction: &'a IdlInstruction,
+    accounts: &'a [&'a IdlInstructionAccount],
+    pubkeys: &'a [Option<Pubkey>],
+    args: &'a Value,
+    account: &'a str,
+}
+
+impl Seeds<'_> {
+    // Address of a derived account, or None while an account it is derived
+    // from is still unknown
+    fn address(&self, account: &IdlInstructionAccount) -> Result<Option<Pubkey>, SolError> {
+        let pda = account.pda.as_ref().ok_or_else(|| {
+            SolError::InvalidInput(format!("account '{}' has no seeds", account.name))
+        })?;
+        let mut seeds = Vec::with_capacity(pda.seeds.len());
+        for seed in &pda.seeds {
+            match self.bytes(seed)? {
+                Some(bytes) => seeds.push(bytes),
+                None => return Ok(None),
+            }
+        }
+        let program_id = match &pda.program {
+            None => self.idl.program_id()?,
+            Some(seed) => match self.bytes(seed)? {
+                Some(bytes) => Pubkey::try_from(bytes.as_slice()).map_err(|_| {
+                    SolError::InvalidInput(format!(
+                        "program of account '{}' is not a pubkey",
+                        self.account
+                    ))
+                })?,
+                None => return Ok(None),
+            },
+        };
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        let (address, _bump) = find_program_address(&seeds, &program_id)?;
+        Ok(Some(address))
+    }
+
+    fn bytes(&self, seed: &IdlSeed) -> Result<Option<Vec<u8>>, SolError> {
+        match seed {
+            IdlSeed::Const { value, ty } => self.const_bytes(value, ty.as_ref()).map(Some),
+            IdlSeed::Arg { path } => self.arg_bytes(path).map(Some),
+            IdlSeed::Account { path } => self.account_bytes(path),
+        }
+    }
+
+    fn const_bytes(&self, value: &Value, ty: Option<&IdlType>) -> Result<Vec<u8>, SolError> {
+        let path = format!("constant seed of account '{}'", self.account);
+        match (value, ty) {
+            (Value::Array(_), _) => seed_bytes(self.idl, &IdlType::Bytes, value, &path),
+            (Value::String(string), None) => Ok(string.as_bytes().to_vec()),
+            (value, Some(ty)) => seed_bytes(self.idl, ty, value, &path),
+            (value, None) => Err(SolError::InvalidInput(format!(
+                "{} has no type: {}",
+                path, value
+            ))),
+        }
+    }
+
+    // An argument, or a field of a struct argument
+    fn arg_bytes(&self, path: &str) -> Result<Vec<u8>, SolError> {
+        let missing = || {
+            SolError::InvalidInput(format!(
+                "account '{}' is derived from argument '{}', which is missing",
+                self.account, path
+            ))
+        };
+        let mut names = path.split('.');
+        let name = names.next().unwrap_or_default();
+        let mut ty = self
+            .instruction
+            .args
+            .iter()
+            .find(|arg| arg.name == name)
+            .map(|arg| &arg.ty)
+            .ok_or_else(missing)?;
+        let mut value = self.args.get(name).ok_or_else(missing)?;
+        for name in names {
+            ty = self.field_type(ty, name).ok_or_else(missing)?;
+            value = value.get(name).ok_or_else(missing)?;
+        }
+        seed_bytes(self.idl, ty, value, path)
+    }
+
+    fn field_type(&self, ty: &IdlType, name: &str) -> Option<&IdlType> {
+        let IdlType::Defined(type_name) = ty else {
+            return None;
+        };
+        let Some(IdlTypeDefTy::Struct {
+            fields: IdlFields::Named(fields),
+        }) = self.idl.type_def(type_name)
+        else {
+            return None;
+        };
+        fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| &field.ty)
+    }
+
+    // The address of another account; None if it is not known yet
+    fn account_bytes(&self, path: &str) -> Result<Option<Vec<u8>>, SolError> {
+        if path.contains('.') {
+            return Err(SolError::InvalidInput(format!(
+                "account '{}' is derived from the data of '{}', pass it explicitly",
+                self.account, path
+            )));
+        }
+        let index = self
+            .accounts
+            .iter()
+            .position(|account| account.name == path)
+            .ok_or_else(|| {
+                SolError::InvalidInput(format!(
+                    "account '{}' is derived from unknown account '{}'",
+                    self.account, path
+                ))
+            })?;
+        Ok(self.pubkeys[index].map(|pubkey| pubkey.to_bytes().to_vec()))
+    }
+}
+
+// The seed bytes of a value, as Anchor programs write them: strings and byte
+// vectors without their length prefix, anything else in its Borsh encoding
+fn seed_bytes(idl: &Idl, ty: &IdlType, value: &Value, path: &str) -> Result<Vec<u8>, SolError> {
+    let mut out = Vec::new();
+    codec::encode(idl, ty, value, path, &mut out, 0)?;
+    match ty {
+        IdlType::String | IdlType::Bytes => Ok(out.split_off(4)),
+        IdlType::Vec(inner) if **inner == IdlType::U8 => Ok(out.split_off(4)),
+        _ => Ok(out),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    const VAULT_IDL: &str = r#"{
+        "address": "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy",
+        "metadata": {"name": "vault", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{
+            "name": "deposit",
+            "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+            "accounts": [
+                {"name": "owner", "writable": true, "signer": true},
+                {"name": "receipt", "writable": true, "pda": {"seeds": [
+                    {"kind": "const", "value": [114, 101, 99, 101, 105, 112, 116]},
+                    {"kind": "account", "path": "vault"},
+                    {"kind": "arg", "path": "params.id"}
+                ]}},
+                {"name": "vault", "writable": true, "pda": {"seeds": [
+                    {"kind": "const", "value": [118, 97, 117, 108, 116]},
+                    {"kind": "account", "path": "owner"},
+                    {"kind": "arg", "path": "name"}
+                ]}},
+                {"name": "metadata", "pda": {
+                    "seeds": [{"kind": "account", "path": "mint"}],
+                    "program": {"kind": "account", "path": "metadata_program"}
+                }},
+                {"name": "mint"},
+                {"name": "metadata_program", "address": "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"},
+                {"name": "system_program", "address": "11111111111111111111111111111111"}
+            ],
+            "args": [
+                {"name": "name", "type": "string"},
+                {"name": "params", "type": {"defined": {"name": "Params"}}}
+            ]
+        }],
+        "types": [{"name": "Params", "type": {"kind": "struct", "fields": [
+            {"name": "id", "type": "u64"}
+        ]}}]
+    }"#;
+
+    #[test]
+    fn derives_accounts_from_constants_args_and_accounts() {
+        let idl = Idl::parse(VAULT_IDL).unwrap();
+        let instruction = idl.instruction("deposit").unwrap();
+        let program = idl.program_id().unwrap();
+        let metadata_program: Pubkey = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+            .parse()
+            .unwrap();
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let args = serde_json::json!({"name": "main", "params": {"id": 7}});
+
+        let vault = Pubkey::find_program_address(&[b"vault", owner.as_ref(), b"main"], &program).0;
+        let receipt = Pubkey::find_program_address(
+            &[b"receipt", vault.as_ref(), &7u64.to_le_bytes()],
+            &program,
+        )
+        .0;
+        let metadata = Pubkey::find_program_address(&[mint.as_ref()], &metadata_program).0;
+
+        let metas = instruction
+            .account_metas(&idl, &[owner, mint], &args)
+            .unwrap();
+        assert_eq!(
+            metas,
+            [
+                AccountMeta::new(owner, true),
+                AccountMeta::new(receipt, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(metadata, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(metadata_program, false),
+                AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            ]
+        );
+
+        // Derived accounts can still be passed explicitly
+        let explicit = [owner, receipt, vault, metadata, mint];
+        assert_eq!(
+            instruction
+                .account_metas(&idl, &explicit, &Value::Null)
+                .unwrap(),
+            metas
+        );
+
+        let err = instruction
+            .account_metas(&idl, &[owner, mint], &serde_json::json!({"name": "main"}))
+            .unwrap_err();
+        assert!(err.to_string().contains("params.id"), "{}", err);
+    }
+
+    #[test]
+    fn seeds_use_the_argument_encoding() {
+        let idl = Idl::parse(VAULT_IDL).unwrap();
+        let key = Pubkey::new_unique();
+        let cases = [
+            (IdlType::String, serde_json::json!("abc"), b"abc".to_vec()),
+            (IdlType::Bytes, serde_json::json!([1, 2]), vec![1, 2]),
+            (IdlType::U16, serde_json::json!(258), vec![2, 1]),
+            (
+                IdlType::Pubkey,
+                serde_json::json!(key.to_string()),
+                key.to_bytes().to_vec(),
+            ),
+            (
+                IdlType::Array(Box::new(IdlType::U8), 2),
+                serde_json::json!([3, 4]),
+                vec![3, 4],
+            ),
+        ];
+        for (ty, value, bytes) in cases {
+            assert_eq!(seed_bytes(&idl, &ty, &value, "seed").unwrap(), bytes);
+        }
+
+        // Legacy constants carry their type
+        let legacy = Idl::parse(
+            r#"{"name": "legacy", "instructions": [{"name": "init", "accounts": [
+                {"name": "user", "isMut": true, "isSigner": true},
+                {"name": "state", "isMut": true, "isSigner": false, "pda": {"seeds": [
+                    {"kind": "const", "type": "string", "value": "state"},
+                    {"kind": "account", "type": "publicKey", "path": "user"}
+                ]}}
+            ]}], "metadata": {"address": "DsfPR2teuRS9ABmqGqq5NobD8Y9A9KvzMVNVzsjSP8Dy"}}"#,
+        )
+        .unwrap();
+        let user = Pubkey::new_unique();
+        let metas = legacy
+            .instruction("init")
+            .unwrap()
+            .account_metas(&legacy, &[user], &Value::Null)
+            .unwrap();
+        let state =
+            Pubkey::find_program_address(&[b"state", user.as_ref()], &legacy.program_id().unwrap())
+                .0;
+        assert_eq!(metas[1].pubkey, state);
+    }
+}
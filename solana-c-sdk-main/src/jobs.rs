@@ -0,0 +1,731 @@
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CString};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use solana_sdk::signature::Signature;
+use tokio::task::AbortHandle;
+
+use crate::client::SolClient;
+use crate::confirm::SolConfirmationStatus;
+use crate::error::{catch_panic, ffi_call, ffi_guard, SolError, SolErrorCode};
+use crate::token::{into_token_list, TokenEntry, TokenList};
+
+// Job ids start at 1; 0 is what `_async` functions return when they fail to start
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+static JOBS: LazyLock<Mutex<HashMap<u64, Job>>> = LazyLock::new(Default::default);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolJobStatus {
+    Pending = 0,
+    Succeeded = 1,
+    Failed = 2,
+    Cancelled = 3,
+    // No job with this id, or its result was already taken
+    Unknown = 4,
+}
+
+// Passed to a completion callback; only valid for the duration of the call
+#[repr(C)]
+pub struct SolCompletionStatus {
+    pub outcome: SolConfirmationStatus,
+    pub error_message: *const c_char, // NULL unless the outcome is Failed or TimedOut
+    pub error_code: SolErrorCode,     // `Ok` unless the outcome is Failed or TimedOut
+    pub program_error_code: i64,      // Custom program error code, or -1
+}
+
+// Called once, from an SDK worker thread (or from `sol_job_cancel`), when a job completes.
+// `signature` is NULL unless the transaction was confirmed.
+pub type SolCompletionCallback = Option<
+    extern "C" fn(
+        signature: *const c_char,
+        status: *const SolCompletionStatus,
+        user_data: *mut c_void,
+    ),
+>;
+
+// A registered callback and the host's context pointer
+pub struct Completion {
+    callback: extern "C" fn(*const c_char, *const SolCompletionStatus, *mut c_void),
+    user_data: *mut c_void,
+}
+
+// Safety: the host is told the callback runs on an SDK thread, so `user_data`
+// must be safe to use from there.
+unsafe impl Send for Completion {}
+
+impl Completion {
+    pub fn new(callback: SolCompletionCallback, user_data: *mut c_void) -> Result<Self, SolError> {
+        let callback = callback
+            .ok_or_else(|| SolError::InvalidInput("callback pointer is null".to_string()))?;
+        Ok(Completion {
+            callback,
+            user_data,
+        })
+    }
+
+    fn fire(self, result: Option<Result<JobOutput, SolError>>) {
+        let (outcome, signature, error) = match result {
+            Some(Ok(JobOutput::Signature(signature))) => {
+                (SolConfirmationStatus::Confirmed, Some(signature), None)
+            }
+            Some(Ok(_)) => (SolConfirmationStatus::Confirmed, None, None),
+            Some(Err(err @ SolError::Timeout(_))) => {
+                (SolConfirmationStatus::TimedOut, None, Some(err))
+            }
+            Some(Err(err)) => (SolConfirmationStatus::Failed, None, Some(err)),
+            None => (SolConfirmationStatus::Cancelled, None, None),
+        };
+        let signature = signature.and_then(|signature| CString::new(signature.to_string()).ok());
+        let message = error
+            .as_ref()
+            .and_then(|err| CString::new(err.to_string().replace('\0', " ")).ok());
+        let status = SolCompletionStatus {
+            outcome,
+            error_message: message.as_ref().map_or(std::ptr::null(), |m| m.as_ptr()),
+            error_code: error.as_ref().map_or(SolErrorCode::Ok, SolError::code),
+            program_error_code: error.as_ref().map_or(-1, SolError::program_code),
+        };
+        (self.callback)(
+            signature.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            &status,
+            self.user_data,
+        );
+    }
+}
+
+// What a finished job hands back to C
+pub enum JobOutput {
+    Signature(Signature),
+    Tokens(Vec<TokenEntry>),
+}
+
+enum JobState {
+    Pending(Option<AbortHandle>),
+    Succeeded(JobOutput),
+    Failed(SolError),
+    Cancelled,
+}
+
+struct Job {
+    state: JobState,
+    // Jobs with a callback hand their result to it and are then forgotten
+    completion: Option<Completion>,
+}
+
+fn with_jobs<T>(f: impl FnOnce(&mut HashMap<u64, Job>) -> T) -> T {
+    // A panic while holding the lock cannot leave the map half-updated
+    let mut jobs = JOBS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut jobs)
+}
+
+// Record the outcome of job `id`, unless it was cancelled in the meantime
+fn complete(id: u64, result: Result<JobOutput, SolError>) {
+    let completion = with_jobs(|jobs| match jobs.get_mut(&id) {
+        Some(job) if matches!(job.state, JobState::Pending(_)) => match job.completion.take() {
+            Some(completion) => {
+                jobs.remove(&id);
+                Some((completion, result))
+            }
+            None => {
+                job.state = match result {
+                    Ok(output) => JobState::Succeeded(output),
+                    Err(err) => JobState::Failed(err),
+                };
+                None
+            }
+        },
+        _ => None,
+    });
+    // Never call into the host while holding the lock
+    if let Some((completion, result)) = completion {
+        completion.fire(Some(result));
+    }
+}
+
+// Fails the job if its task is dropped without running (e.g. the client was freed first)
+struct CompletionGuard {
+    id: u64,
+    completed: bool,
+}
+
+impl CompletionGuard {
+    fn finish(mut self, result: Result<JobOutput, SolError>) {
+        self.completed = true;
+        complete(self.id, result);
+    }
+}
+
+impl Drop for CompletionGuard {
+    fn drop(&mut self) {
+        if !self.completed {
+            complete(
+                self.id,
+                Err(SolError::Internal(
+                    "job was dropped before it ran".to_string(),
+                )),
+            );
+        }
+    }
+}
+
+// Run `task` on the client's job runtime and return its job id. With a
+// `completion`, the result goes to the callback instead of being kept for polling.
+pub(crate) fn spawn_job(
+    client: &SolClient,
+    completion: Option<Completion>,
+    task: impl FnOnce(&SolClient) -> Result<JobOutput, SolError> + Send + 'static,
+) -> Result<u64, SolError> {
+    let runtime = client.runtime()?;
+    let id = NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed);
+    with_jobs(|jobs| {
+        jobs.insert(
+            id,
+            Job {
+                state: JobState::Pending(None),
+                completion,
+            },
+        )
+    });
+
+    let client = client.detached();
+    let guard = CompletionGuard {
+        id,
+        completed: false,
+    };
+    // The RPC calls are blocking, so jobs run on the runtime's blocking pool
+    let handle = runtime.spawn_blocking(move || {
+        guard.finish(catch_panic(|| task(&client)));
+    });
+
+    // Keep the abort handle so a job that has not started yet can be cancelled
+    with_jobs(|jobs| {
+        if let Some(Job {
+            state: JobState::Pending(abort),
+            ..
+        }) = jobs.get_mut(&id)
+        {
+            *abort = Some(handle.abort_handle());
+        }
+    });
+    Ok(id)
+}
+
+// Remove a finished job and return its output. Pending jobs, and jobs whose
+// output is not the kind `accepts` expects, are left in place.
+fn take_output(id: u64, accepts: impl FnOnce(&JobOutput) -> bool) -> Result<JobOutput, SolError> {
+    let job = with_jobs(|jobs| {
+        match jobs.get(&id).map(|job| &job.state) {
+            None => {
+                return Err(SolError::InvalidInput(format!("unknown job id {}", id)));
+            }
+            Some(JobState::Pending(_)) => {
+                return Err(SolError::InvalidInput(format!(
+                    "job {} is still pending",
+                    id
+                )));
+            }
+            Some(JobState::Succeeded(output)) if !accepts(output) => {
+                return Err(SolError::InvalidInput(format!(
+                    "job {} produced a different kind of result",
+                    id
+                )));
+            }
+            _ => {}
+        }
+        Ok(jobs.remove(&id))
+    })?;
+
+    match job.map(|job| job.state) {
+        Some(JobState::Succeeded(output)) => Ok(output),
+        Some(JobState::Failed(err)) => Err(err),
+        Some(JobState::Cancelled) => {
+            Err(SolError::InvalidInput(format!("job {} was cancelled", id)))
+        }
+        Some(JobState::Pending(_)) | None => unreachable!("checked above"),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn sol_job_poll(job_id: u64) -> SolJobStatus {
+    ffi_guard(SolJobStatus::Unknown, || {
+        with_jobs(|jobs| match jobs.get(&job_id).map(|job| &job.state) {
+            None => SolJobStatus::Unknown,
+            Some(JobState::Pending(_)) => SolJobStatus::Pending,
+            Some(JobState::Succeeded(_)) => SolJobStatus::Succeeded,
+            Some(JobState::Failed(_)) => SolJobStatus::Failed,
+            Some(JobState::Cancelled) => SolJobStatus::Cancelled,
+        })
+    })
+}
+
+// Take the signature produced by a finished transaction job (free it with
+// `free_transaction_signature`). Returns NULL and sets the last error if the
+// job failed, was cancelled or is still pending. Once a job has finished this
+// consumes it, whatever the outcome.
+#[no_mangle]
+pub extern "C" fn sol_job_result(job_id: u64) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        match take_output(job_id, |output| matches!(output, JobOutput::Signature(_)))? {
+            JobOutput::Signature(signature) => Ok(CString::new(signature.to_string())?.into_raw()),
+            _ => unreachable!("checked by take_output"),
+        }
+    })
+}
+
+// Same as `sol_job_result`, for `get_all_tokens_async` jobs (free with `free_token_list`)
+#[no_mangle]
+pub extern "C" fn sol_job_token_list(job_id: u64) -> *mut TokenList {
+    ffi_call(std::ptr::null_mut(), || {
+        match take_output(job_id, |output| matches!(output, JobOutput::Tokens(_)))? {
+            JobOutput::Tokens(entries) => Ok(into_token_list(entries)),
+            _ => unreachable!("checked by take_output"),
+        }
+    })
+}
+
+// Cancel a pending job; returns false if it already finished or does not exist.
+// A job that already started keeps running, but its result is discarded, so a
+// transaction it already sent may still land. A job's callback fires right
+// away, on this thread, with `SolConfirmationStatus_Cancelled`. A cancelled job
+// without a callback is kept, as `Cancelled`, until its result is taken or it
+// is forgotten with `sol_job_forget`.
+#[no_mangle]
+pub extern "C" fn sol_job_cancel(job_id: u64) -> bool {
+    ffi_guard(false, || {
+        let cancelled = with_jobs(|jobs| {
+            let job = jobs.get_mut(&job_id)?;
+            let abort = match std::mem::replace(&mut job.state, JobState::Cancelled) {
+                JobState::Pending(abort) => abort,
+                finished => {
+                    job.state = finished;
+                    return None;
+                }
+            };
+            let completion = job.completion.take();
+            if completion.is_some() {
+                jobs.remove(&job_id);
+            }
+            Some((abort, completion))
+        });
+        // Abort and call back outside the lock: dropping an unstarted task completes its guard
+        match cancelled {
+            Some((abort, completion)) => {
+                if let Some(abort) = abort {
+                    abort.abort();
+                }
+                if let Some(completion) = completion {
+                    completion.fire(None);
+                }
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+// Drop job `job_id` and its result without taking it. Finished jobs are kept
+// until their result is taken, so call this for jobs whose result is not
+// wanted. A pending job is cancelled first, without calling its callback.
+// Returns false if the job does not exist.
+#[no_mangle]
+pub extern "C" fn sol_job_forget(job_id: u64) -> bool {
+    ffi_guard(false, || {
+        let Some(job) = with_jobs(|jobs| jobs.remove(&job_id)) else {
+            return false;
+        };
+        // Outside the lock, as in `sol_job_cancel`
+        if let JobState::Pending(Some(abort)) = job.state {
+            abort.abort();
+        }
+        true
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::new_sol_client;
+    use crate::confirm::SolSendOptions;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use crate::fees::SolComputeBudget;
+    use crate::mock_rpc::{
+        recording_handler, transaction_handler, with_context, MockRpc, SentTransaction,
+    };
+    use crate::smart_contract::{
+        free_client, send_generic_transaction_async, send_generic_transaction_with_callback,
+    };
+    use crate::token::{
+        free_token_list, get_all_tokens_async, token_list_get_len, transfer_sol_async,
+    };
+    use crate::wallet::{SolKeyPair, SolPublicKey};
+    use serde_json::json;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::transaction::Transaction;
+    use std::ffi::CStr;
+    use std::str::FromStr;
+    use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+    use std::time::{Duration, Instant};
+
+    type Completed = (
+        Option<String>,
+        SolConfirmationStatus,
+        SolErrorCode,
+        Option<String>,
+    );
+
+    extern "C" fn record_completion(
+        signature: *const c_char,
+        status: *const SolCompletionStatus,
+        user_data: *mut c_void,
+    ) {
+        let to_string = |ptr: *const c_char| {
+            (!ptr.is_null()).then(|| {
+                unsafe { CStr::from_ptr(ptr) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        };
+        let status = unsafe { &*status };
+        let sender = unsafe { &*(user_data as *const SyncSender<Completed>) };
+        sender
+            .send((
+                to_string(signature),
+                status.outcome,
+                status.error_code,
+                to_string(status.error_message),
+            ))
+            .unwrap();
+    }
+
+    // Submit a system-program transaction with `record_completion` as its callback.
+    // The callback gets its own sender, leaked, as it may still be inside `send`
+    // after the test has received the value and returned.
+    fn submit_with_callback(client: *mut SolClient, sender: &SyncSender<Completed>) -> u64 {
+        let user_data = Box::into_raw(Box::new(sender.clone()));
+        let mut payer = SolKeyPair::new();
+        let signers = [&mut payer as *mut SolKeyPair];
+        send_generic_transaction_with_callback(
+            client,
+            c"11111111111111111111111111111111".as_ptr(),
+            c"initialize".as_ptr(),
+            std::ptr::null(),
+            0,
+            signers.as_ptr(),
+            signers.len(),
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+            Some(record_completion),
+            user_data as *mut c_void,
+        )
+    }
+
+    fn completed(receiver: &Receiver<Completed>) -> Completed {
+        receiver.recv_timeout(Duration::from_secs(10)).unwrap()
+    }
+
+    fn wait_for(job_id: u64) -> SolJobStatus {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let status = sol_job_poll(job_id);
+            if status != SolJobStatus::Pending || Instant::now() > deadline {
+                return status;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn token_list_job_returns_parsed_accounts() {
+        let owner = solana_sdk::pubkey::Pubkey::new_unique();
+        let mock = MockRpc::start(move |method, _| match method {
+            "getTokenAccountsByOwner" => Ok(with_context(json!([{
+                "pubkey": solana_sdk::pubkey::Pubkey::new_unique().to_string(),
+                "account": {
+                    "lamports": 2039280,
+                    "owner": spl_token::id().to_string(),
+                    "executable": false,
+                    "rentEpoch": 0,
+                    "space": 165,
+                    "data": {
+                        "program": "spl-token",
+                        "space": 165,
+                        "parsed": {
+                            "type": "account",
+                            "info": {
+                                "mint": solana_sdk::pubkey::Pubkey::new_unique().to_string(),
+                                "owner": owner.to_string(),
+                                "state": "initialized",
+                                "isNative": false,
+                                "tokenAmount": {
+                                    "amount": "150",
+                                    "decimals": 2,
+                                    "uiAmount": 1.5,
+                                    "uiAmountString": "1.5",
+                                },
+                            },
+                        },
+                    },
+                },
+            }]))),
+            _ => Err(format!("unexpected {}", method)),
+        });
+        let client = new_sol_client(mock.url.as_ptr());
+        let mut wallet = SolPublicKey::new(owner);
+
+        let job = get_all_tokens_async(client, &mut wallet);
+        assert_ne!(job, 0);
+        assert_eq!(wait_for(job), SolJobStatus::Succeeded);
+
+        // Asking for the wrong kind of result leaves the job in place
+        assert!(sol_job_result(job).is_null());
+        assert_eq!(sol_job_poll(job), SolJobStatus::Succeeded);
+
+        let list = sol_job_token_list(job);
+        assert!(!list.is_null());
+        assert_eq!(token_list_get_len(list), 1);
+        assert_eq!(sol_job_poll(job), SolJobStatus::Unknown);
+
+        free_token_list(list);
+        free_client(client);
+    }
+
+    #[test]
+    fn transaction_job_returns_signature() {
+        let mock = MockRpc::start(transaction_handler);
+        let client = new_sol_client(mock.url.as_ptr());
+        let mut payer = SolKeyPair::new();
+        let signers = [&mut payer as *mut SolKeyPair];
+
+        let job = send_generic_transaction_async(
+            client,
+            c"11111111111111111111111111111111".as_ptr(),
+            c"initialize".as_ptr(),
+            std::ptr::null(),
+            0,
+            signers.as_ptr(),
+            signers.len(),
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+        );
+        assert_ne!(job, 0);
+        assert_eq!(wait_for(job), SolJobStatus::Succeeded);
+
+        let signature = sol_job_result(job);
+        assert!(!signature.is_null());
+        let signature = unsafe { CString::from_raw(signature) };
+        assert!(Signature::from_str(signature.to_str().unwrap()).is_ok());
+
+        free_client(client);
+    }
+
+    #[test]
+    fn transaction_job_uses_its_send_options() {
+        let sent: SentTransaction<Transaction> = Default::default();
+        let mock = MockRpc::start(recording_handler(&sent));
+        let client = new_sol_client(mock.url.as_ptr());
+        let mut sender = SolKeyPair::new();
+        let mut recipient = SolPublicKey::new(Pubkey::new_unique());
+        let budget = SolComputeBudget {
+            unit_limit: 0,
+            unit_price: 7,
+            auto_unit_limit: false,
+        };
+        let options = SolSendOptions {
+            budget: &budget,
+            ..SolSendOptions::default()
+        };
+
+        let job = transfer_sol_async(client, &mut sender, &mut recipient, 10, &options);
+        assert_ne!(job, 0);
+        assert_eq!(wait_for(job), SolJobStatus::Succeeded);
+
+        let transaction = sent.lock().unwrap().take().unwrap();
+        let message = &transaction.message;
+        let instruction = &message.instructions[0];
+        assert_eq!(
+            *instruction.program_id(&message.account_keys),
+            solana_sdk::compute_budget::id()
+        );
+        // No unit limit in the budget, so the price comes first
+        assert_eq!(
+            instruction.data,
+            ComputeBudgetInstruction::set_compute_unit_price(7).data
+        );
+
+        sol_job_forget(job);
+        free_client(client);
+    }
+
+    #[test]
+    fn failed_job_reports_its_error_through_result() {
+        let mock = MockRpc::start(|_, _| Err("node is unhealthy".to_string()));
+        let client = new_sol_client(mock.url.as_ptr());
+        let mut wallet = SolPublicKey::new(solana_sdk::pubkey::Pubkey::new_unique());
+
+        let job = get_all_tokens_async(client, &mut wallet);
+        assert_eq!(wait_for(job), SolJobStatus::Failed);
+        assert!(sol_job_token_list(job).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::RpcServer);
+        assert_eq!(sol_job_poll(job), SolJobStatus::Unknown);
+
+        free_client(client);
+    }
+
+    #[test]
+    fn cancelled_job_discards_its_result() {
+        let mock = MockRpc::start(|_, _| {
+            std::thread::sleep(Duration::from_millis(200));
+            Ok(with_context(json!([])))
+        });
+        let client = new_sol_client(mock.url.as_ptr());
+        let mut wallet = SolPublicKey::new(solana_sdk::pubkey::Pubkey::new_unique());
+
+        let job = get_all_tokens_async(client, &mut wallet);
+        assert!(sol_job_cancel(job));
+        assert!(!sol_job_cancel(job));
+        assert_eq!(sol_job_poll(job), SolJobStatus::Cancelled);
+
+        // Still cancelled after the request would have completed
+        std::thread::sleep(Duration::from_millis(400));
+        assert_eq!(sol_job_poll(job), SolJobStatus::Cancelled);
+        assert!(sol_job_token_list(job).is_null());
+        let message = unsafe { CStr::from_ptr(crate::error::sol_last_error_message()) };
+        assert!(message.to_str().unwrap().contains("cancelled"));
+
+        free_client(client);
+    }
+
+    #[test]
+    fn forgotten_jobs_are_dropped() {
+        let mock = MockRpc::start(|_, _| {
+            std::thread::sleep(Duration::from_millis(200));
+            Ok(with_context(json!([])))
+        });
+        let client = new_sol_client(mock.url.as_ptr());
+        let mut wallet = SolPublicKey::new(solana_sdk::pubkey::Pubkey::new_unique());
+
+        let cancelled = get_all_tokens_async(client, &mut wallet);
+        assert!(sol_job_cancel(cancelled));
+        assert!(sol_job_forget(cancelled));
+        assert_eq!(sol_job_poll(cancelled), SolJobStatus::Unknown);
+
+        let finished = get_all_tokens_async(client, &mut wallet);
+        assert_eq!(wait_for(finished), SolJobStatus::Succeeded);
+        assert!(sol_job_forget(finished));
+        assert_eq!(sol_job_poll(finished), SolJobStatus::Unknown);
+
+        // A pending job is not recorded once it completes
+        let pending = get_all_tokens_async(client, &mut wallet);
+        assert!(sol_job_forget(pending));
+        std::thread::sleep(Duration::from_millis(400));
+        assert_eq!(sol_job_poll(pending), SolJobStatus::Unknown);
+        assert!(!sol_job_forget(pending));
+
+        free_client(client);
+    }
+
+    #[test]
+    fn unknown_job_ids() {
+        assert_eq!(sol_job_poll(0), SolJobStatus::Unknown);
+        assert!(!sol_job_cancel(u64::MAX));
+        assert!(!sol_job_forget(u64::MAX));
+        assert!(sol_job_result(u64::MAX).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn callback_receives_confirmed_signature() {
+        let mock = MockRpc::start(transaction_handler);
+        let client = new_sol_client(mock.url.as_ptr());
+        let (sender, receiver) = sync_channel(1);
+
+        let job = submit_with_callback(client, &sender);
+        assert_ne!(job, 0);
+        let (signature, outcome, code, message) = completed(&receiver);
+        assert_eq!(outcome, SolConfirmationStatus::Confirmed);
+        assert_eq!(code, SolErrorCode::Ok);
+        assert!(message.is_none());
+        assert!(Signature::from_str(&signature.unwrap()).is_ok());
+
+        // Callback jobs are not kept around for polling
+        assert_eq!(sol_job_poll(job), SolJobStatus::Unknown);
+        free_client(client);
+    }
+
+    #[test]
+    fn callback_reports_failures_and_timeouts() {
+        let mock = MockRpc::start(|_, _| Err("node is unhealthy".to_string()));
+        let client = new_sol_client(mock.url.as_ptr());
+        let (sender, receiver) = sync_channel(1);
+
+        submit_with_callback(client, &sender);
+        let (signature, outcome, code, message) = completed(&receiver);
+        assert!(signature.is_none());
+        assert_eq!(outcome, SolConfirmationStatus::Failed);
+        assert_eq!(code, SolErrorCode::RpcServer);
+        assert!(message.unwrap().contains("node is unhealthy"));
+        free_client(client);
+
+        // The transaction is sent but never shows up before its blockhash expires
+        let mock = MockRpc::start(|method, params| match method {
+            "getSignatureStatuses" => Ok(with_context(json!([null]))),
+            "isBlockhashValid" => Ok(with_context(json!(false))),
+            _ => transaction_handler(method, params),
+        });
+        let client = new_sol_client(mock.url.as_ptr());
+        submit_with_callback(client, &sender);
+        let (_, outcome, code, _) = completed(&receiver);
+        assert_eq!(outcome, SolConfirmationStatus::TimedOut);
+        assert_eq!(code, SolErrorCode::Timeout);
+        free_client(client);
+    }
+
+    #[test]
+    fn cancelling_fires_the_callback_once() {
+        let mock = MockRpc::start(|method, params| {
+            std::thread::sleep(Duration::from_millis(200));
+            transaction_handler(method, params)
+        });
+        let client = new_sol_client(mock.url.as_ptr());
+        let (sender, receiver) = sync_channel(2);
+
+        let job = submit_with_callback(client, &sender);
+        assert!(sol_job_cancel(job));
+        let (signature, outcome, _, _) = completed(&receiver);
+        assert!(signature.is_none());
+        assert_eq!(outcome, SolConfirmationStatus::Cancelled);
+
+        // The request still in flight must not trigger a second call
+        assert!(receiver.recv_timeout(Duration::from_millis(1500)).is_err());
+        free_client(client);
+    }
+
+    #[test]
+    fn null_callback_is_rejected() {
+        let mut payer = SolKeyPair::new();
+        let signers = [&mut payer as *mut SolKeyPair];
+        let client = new_sol_client(c"http://127.0.0.1:1".as_ptr());
+        let job = send_generic_transaction_with_callback(
+            client,
+            c"11111111111111111111111111111111".as_ptr(),
+            c"initialize".as_ptr(),
+            std::ptr::null(),
+            0,
+            signers.as_ptr(),
+            signers.len(),
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+            None,
+            std::ptr::null_mut(),
+        );
+        assert_eq!(job, 0);
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        free_client(client);
+    }
+}
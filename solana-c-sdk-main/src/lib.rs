@@ -1,4 +1,24 @@
+// `ClientError` from solana-client is large; it only flows through internal helpers
+#![allow(clippy::result_large_err)]
+
+mod accounts;
+mod builder;
 mod client;
+mod confirm;
+mod error;
+mod fees;
+mod idl;
+mod jobs;
+mod logging;
+mod lookup_table;
+#[cfg(test)]
+mod mock_rpc;
+mod nonce;
+mod pda;
+mod simulate;
 mod smart_contract;
 mod token;
 mod wallet;
+
+// Used by the `idl_codegen` binary
+pub use idl::codegen::generate_c_header;
@@ -0,0 +1,220 @@
+use std::ffi::{c_char, c_void, CString};
+use std::sync::{Once, RwLock};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::error::{ffi_call, SolError};
+
+// Log levels. `sol_set_log_callback` takes them as `uint32_t` and rejects
+// unknown values.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SolLogLevel {
+    Off = 0,
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl TryFrom<u32> for SolLogLevel {
+    type Error = SolError;
+
+    fn try_from(value: u32) -> Result<Self, SolError> {
+        match value {
+            0 => Ok(SolLogLevel::Off),
+            1 => Ok(SolLogLevel::Error),
+            2 => Ok(SolLogLevel::Warn),
+            3 => Ok(SolLogLevel::Info),
+            4 => Ok(SolLogLevel::Debug),
+            5 => Ok(SolLogLevel::Trace),
+            _ => Err(SolError::InvalidInput(format!(
+                "unknown log level {}",
+                value
+            ))),
+        }
+    }
+}
+
+impl SolLogLevel {
+    fn to_level_filter(self) -> LevelFilter {
+        match self {
+            SolLogLevel::Off => LevelFilter::Off,
+            SolLogLevel::Error => LevelFilter::Error,
+            SolLogLevel::Warn => LevelFilter::Warn,
+            SolLogLevel::Info => LevelFilter::Info,
+            SolLogLevel::Debug => LevelFilter::Debug,
+            SolLogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+
+    fn from_level(level: Level) -> Self {
+        match level {
+            Level::Error => SolLogLevel::Error,
+            Level::Warn => SolLogLevel::Warn,
+            Level::Info => SolLogLevel::Info,
+            Level::Debug => SolLogLevel::Debug,
+            Level::Trace => SolLogLevel::Trace,
+        }
+    }
+}
+
+// `target` is the Rust module that logged (SDK or dependency); both strings are
+// only valid for the duration of the call
+pub type SolLogCallback = Option<
+    extern "C" fn(
+        level: SolLogLevel,
+        target: *const c_char,
+        message: *const c_char,
+        user_data: *mut c_void,
+    ),
+>;
+
+#[derive(Clone, Copy)]
+struct LogSink {
+    callback: extern "C" fn(SolLogLevel, *const c_char, *const c_char, *mut c_void),
+    user_data: *mut c_void,
+}
+
+// Safety: the host is told the callback is called from whichever thread logs
+unsafe impl Send for LogSink {}
+unsafe impl Sync for LogSink {}
+
+static SINK: RwLock<Option<LogSink>> = RwLock::new(None);
+static INSTALL: Once = Once::new();
+
+// Forwards `log` records to the host's sink; installed as the global logger
+struct HostLogger;
+
+impl Log for HostLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // Copy the sink out so the host can change it from inside the callback
+        let sink = match *SINK.read().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+            Some(sink) => sink,
+            None => return,
+        };
+        let target = CString::new(record.target()).unwrap_or_default();
+        let message =
+            CString::new(record.args().to_string().replace('\0', " ")).unwrap_or_default();
+        (sink.callback)(
+            SolLogLevel::from_level(record.level()),
+            target.as_ptr(),
+            message.as_ptr(),
+            sink.user_data,
+        );
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: HostLogger = HostLogger;
+
+// Route SDK logs at `level` (a `SolLogLevel`) and more severe to `callback`.
+// Nothing is logged until this is called; `SolLogLevel_Off` or a NULL callback
+// silences the SDK again. Fails, changing nothing, on an unknown level.
+#[no_mangle]
+pub extern "C" fn sol_set_log_callback(
+    level: u32,
+    callback: SolLogCallback,
+    user_data: *mut c_void,
+) -> bool {
+    ffi_call(false, || {
+        let level = SolLogLevel::try_from(level)?;
+        INSTALL.call_once(|| {
+            // Fails only if something else in this library already installed a logger
+            let _ = log::set_logger(&LOGGER);
+        });
+        let sink = callback.map(|callback| LogSink {
+            callback,
+            user_data,
+        });
+        let level = if sink.is_some() {
+            level
+        } else {
+            SolLogLevel::Off
+        };
+        *SINK
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = sink;
+        log::set_max_level(level.to_level_filter());
+        Ok(true)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use std::sync::Mutex;
+
+    const TARGET: &str = "solana_c_sdk::logging::tests";
+    static RECEIVED: Mutex<Vec<(SolLogLevel, String)>> = Mutex::new(Vec::new());
+
+    extern "C" fn record(
+        level: SolLogLevel,
+        target: *const c_char,
+        message: *const c_char,
+        _user_data: *mut c_void,
+    ) {
+        // Other tests may log concurrently; keep only ours
+        if unsafe { CStr::from_ptr(target) }.to_str() == Ok(TARGET) {
+            let message = unsafe { CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned();
+            RECEIVED.lock().unwrap().push((level, message));
+        }
+    }
+
+    fn received() -> Vec<(SolLogLevel, String)> {
+        std::mem::take(&mut *RECEIVED.lock().unwrap())
+    }
+
+    #[test]
+    fn callback_receives_records_up_to_its_level() {
+        // Silent by default
+        log::error!("before any callback");
+        assert!(received().is_empty());
+
+        sol_set_log_callback(SolLogLevel::Info as u32, Some(record), std::ptr::null_mut());
+        log::info!("transfer {}", 1);
+        log::warn!("slow node");
+        log::debug!("too verbose");
+        assert_eq!(
+            received(),
+            [
+                (SolLogLevel::Info, "transfer 1".to_string()),
+                (SolLogLevel::Warn, "slow node".to_string()),
+            ]
+        );
+
+        sol_set_log_callback(
+            SolLogLevel::Trace as u32,
+            Some(record),
+            std::ptr::null_mut(),
+        );
+        log::trace!("everything");
+        assert_eq!(received(), [(SolLogLevel::Trace, "everything".to_string())]);
+
+        sol_set_log_callback(SolLogLevel::Off as u32, Some(record), std::ptr::null_mut());
+        log::error!("silenced");
+        sol_set_log_callback(SolLogLevel::Error as u32, None, std::ptr::null_mut());
+        log::error!("no callback");
+        assert!(received().is_empty());
+
+        assert!(!sol_set_log_callback(6, Some(record), std::ptr::null_mut()));
+        assert_eq!(
+            crate::error::sol_last_error_code(),
+            crate::error::SolErrorCode::InvalidInput
+        );
+        log::error!("still silenced");
+        assert!(received().is_empty());
+    }
+}
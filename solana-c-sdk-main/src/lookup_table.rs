@@ -0,0 +1,737 @@
+// Address lookup tables: on-chain lists of addresses that v0 transactions refer
+// to by a one-byte index, so one transaction can use many more accounts than
+// fit in a legacy message. Tables are created, extended, deactivated and
+// closed by their authority.
+
+use solana_program::address_lookup_table::{
+    self, instruction as table_instruction, state::AddressLookupTable, AddressLookupTableAccount,
+};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::VersionedTransaction,
+};
+use std::ffi::{c_char, CString};
+
+use crate::{
+    client::SolClient,
+    error::{ffi_call, ffi_guard, mut_arg, ref_arg, slice_arg, SolError},
+    wallet::{SolKeyPair, SolPublicKey},
+};
+
+impl SolClient {
+    // The account of the table at `address`
+    fn fetch_lookup_table_account(&self, address: &Pubkey) -> Result<Account, SolError> {
+        self.rpc(|rpc| rpc.get_multiple_accounts_with_commitment(&[*address], rpc.commitment()))?
+            .value
+            .pop()
+            .flatten()
+            .ok_or_else(|| SolError::AccountNotFound(format!("lookup table {}", address)))
+    }
+
+    // The address lists of the `addresses` tables, in order
+    pub(crate) fn fetch_lookup_tables(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<AddressLookupTableAccount>, SolError> {
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let accounts = self
+            .rpc(|rpc| rpc.get_multiple_accounts_with_commitment(addresses, rpc.commitment()))?
+            .value;
+        addresses
+            .iter()
+            .zip(accounts)
+            .map(|(key, account)| {
+                let account = account
+                    .ok_or_else(|| SolError::AccountNotFound(format!("lookup table {}", key)))?;
+                Ok(AddressLookupTableAccount {
+                    key: *key,
+                    addresses: parse_lookup_table(key, &account)?.addresses.to_vec(),
+                })
+            })
+            .collect()
+    }
+
+    // Every account `message` references, in index order: its static keys,
+    // then the writable and the readonly addresses it loads from tables
+    pub(crate) fn message_account_keys(
+        &self,
+        message: &VersionedMessage,
+    ) -> Result<Vec<Pubkey>, SolError> {
+        let mut keys = message.static_account_keys().to_vec();
+        let VersionedMessage::V0(message) = message else {
+            return Ok(keys);
+        };
+        let lookups = &message.address_table_lookups;
+        let addresses: Vec<Pubkey> = lookups.iter().map(|lookup| lookup.account_key).collect();
+        let tables = self.fetch_lookup_tables(&addresses)?;
+        let load = |table: &AddressLookupTableAccount, indexes: &[u8]| {
+            indexes
+                .iter()
+                .map(|&index| {
+                    table.addresses.get(index as usize).copied().ok_or_else(|| {
+                        SolError::InvalidInput(format!(
+                            "lookup table {} has no address {}",
+                            table.key, index
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<Pubkey>, SolError>>()
+        };
+        for (lookup, table) in lookups.iter().zip(&tables) {
+            keys.extend(load(table, &lookup.writable_indexes)?);
+        }
+        for (lookup, table) in lookups.iter().zip(&tables) {
+            keys.extend(load(table, &lookup.readonly_indexes)?);
+        }
+        Ok(keys)
+    }
+}
+
+// The table stored in `account`
+pub(crate) fn parse_lookup_table<'a>(
+    key: &Pubkey,
+    account: &'a Account,
+) -> Result<AddressLookupTable<'a>, SolError> {
+    let not_a_table =
+        || SolError::InvalidInput(format!("account {} is not an address lookup table", key));
+    if !address_lookup_table::program::check_id(&account.owner) {
+        return Err(not_a_table());
+    }
+    AddressLookupTable::deserialize(&account.data).map_err(|_| not_a_table())
+}
+
+// A legacy message without lookup tables, otherwise a v0 message reading every
+// account it can from them (signers and programs always stay in the message)
+pub(crate) fn compile_message(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedMessage, SolError> {
+    if tables.is_empty() {
+        let mut message = Message::new(instructions, Some(payer));
+        message.recent_blockhash = blockhash;
+        return Ok(VersionedMessage::Legacy(message));
+    }
+    let message = v0::Message::try_compile(payer, instructions, tables, blockhash)
+        .map_err(|err| SolError::InvalidInput(format!("cannot compile v0 message: {}", err)))?;
+    Ok(VersionedMessage::V0(message))
+}
+
+// Compile and sign; every signer of the message must be in `signers`, and only those
+pub(crate) fn sign_message(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &[&Keypair],
+    tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedTransaction, SolError> {
+    let message = compile_message(instructions, payer, tables, blockhash)?;
+    Ok(VersionedTransaction::try_new(message, signers)?)
+}
+
+// ==================== C API ==================== //
+
+// An address lookup table, from `sol_get_lookup_table`. Release it with
+// `free_lookup_table`.
+#[repr(C)]
+pub struct SolLookupTable {
+    pub authority: SolPublicKey, // Meaningless if `has_authority` is false
+    pub has_authority: bool,     // False once the table is frozen
+    pub deactivation_slot: u64,  // u64::MAX while the table is active
+    pub last_extended_slot: u64, // Addresses added in this slot are not usable until the next one
+    pub last_extended_slot_start_index: u8, // Number of addresses before that last extension
+    pub addresses: *mut SolPublicKey,
+    pub address_count: usize,
+}
+
+// Create a lookup table owned by `authority`, paid by `payer`. Its address is
+// derived from the authority and `recent_slot`, which must be a recent slot
+// (0 = the latest finalized slot) and is written to `table`. Returns the
+// signature (free with `free_transaction_signature`), or NULL on failure.
+#[no_mangle]
+pub extern "C" fn sol_create_lookup_table(
+    client: *mut SolClient,
+    payer: *mut SolKeyPair,
+    authority: *const SolPublicKey,
+    recent_slot: u64,
+    table: *mut SolPublicKey,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let payer = unsafe { ref_arg(payer, "payer")? }.to_keypair()?;
+        let authority = unsafe { ref_arg(authority, "authority")? }.to_pubkey();
+        let table = unsafe { mut_arg(table, "table")? };
+
+        let recent_slot = match recent_slot {
+            0 => client.rpc(|rpc| rpc.get_slot_with_commitment(CommitmentConfig::finalized()))?,
+            slot => slot,
+        };
+        let (instruction, address) =
+            table_instruction::create_lookup_table(authority, payer.pubkey(), recent_slot);
+        let signature = client.send_instructions(&[instruction], &payer, &payer)?;
+        log::info!("Created lookup table {} (signature {})", address, signature);
+        *table = SolPublicKey::new(address);
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
+
+// Append `count` addresses to `table`, signed by its `authority`. `payer` pays
+// the extra rent and may be the authority. A transaction holds about 30
+// addresses; extend large tables over several calls. New addresses can be
+// used from the next slot on.
+#[no_mangle]
+pub extern "C" fn sol_extend_lookup_table(
+    client: *mut SolClient,
+    payer: *mut SolKeyPair,
+    authority: *mut SolKeyPair,
+    table: *const SolPublicKey,
+    addresses: *const SolPublicKey,
+    count: usize,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let payer = unsafe { ref_arg(payer, "payer")? }.to_keypair()?;
+        let authority = unsafe { ref_arg(authority, "authority")? }.to_keypair()?;
+        let table = unsafe { ref_arg(table, "table")? }.to_pubkey();
+        let addresses: Vec<Pubkey> = unsafe { slice_arg(addresses, count, "addresses")? }
+            .iter()
+            .map(SolPublicKey::to_pubkey)
+            .collect();
+        if addresses.is_empty() {
+            return Err(SolError::InvalidInput("no addresses to add".to_string()));
+        }
+
+        let instruction = table_instruction::extend_lookup_table(
+            table,
+            authority.pubkey(),
+            Some(payer.pubkey()),
+            addresses,
+        );
+        let signature = client.send_instructions(&[instruction], &payer, &authority)?;
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
+
+// Deactivate `table`, signed and paid by its `authority`. It can no longer be
+// extended or used by new transactions, and can be closed once the
+// deactivation slot is no longer a recent slot (about 513 slots later).
+#[no_mangle]
+pub extern "C" fn sol_deactivate_lookup_table(
+    client: *mut SolClient,
+    authority: *mut SolKeyPair,
+    table: *const SolPublicKey,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let authority = unsafe { ref_arg(authority, "authority")? }.to_keypair()?;
+        let table = unsafe { ref_arg(table, "table")? }.to_pubkey();
+
+        let instruction = table_instruction::deactivate_lookup_table(table, authority.pubkey());
+        let signature = client.send_instructions(&[instruction], &authority, &authority)?;
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
+
+// Close a deactivated `table`, signed and paid by its `authority`, sending its
+// rent to `recipient`
+#[no_mangle]
+pub extern "C" fn sol_close_lookup_table(
+    client: *mut SolClient,
+    authority: *mut SolKeyPair,
+    table: *const SolPublicKey,
+    recipient: *const SolPublicKey,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let authority = unsafe { ref_arg(authority, "authority")? }.to_keypair()?;
+        let table = unsafe { ref_arg(table, "table")? }.to_pubkey();
+        let recipient = unsafe { ref_arg(recipient, "recipient")? }.to_pubkey();
+
+        let instruction =
+            table_instruction::close_lookup_table(table, authority.pubkey(), recipient);
+        let signature = client.send_instructions(&[instruction], &authority, &authority)?;
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
+
+// Fetch and parse `table` into `out`. Fails with `AccountNotFound` if it does
+// not exist, `InvalidInput` if the account is not a lookup table.
+#[no_mangle]
+pub extern "C" fn sol_get_lookup_table(
+    client: *mut SolClient,
+    table: *const SolPublicKey,
+    out: *mut SolLookupTable,
+) -> bool {
+    ffi_call(false, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let table = unsafe { ref_arg(table, "table")? }.to_pubkey();
+        let out = unsafe { mut_arg(out, "out")? };
+
+        let account = client.fetch_lookup_table_account(&table)?;
+        let parsed = parse_lookup_table(&table, &account)?;
+        let addresses: Vec<SolPublicKey> = parsed
+            .addresses
+            .iter()
+            .copied()
+            .map(SolPublicKey::new)
+            .collect();
+        let meta = parsed.meta;
+        *out = SolLookupTable {
+            authority: SolPublicKey::new(meta.authority.unwrap_or_default()),
+            has_authority: meta.authority.is_some(),
+            deactivation_slot: meta.deactivation_slot,
+            last_extended_slot: meta.last_extended_slot,
+            last_extended_slot_start_index: meta.last_extended_slot_start_index,
+            address_count: addresses.len(),
+            addresses: match addresses.is_empty() {
+                true => std::ptr::null_mut(),
+                false => Box::into_raw(addresses.into_boxed_slice()).cast(),
+            },
+        };
+        Ok(true)
+    })
+}
+
+// Release the addresses of a `SolLookupTable` filled by `sol_get_lookup_table`
+#[no_mangle]
+pub extern "C" fn free_lookup_table(table: *mut SolLookupTable) {
+    ffi_guard((), || {
+        let Some(table) = (unsafe { table.as_mut() }) else {
+            return;
+        };
+        if !table.addresses.is_null() {
+            unsafe {
+                drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                    table.addresses,
+                    table.address_count,
+                )));
+            }
+        }
+        table.addresses = std::ptr::null_mut();
+        table.address_count = 0;
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::builder::{
+        free_serialized_transaction, sol_tx_builder_add_lookup_table, sol_tx_builder_add_transfer,
+        sol_tx_builder_free, sol_tx_builder_new, sol_tx_builder_serialize, sol_tx_builder_sign,
+    };
+    use crate::client::new_sol_client;
+    use crate::confirm::SolSendOptions;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use crate::idl::{sol_idl_free, sol_idl_load, sol_idl_send_instruction};
+    use crate::mock_rpc::{recording_handler, with_context, MockRpc, SentTransaction};
+    use crate::smart_contract::{
+        free_client, send_anchor_transaction_c, send_raw_instruction_c, SolAccountMeta,
+    };
+    use crate::token::free_transaction_signature;
+    use crate::wallet::{SolKeyPair, SolPublicKey};
+    use serde_json::{json, Value};
+    use solana_program::address_lookup_table::{
+        instruction::ProgramInstruction, state::LookupTableMeta,
+    };
+    use std::borrow::Cow;
+    use std::ffi::{c_char, CStr, CString};
+
+    // A `getMultipleAccounts` entry holding a table of `addresses`
+    pub(crate) fn table_account(addresses: &[Pubkey]) -> Value {
+        table_account_with_meta(LookupTableMeta::new(Pubkey::new_unique()), addresses)
+    }
+
+    fn table_account_with_meta(meta: LookupTableMeta, addresses: &[Pubkey]) -> Value {
+        let data = AddressLookupTable {
+            meta,
+            addresses: Cow::Borrowed(addresses),
+        }
+        .serialize_for_tests()
+        .unwrap();
+        crate::accounts::tests::keyed_account(
+            &Pubkey::new_unique(),
+            &address_lookup_table::program::id(),
+            1_000_000,
+            &data,
+        )["account"]
+            .clone()
+    }
+
+    // Serves `tables` (None for a missing account) and records the sent transaction
+    fn table_rpc(tables: Vec<Option<Value>>) -> (MockRpc, SentTransaction<VersionedTransaction>) {
+        let sent = SentTransaction::default();
+        let record = recording_handler(&sent);
+        let rpc = MockRpc::start(move |method, params| match method {
+            "getMultipleAccounts" => {
+                let count = params[0].as_array().unwrap().len();
+                Ok(with_context(json!(tables[..count])))
+            }
+            _ => record(method, params),
+        });
+        (rpc, sent)
+    }
+
+    #[test]
+    fn builder_compiles_v0_transactions_with_lookup_tables() {
+        let recipients: Vec<Pubkey> = (0..40).map(|_| Pubkey::new_unique()).collect();
+        let table = Pubkey::new_unique();
+        let (rpc, _) = table_rpc(vec![Some(table_account(&recipients))]);
+        let client = new_sol_client(rpc.url.as_ptr());
+        let mut payer = SolKeyPair::new();
+        let builder = sol_tx_builder_new();
+        for recipient in &recipients {
+            assert!(sol_tx_builder_add_transfer(
+                builder,
+                &payer.pubkey,
+                &SolPublicKey::new(*recipient),
+                1
+            ));
+        }
+        assert!(sol_tx_builder_add_lookup_table(
+            builder,
+            &SolPublicKey::new(table)
+        ));
+        let signers = [&mut payer as *mut SolKeyPair];
+        assert!(sol_tx_builder_sign(builder, client, signers.as_ptr(), 1));
+
+        let mut len = 0;
+        let bytes = sol_tx_builder_serialize(builder, &mut len);
+        let transaction: VersionedTransaction =
+            bincode::deserialize(unsafe { std::slice::from_raw_parts(bytes, len) }).unwrap();
+        free_serialized_transaction(bytes, len);
+
+        assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+        let VersionedMessage::V0(message) = &transaction.message else {
+            panic!("expected a v0 message");
+        };
+        // Only the payer and the system program stay in the message
+        assert_eq!(message.account_keys.len(), 2);
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(message.address_table_lookups[0].account_key, table);
+        assert_eq!(message.address_table_lookups[0].writable_indexes.len(), 40);
+
+        sol_tx_builder_free(builder);
+        free_client(client);
+    }
+
+    // Every account of a sent transaction, lookups resolved, with its signer and writable flags
+    fn resolved_flags(
+        client: *mut SolClient,
+        transaction: &VersionedTransaction,
+    ) -> Vec<(Pubkey, bool, bool)> {
+        let message = &transaction.message;
+        let keys = unsafe { &*client }.message_account_keys(message).unwrap();
+        keys.into_iter()
+            .enumerate()
+            .map(|(i, key)| {
+                (
+                    key,
+                    message.is_signer(i),
+                    message.is_maybe_writable(i, None),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn program_calls_resolve_lookup_tables() {
+        let (vault, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut payer = SolKeyPair::new();
+        let mut authority = SolKeyPair::new();
+        let authority_key = authority.pubkey.to_pubkey();
+        let (table, missing) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (rpc, sent) = table_rpc(vec![
+            Some(table_account(&[vault, mint, authority_key])),
+            None,
+        ]);
+        let client = new_sol_client(rpc.url.as_ptr());
+        let program = Pubkey::new_unique();
+        let program_id = CString::new(program.to_string()).unwrap();
+        let meta = |pubkey, is_signer, is_writable| SolAccountMeta {
+            pubkey: SolPublicKey::new(pubkey),
+            is_signer,
+            is_writable,
+        };
+        let accounts = [
+            meta(authority_key, true, false),
+            meta(vault, false, true),
+            meta(mint, false, false),
+        ];
+        let signers = [&mut payer as *mut SolKeyPair, &mut authority];
+        let tables = [SolPublicKey::new(table)];
+        let options = SolSendOptions {
+            lookup_tables: tables.as_ptr(),
+            lookup_table_count: tables.len(),
+            ..SolSendOptions::default()
+        };
+        let idl = CString::new(format!(
+            r#"{{
+                "address": "{}",
+                "metadata": {{"name": "vault", "version": "0.1.0", "spec": "0.1.0"}},
+                "instructions": [{{
+                    "name": "settle",
+                    "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                    "accounts": [
+                        {{"name": "authority", "signer": true}},
+                        {{"name": "vault", "writable": true}},
+                        {{"name": "mint"}}
+                    ],
+                    "args": []
+                }}]
+            }}"#,
+            program
+        ))
+        .unwrap();
+        let idl = sol_idl_load(idl.as_ptr());
+        let idl_accounts = [
+            SolPublicKey::new(authority_key),
+            SolPublicKey::new(vault),
+            SolPublicKey::new(mint),
+        ];
+
+        let raw = |options: &SolSendOptions| {
+            send_raw_instruction_c(
+                client,
+                program_id.as_ptr(),
+                accounts.as_ptr(),
+                accounts.len(),
+                signers.as_ptr(),
+                signers.len(),
+                std::ptr::null(),
+                0,
+                options,
+            )
+        };
+        let anchor = |options: &SolSendOptions| {
+            send_anchor_transaction_c(
+                client,
+                program_id.as_ptr(),
+                c"settle".as_ptr(),
+                accounts.as_ptr(),
+                accounts.len(),
+                signers.as_ptr(),
+                signers.len(),
+                std::ptr::null(),
+                0,
+                options,
+            )
+        };
+        let from_idl = |options: &SolSendOptions| {
+            sol_idl_send_instruction(
+                client,
+                idl,
+                c"settle".as_ptr(),
+                idl_accounts.as_ptr(),
+                idl_accounts.len(),
+                signers.as_ptr(),
+                signers.len(),
+                std::ptr::null(),
+                options,
+            )
+        };
+        let sends: [&dyn Fn(&SolSendOptions) -> *mut c_char; 3] = [&raw, &anchor, &from_idl];
+        for send in sends {
+            let signature = send(&options);
+            assert!(!signature.is_null());
+            free_transaction_signature(signature);
+            let transaction = sent.lock().unwrap().take().unwrap();
+            let VersionedMessage::V0(message) = &transaction.message else {
+                panic!("expected a v0 message");
+            };
+            // The signer stays in the message; the others are read through the table
+            assert_eq!(message.address_table_lookups[0].writable_indexes, [0]);
+            assert_eq!(message.address_table_lookups[0].readonly_indexes, [1]);
+            let flags = resolved_flags(client, &transaction);
+            for expected in [
+                (payer.pubkey.to_pubkey(), true, true),
+                (authority_key, true, false),
+                (vault, false, true),
+                (mint, false, false),
+                (program, false, false),
+            ] {
+                assert!(
+                    flags.contains(&expected),
+                    "{:?} not in {:?}",
+                    expected,
+                    flags
+                );
+            }
+        }
+
+        let tables = [SolPublicKey::new(table), SolPublicKey::new(missing)];
+        let options = SolSendOptions {
+            lookup_tables: tables.as_ptr(),
+            lookup_table_count: tables.len(),
+            ..SolSendOptions::default()
+        };
+        assert!(raw(&options).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::AccountNotFound);
+
+        sol_idl_free(idl);
+        free_client(client);
+    }
+
+    #[test]
+    fn rejects_accounts_that_are_not_tables() {
+        let key = Pubkey::new_unique();
+        let account = Account {
+            owner: solana_program::system_program::id(),
+            ..Account::default()
+        };
+        let err = parse_lookup_table(&key, &account).unwrap_err();
+        assert_eq!(err.code(), SolErrorCode::InvalidInput);
+    }
+
+    // The signer count and lookup table instruction of a sent transaction
+    fn table_instruction(transaction: &VersionedTransaction) -> (usize, ProgramInstruction) {
+        let message = &transaction.message;
+        let instruction = message
+            .instructions()
+            .iter()
+            .find(|ix| {
+                address_lookup_table::program::check_id(
+                    ix.program_id(message.static_account_keys()),
+                )
+            })
+            .unwrap();
+        (
+            transaction.signatures.len(),
+            bincode::deserialize(&instruction.data).unwrap(),
+        )
+    }
+
+    #[test]
+    fn manages_lookup_tables() {
+        let (rpc, sent) = table_rpc(vec![]);
+        let client = new_sol_client(rpc.url.as_ptr());
+        let mut authority = SolKeyPair::new();
+        let mut payer = SolKeyPair::new();
+        let mut table = SolPublicKey::new(Pubkey::default());
+        let last_sent = |signature: *mut c_char| {
+            assert!(!signature.is_null());
+            free_transaction_signature(signature);
+            table_instruction(&sent.lock().unwrap().take().unwrap())
+        };
+
+        let signature =
+            sol_create_lookup_table(client, &mut payer, &authority.pubkey, 42, &mut table);
+        let (address, bump) =
+            table_instruction::derive_lookup_table_address(&authority.get_pubkey(), 42);
+        assert_eq!(table.to_pubkey(), address);
+        assert_eq!(
+            last_sent(signature),
+            (
+                1,
+                ProgramInstruction::CreateLookupTable {
+                    recent_slot: 42,
+                    bump_seed: bump,
+                }
+            )
+        );
+
+        let addresses = [
+            SolPublicKey::new(Pubkey::new_unique()),
+            SolPublicKey::new(Pubkey::new_unique()),
+        ];
+        let signature = sol_extend_lookup_table(
+            client,
+            &mut payer,
+            &mut authority,
+            &table,
+            addresses.as_ptr(),
+            2,
+        );
+        let new_addresses = addresses.iter().map(SolPublicKey::to_pubkey).collect();
+        assert_eq!(
+            last_sent(signature),
+            (2, ProgramInstruction::ExtendLookupTable { new_addresses })
+        );
+        // The authority may pay for itself
+        let authority_ptr: *mut SolKeyPair = &mut authority;
+        let signature = sol_extend_lookup_table(
+            client,
+            authority_ptr,
+            authority_ptr,
+            &table,
+            addresses.as_ptr(),
+            1,
+        );
+        assert_eq!(last_sent(signature).0, 1);
+        assert!(sol_extend_lookup_table(
+            client,
+            &mut payer,
+            &mut authority,
+            &table,
+            std::ptr::null(),
+            0
+        )
+        .is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        let signature = sol_deactivate_lookup_table(client, &mut authority, &table);
+        assert_eq!(
+            last_sent(signature),
+            (1, ProgramInstruction::DeactivateLookupTable)
+        );
+        let signature = sol_close_lookup_table(client, &mut authority, &table, &payer.pubkey);
+        assert_eq!(
+            last_sent(signature),
+            (1, ProgramInstruction::CloseLookupTable)
+        );
+
+        free_client(client);
+    }
+
+    #[test]
+    fn parses_lookup_tables() {
+        let authority = Pubkey::new_unique();
+        let addresses = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let meta = LookupTableMeta {
+            deactivation_slot: 900,
+            last_extended_slot: 800,
+            last_extended_slot_start_index: 1,
+            ..LookupTableMeta::new(authority)
+        };
+        let (rpc, _) = table_rpc(vec![Some(table_account_with_meta(meta, &addresses))]);
+        let client = new_sol_client(rpc.url.as_ptr());
+        let table = SolPublicKey::new(Pubkey::new_unique());
+
+        let mut out = unsafe { std::mem::zeroed::<SolLookupTable>() };
+        assert!(sol_get_lookup_table(client, &table, &mut out));
+        assert!(out.has_authority);
+        assert_eq!(out.authority.to_pubkey(), authority);
+        assert_eq!((out.deactivation_slot, out.last_extended_slot), (900, 800));
+        assert_eq!(out.last_extended_slot_start_index, 1);
+        let parsed: Vec<Pubkey> = (0..out.address_count)
+            .map(|i| unsafe { &*out.addresses.add(i) }.to_pubkey())
+            .collect();
+        assert_eq!(parsed, addresses);
+        free_lookup_table(&mut out);
+        assert!(out.addresses.is_null() && out.address_count == 0);
+        free_client(client);
+
+        let (rpc, _) = table_rpc(vec![None]);
+        let client = new_sol_client(rpc.url.as_ptr());
+        assert!(!sol_get_lookup_table(client, &table, &mut out));
+        assert_eq!(sol_last_error_code(), SolErrorCode::AccountNotFound);
+        let message = unsafe { CStr::from_ptr(crate::error::sol_last_error_message()) };
+        assert!(message
+            .to_str()
+            .unwrap()
+            .contains(&table.to_pubkey().to_string()));
+        free_client(client);
+    }
+}
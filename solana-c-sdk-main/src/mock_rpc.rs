@@ -0,0 +1,166 @@
+// Minimal JSON-RPC server for tests. Each request is answered by a handler
+// that maps (method, params) to the `result` value, or to an error message.
+
+use std::ffi::CString;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use solana_sdk::{hash::Hash, signature::Signature};
+
+type Handler = dyn Fn(&str, &Value) -> Result<Value, String> + Send + Sync;
+
+pub struct MockRpc {
+    pub url: CString,
+}
+
+impl MockRpc {
+    pub fn start(handler: impl Fn(&str, &Value) -> Result<Value, String> + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handler: Arc<Handler> = Arc::new(handler);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = Arc::clone(&handler);
+                std::thread::spawn(move || serve(stream, &*handler));
+            }
+        });
+        MockRpc {
+            url: CString::new(url).unwrap(),
+        }
+    }
+}
+
+fn serve(stream: TcpStream, handler: &Handler) {
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+    // Keep-alive: answer requests until the client hangs up
+    loop {
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let request: Value = serde_json::from_slice(&body).unwrap();
+
+        let method = request["method"].as_str().unwrap_or_default();
+        let response = match handler(method, &request["params"]) {
+            Ok(result) => json!({"jsonrpc": "2.0", "result": result, "id": request["id"]}),
+            // A full error object, see `rpc_error`
+            Err(message) if message.starts_with('{') => json!({
+                "jsonrpc": "2.0",
+                "error": serde_json::from_str::<Value>(&message).unwrap(),
+                "id": request["id"],
+            }),
+            Err(message) => json!({
+                "jsonrpc": "2.0",
+                "error": {"code": -32000, "message": message},
+                "id": request["id"],
+            }),
+        };
+        let response = response.to_string();
+        let written = write!(
+            writer,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            response.len(),
+            response
+        );
+        if written.is_err() {
+            return;
+        }
+    }
+}
+
+// A handler error carrying a JSON-RPC error object with `data`, instead of a
+// plain message
+pub fn rpc_error(code: i64, message: &str, data: Value) -> String {
+    json!({"code": code, "message": message, "data": data}).to_string()
+}
+
+// Wrap a value the way "with context" RPC methods return it
+pub fn with_context(value: Value) -> Value {
+    json!({"context": {"slot": 1}, "value": value})
+}
+
+// Answers the calls made while sending and confirming a transaction:
+// every transaction lands and is immediately finalized.
+pub fn transaction_handler(method: &str, params: &Value) -> Result<Value, String> {
+    match method {
+        "getLatestBlockhash" => Ok(with_context(json!({
+            "blockhash": Hash::new_unique().to_string(),
+            "lastValidBlockHeight": 100,
+        }))),
+        "sendTransaction" => Ok(json!(sent_signature(params).to_string())),
+        "getSignatureStatuses" => Ok(with_context(json!([signature_status(1, "finalized", None)]))),
+        "isBlockhashValid" => Ok(with_context(json!(true))),
+        _ => Err(format!("mock does not implement {}", method)),
+    }
+}
+
+// One entry of a `getSignatureStatuses` result
+pub fn signature_status(slot: u64, confirmation_status: &str, err: Option<Value>) -> Value {
+    let status = match &err {
+        Some(err) => json!({"Err": err}),
+        None => json!({"Ok": null}),
+    };
+    // `confirmations` is null once the slot is rooted
+    let confirmations = (confirmation_status != "finalized").then_some(1);
+    json!({
+        "slot": slot,
+        "confirmations": confirmations,
+        "err": err,
+        "status": status,
+        "confirmationStatus": confirmation_status,
+    })
+}
+
+// The wire bytes of a base64 `sendTransaction`/`simulateTransaction` payload
+fn payload(params: &Value) -> Vec<u8> {
+    base64::engine::general_purpose::STANDARD
+        .decode(params[0].as_str().unwrap())
+        .unwrap()
+}
+
+// The signature of a base64 `sendTransaction` payload: the first signature
+// follows the one-byte signature count.
+pub fn sent_signature(params: &Value) -> Signature {
+    Signature::try_from(&payload(params)[1..65]).unwrap()
+}
+
+// The transaction of a base64 `sendTransaction`/`simulateTransaction` payload,
+// as a `Transaction` or a `VersionedTransaction`
+pub fn sent_transaction<T: DeserializeOwned>(params: &Value) -> T {
+    bincode::deserialize(&payload(params)).unwrap()
+}
+
+// The last transaction seen by `recording_handler`
+pub type SentTransaction<T> = Arc<Mutex<Option<T>>>;
+
+// `transaction_handler`, also keeping the last transaction sent in `sent`
+pub fn recording_handler<T: DeserializeOwned + Send + 'static>(
+    sent: &SentTransaction<T>,
+) -> impl Fn(&str, &Value) -> Result<Value, String> + Send + Sync + 'static {
+    let sent = Arc::clone(sent);
+    move |method, params| {
+        if method == "sendTransaction" {
+            *sent.lock().unwrap() = Some(sent_transaction(params));
+        }
+        transaction_handler(method, params)
+    }
+}
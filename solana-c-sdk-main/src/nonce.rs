@@ -0,0 +1,541 @@
+// Durable nonces: a nonce account stores a blockhash that transactions can use
+// instead of a recent one, so they can be signed now and sent much later. Such
+// a transaction must advance the nonce first, which also keeps it from being
+// replayed.
+
+use solana_client::nonce_utils;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    nonce::{self, state::Data},
+    pubkey::Pubkey,
+    signature::Signer,
+    system_instruction,
+};
+use std::ffi::{c_char, CString};
+
+use crate::{
+    client::SolClient,
+    error::{ffi_call, mut_arg, ref_arg, SolError},
+    wallet::{SolKeyPair, SolPublicKey},
+};
+
+impl SolClient {
+    // The account of the nonce at `address`. Read at `confirmed` (or the
+    // client's commitment if that is fresher) so a nonce advanced by the
+    // previous send is seen without waiting for it to finalize
+    fn fetch_nonce_account(&self, address: &Pubkey) -> Result<Account, SolError> {
+        self.rpc(|rpc| {
+            let commitment = if rpc.commitment().is_at_least_confirmed() {
+                CommitmentConfig::confirmed()
+            } else {
+                rpc.commitment()
+            };
+            rpc.get_multiple_accounts_with_commitment(&[*address], commitment)
+        })?
+        .value
+        .pop()
+        .flatten()
+        .ok_or_else(|| SolError::AccountNotFound(format!("nonce account {}", address)))
+    }
+
+    // The stored nonce and authority of the nonce account at `address`
+    pub(crate) fn fetch_nonce(&self, address: &Pubkey) -> Result<Data, SolError> {
+        parse_nonce(address, &self.fetch_nonce_account(address)?)
+    }
+}
+
+// The state of an initialized nonce account
+pub(crate) fn parse_nonce(key: &Pubkey, account: &Account) -> Result<Data, SolError> {
+    nonce_utils::data_from_account(account).map_err(|err| {
+        SolError::InvalidInput(format!(
+            "{} is not an initialized nonce account: {}",
+            key, err
+        ))
+    })
+}
+
+// ==================== C API ==================== //
+
+// A nonce account, from `sol_get_nonce_account`
+#[repr(C)]
+pub struct SolNonceAccount {
+    pub authority: SolPublicKey, // Signs advances, withdrawals and authority changes
+    pub nonce: [u8; 32],         // Stored blockhash that nonce transactions are signed over
+    pub lamports_per_signature: u64, // Fee rate when the nonce was last advanced
+    pub lamports: u64,
+}
+
+// Create and initialize `nonce_account`, a fresh keypair, funded by `payer`
+// with `lamports` (0 = the rent-exempt minimum). `authority` will sign for it.
+// Returns the signature (free with `free_transaction_signature`), or NULL on
+// failure.
+#[no_mangle]
+pub extern "C" fn sol_create_nonce_account(
+    client: *mut SolClient,
+    payer: *mut SolKeyPair,
+    nonce_account: *mut SolKeyPair,
+    authority: *const SolPublicKey,
+    lamports: u64,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let payer = unsafe { ref_arg(payer, "payer")? }.to_keypair()?;
+        let nonce_account = unsafe { ref_arg(nonce_account, "nonce_account")? }.to_keypair()?;
+        let authority = unsafe { ref_arg(authority, "authority")? }.to_pubkey();
+
+        let lamports = match lamports {
+            0 => client
+                .rpc(|rpc| rpc.get_minimum_balance_for_rent_exemption(nonce::State::size()))?,
+            lamports => lamports,
+        };
+        let instructions = system_instruction::create_nonce_account(
+            &payer.pubkey(),
+            &nonce_account.pubkey(),
+            &authority,
+            lamports,
+        );
+        let signature = client.send_instructions(&instructions, &payer, &nonce_account)?;
+        log::info!(
+            "Created nonce account {} (signature {})",
+            nonce_account.pubkey(),
+            signature
+        );
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
+
+// Fetch and parse `nonce_account` into `out`. Fails with `AccountNotFound` if
+// it does not exist, `InvalidInput` if it is not an initialized nonce account.
+#[no_mangle]
+pub extern "C" fn sol_get_nonce_account(
+    client: *mut SolClient,
+    nonce_account: *const SolPublicKey,
+    out: *mut SolNonceAccount,
+) -> bool {
+    ffi_call(false, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let nonce_account = unsafe { ref_arg(nonce_account, "nonce_account")? }.to_pubkey();
+        let out = unsafe { mut_arg(out, "out")? };
+
+        let account = client.fetch_nonce_account(&nonce_account)?;
+        let data = parse_nonce(&nonce_account, &account)?;
+        *out = SolNonceAccount {
+            authority: SolPublicKey::new(data.authority),
+            nonce: data.blockhash().to_bytes(),
+            lamports_per_signature: data.get_lamports_per_signature(),
+            lamports: account.lamports,
+        };
+        Ok(true)
+    })
+}
+
+// Advance the stored nonce of `nonce_account`, signed and paid by its
+// `authority`. Transactions signed over the previous nonce become invalid.
+#[no_mangle]
+pub extern "C" fn sol_advance_nonce_account(
+    client: *mut SolClient,
+    authority: *mut SolKeyPair,
+    nonce_account: *const SolPublicKey,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let authority = unsafe { ref_arg(authority, "authority")? }.to_keypair()?;
+        let nonce_account = unsafe { ref_arg(nonce_account, "nonce_account")? }.to_pubkey();
+
+        let instruction =
+            system_instruction::advance_nonce_account(&nonce_account, &authority.pubkey());
+        let signature = client.send_instructions(&[instruction], &authority, &authority)?;
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
+
+// Withdraw `lamports` from `nonce_account` to `recipient`, signed and paid by
+// its `authority`. Withdrawing the whole balance closes the account; otherwise
+// it must stay rent-exempt.
+#[no_mangle]
+pub extern "C" fn sol_withdraw_nonce_account(
+    client: *mut SolClient,
+    authority: *mut SolKeyPair,
+    nonce_account: *const SolPublicKey,
+    recipient: *const SolPublicKey,
+    lamports: u64,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let authority = unsafe { ref_arg(authority, "authority")? }.to_keypair()?;
+        let nonce_account = unsafe { ref_arg(nonce_account, "nonce_account")? }.to_pubkey();
+        let recipient = unsafe { ref_arg(recipient, "recipient")? }.to_pubkey();
+
+        let instruction = system_instruction::withdraw_nonce_account(
+            &nonce_account,
+            &authority.pubkey(),
+            &recipient,
+            lamports,
+        );
+        let signature = client.send_instructions(&[instruction], &authority, &authority)?;
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
+
+// Hand `nonce_account` over to `new_authority`, signed and paid by its current
+// `authority`
+#[no_mangle]
+pub extern "C" fn sol_authorize_nonce_account(
+    client: *mut SolClient,
+    authority: *mut SolKeyPair,
+    nonce_account: *const SolPublicKey,
+    new_authority: *const SolPublicKey,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let authority = unsafe { ref_arg(authority, "authority")? }.to_keypair()?;
+        let nonce_account = unsafe { ref_arg(nonce_account, "nonce_account")? }.to_pubkey();
+        let new_authority = unsafe { ref_arg(new_authority, "new_authority")? }.to_pubkey();
+
+        let instruction = system_instruction::authorize_nonce_account(
+            &nonce_account,
+            &authority.pubkey(),
+            &new_authority,
+        );
+        let signature = client.send_instructions(&[instruction], &authority, &authority)?;
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{
+        free_serialized_transaction, sol_send_serialized_transaction, sol_tx_builder_add_transfer,
+        sol_tx_builder_free, sol_tx_builder_new, sol_tx_builder_send, sol_tx_builder_serialize,
+        sol_tx_builder_set_compute_unit_price, sol_tx_builder_set_nonce_account,
+        sol_tx_builder_sign,
+    };
+    use crate::client::{new_sol_client, new_sol_client_with_config, SolClientConfig};
+    use crate::confirm::{SolConfirmation, SolConfirmationStatus};
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use crate::mock_rpc::{recording_handler, with_context, MockRpc, SentTransaction};
+    use crate::smart_contract::free_client;
+    use crate::token::free_transaction_signature;
+    use serde_json::{json, Value};
+    use solana_client::rpc_client::SerializableTransaction;
+    use solana_sdk::{
+        compute_budget,
+        hash::Hash,
+        nonce::state::{DurableNonce, State, Versions},
+        system_instruction::SystemInstruction,
+        system_program,
+        transaction::VersionedTransaction,
+    };
+    use std::ffi::CStr;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    const NONCE_RENT: u64 = 1_447_680;
+
+    type Shared<T> = Arc<Mutex<Option<T>>>;
+
+    // A `getMultipleAccounts` entry holding an initialized nonce
+    fn nonce_account(authority: &Pubkey, blockhash: &Hash) -> Value {
+        let state = Versions::new(State::new_initialized(
+            authority,
+            DurableNonce::from_blockhash(blockhash),
+            5_000,
+        ));
+        crate::accounts::tests::keyed_account(
+            &Pubkey::new_unique(),
+            &system_program::id(),
+            NONCE_RENT,
+            &bincode::serialize(&state).unwrap(),
+        )["account"]
+            .clone()
+    }
+
+    // Serves `account` for every address and records the sent transaction
+    fn nonce_rpc(
+        account: Option<Value>,
+    ) -> (
+        MockRpc,
+        Shared<Value>,
+        SentTransaction<VersionedTransaction>,
+    ) {
+        let account = Arc::new(Mutex::new(account));
+        let served = Arc::clone(&account);
+        let sent = SentTransaction::default();
+        let record = recording_handler(&sent);
+        let rpc = MockRpc::start(move |method, params| match method {
+            "getMultipleAccounts" => {
+                assert_eq!(params[1]["commitment"], "confirmed");
+                Ok(with_context(json!([*served.lock().unwrap()])))
+            }
+            "getMinimumBalanceForRentExemption" => {
+                assert_eq!(params[0], json!(nonce::State::size()));
+                Ok(json!(NONCE_RENT))
+            }
+            _ => record(method, params),
+        });
+        (rpc, account, sent)
+    }
+
+    // The system instructions of a transaction, in order
+    fn system_instructions(transaction: &VersionedTransaction) -> Vec<SystemInstruction> {
+        let message = &transaction.message;
+        message
+            .instructions()
+            .iter()
+            .filter(|ix| system_program::check_id(ix.program_id(message.static_account_keys())))
+            .map(|ix| bincode::deserialize(&ix.data).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn gets_nonce_accounts() {
+        let (authority, blockhash) = (Pubkey::new_unique(), Hash::new_unique());
+        let (rpc, account, _) = nonce_rpc(Some(nonce_account(&authority, &blockhash)));
+        let client = new_sol_client(rpc.url.as_ptr());
+        let key = SolPublicKey::new(Pubkey::new_unique());
+        let mut out = SolNonceAccount {
+            authority: SolPublicKey::new(Pubkey::default()),
+            nonce: [0; 32],
+            lamports_per_signature: 0,
+            lamports: 0,
+        };
+
+        assert!(sol_get_nonce_account(client, &key, &mut out));
+        assert_eq!(out.authority.to_pubkey(), authority);
+        assert_eq!(
+            out.nonce,
+            DurableNonce::from_blockhash(&blockhash)
+                .as_hash()
+                .to_bytes()
+        );
+        assert_eq!(out.lamports_per_signature, 5_000);
+        assert_eq!(out.lamports, NONCE_RENT);
+
+        *account.lock().unwrap() = Some(
+            crate::accounts::tests::keyed_account(&key.to_pubkey(), &system_program::id(), 1, &[])
+                ["account"]
+                .clone(),
+        );
+        assert!(!sol_get_nonce_account(client, &key, &mut out));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        *account.lock().unwrap() = None;
+        assert!(!sol_get_nonce_account(client, &key, &mut out));
+        assert_eq!(sol_last_error_code(), SolErrorCode::AccountNotFound);
+
+        free_client(client);
+    }
+
+    #[test]
+    fn manages_nonce_accounts() {
+        let (rpc, _, sent) = nonce_rpc(None);
+        let client = new_sol_client(rpc.url.as_ptr());
+        let mut payer = SolKeyPair::new();
+        let mut nonce = SolKeyPair::new();
+        let mut authority = SolKeyPair::new();
+        let (recipient, new_authority) = (
+            SolPublicKey::new(Pubkey::new_unique()),
+            SolPublicKey::new(Pubkey::new_unique()),
+        );
+        let last_sent = |signature: *mut c_char| {
+            assert!(!signature.is_null());
+            free_transaction_signature(signature);
+            let transaction = sent.lock().unwrap().take().unwrap();
+            (
+                transaction.signatures.len(),
+                system_instructions(&transaction),
+            )
+        };
+
+        let signature =
+            sol_create_nonce_account(client, &mut payer, &mut nonce, &authority.pubkey, 0);
+        let (signers, instructions) = last_sent(signature);
+        assert_eq!(signers, 2);
+        assert_eq!(
+            instructions[0],
+            SystemInstruction::CreateAccount {
+                lamports: NONCE_RENT,
+                space: nonce::State::size() as u64,
+                owner: system_program::id(),
+            }
+        );
+        assert_eq!(
+            instructions[1],
+            SystemInstruction::InitializeNonceAccount(authority.get_pubkey())
+        );
+
+        let signature = sol_advance_nonce_account(client, &mut authority, &nonce.pubkey);
+        assert_eq!(
+            last_sent(signature),
+            (1, vec![SystemInstruction::AdvanceNonceAccount])
+        );
+        let signature =
+            sol_withdraw_nonce_account(client, &mut authority, &nonce.pubkey, &recipient, 42);
+        assert_eq!(
+            last_sent(signature),
+            (1, vec![SystemInstruction::WithdrawNonceAccount(42)])
+        );
+        let signature =
+            sol_authorize_nonce_account(client, &mut authority, &nonce.pubkey, &new_authority);
+        assert_eq!(
+            last_sent(signature),
+            (
+                1,
+                vec![SystemInstruction::AuthorizeNonceAccount(
+                    new_authority.to_pubkey()
+                )]
+            )
+        );
+
+        free_client(client);
+    }
+
+    #[test]
+    fn builder_signs_over_the_stored_nonce() {
+        let mut payer = SolKeyPair::new();
+        let blockhash = Hash::new_unique();
+        let (rpc, _, sent) = nonce_rpc(Some(nonce_account(&payer.get_pubkey(), &blockhash)));
+        let client = new_sol_client(rpc.url.as_ptr());
+        let nonce = SolPublicKey::new(Pubkey::new_unique());
+        let recipient = SolPublicKey::new(Pubkey::new_unique());
+        let signers = [&mut payer as *mut SolKeyPair];
+
+        let builder = sol_tx_builder_new();
+        assert!(sol_tx_builder_set_compute_unit_price(builder, 10));
+        assert!(sol_tx_builder_add_transfer(
+            builder,
+            &payer.pubkey,
+            &recipient,
+            1_000
+        ));
+        assert!(sol_tx_builder_set_nonce_account(
+            builder,
+            &nonce,
+            &payer.pubkey
+        ));
+        assert!(sol_tx_builder_sign(builder, client, signers.as_ptr(), 1));
+        let signature = sol_tx_builder_send(builder, client, std::ptr::null_mut());
+        assert!(!signature.is_null());
+        free_transaction_signature(signature);
+
+        let transaction = sent.lock().unwrap().take().unwrap();
+        assert!(transaction.uses_durable_nonce());
+        assert_eq!(
+            *transaction.get_recent_blockhash(),
+            *DurableNonce::from_blockhash(&blockhash).as_hash()
+        );
+        let message = &transaction.message;
+        let programs: Vec<Pubkey> = message
+            .instructions()
+            .iter()
+            .map(|ix| *ix.program_id(message.static_account_keys()))
+            .collect();
+        assert_eq!(
+            programs,
+            [
+                system_program::id(),
+                compute_budget::id(),
+                system_program::id()
+            ]
+        );
+        assert_eq!(
+            system_instructions(&transaction)[0],
+            SystemInstruction::AdvanceNonceAccount
+        );
+
+        // The nonce authority has to match the account's
+        let other = SolPublicKey::new(Pubkey::new_unique());
+        assert!(sol_tx_builder_set_nonce_account(builder, &nonce, &other));
+        assert!(!sol_tx_builder_sign(builder, client, signers.as_ptr(), 1));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        // Without a nonce account it goes back to the latest blockhash
+        assert!(sol_tx_builder_set_nonce_account(
+            builder,
+            std::ptr::null(),
+            std::ptr::null()
+        ));
+        assert!(sol_tx_builder_sign(builder, client, signers.as_ptr(), 1));
+        let signature = sol_tx_builder_send(builder, client, std::ptr::null_mut());
+        free_transaction_signature(signature);
+        assert!(!sent.lock().unwrap().take().unwrap().uses_durable_nonce());
+
+        sol_tx_builder_free(builder);
+        free_client(client);
+    }
+
+    #[test]
+    fn serialized_nonce_transactions_outlive_their_blockhash() {
+        let mut payer = SolKeyPair::new();
+        let account = nonce_account(&payer.get_pubkey(), &Hash::new_unique());
+        let sent = SentTransaction::<VersionedTransaction>::default();
+        let record = recording_handler(&sent);
+        let polls = AtomicU32::new(0);
+        // Not found on the first poll, long after any blockhash would have expired
+        let rpc = MockRpc::start(move |method, params| match method {
+            "getMultipleAccounts" => Ok(with_context(json!([account]))),
+            "isBlockhashValid" => Ok(with_context(json!(false))),
+            "getSignatureStatuses" if polls.fetch_add(1, Ordering::SeqCst) == 0 => {
+                Ok(with_context(json!([null])))
+            }
+            _ => record(method, params),
+        });
+        let config = SolClientConfig {
+            confirm_poll_interval_ms: 1,
+            ..SolClientConfig::default()
+        };
+        let client = new_sol_client_with_config(rpc.url.as_ptr(), &config);
+        let nonce = SolPublicKey::new(Pubkey::new_unique());
+        let recipient = SolPublicKey::new(Pubkey::new_unique());
+        let signers = [&mut payer as *mut SolKeyPair];
+
+        // Signed and serialized on one side...
+        let builder = sol_tx_builder_new();
+        assert!(sol_tx_builder_add_transfer(
+            builder,
+            &payer.pubkey,
+            &recipient,
+            1_000
+        ));
+        assert!(sol_tx_builder_set_nonce_account(
+            builder,
+            &nonce,
+            &payer.pubkey
+        ));
+        assert!(sol_tx_builder_sign(builder, client, signers.as_ptr(), 1));
+        let mut len = 0;
+        let bytes = sol_tx_builder_serialize(builder, &mut len);
+        assert!(!bytes.is_null());
+        sol_tx_builder_free(builder);
+
+        // ...sent on the other
+        let mut outcome = SolConfirmation {
+            status: SolConfirmationStatus::Failed,
+            slot: 0,
+            error_code: SolErrorCode::Internal,
+            program_error_code: 0,
+        };
+        let signature = sol_send_serialized_transaction(client, bytes, len, &mut outcome);
+        assert!(!signature.is_null());
+        assert_eq!(outcome.status, SolConfirmationStatus::Confirmed);
+        let sent = sent.lock().unwrap().take().unwrap();
+        assert!(sent.uses_durable_nonce());
+        assert_eq!(
+            unsafe { CStr::from_ptr(signature) }.to_str().unwrap(),
+            sent.signatures[0].to_string()
+        );
+        free_transaction_signature(signature);
+        free_serialized_transaction(bytes, len);
+
+        let garbage = [1u8, 2, 3];
+        let signature =
+            sol_send_serialized_transaction(client, garbage.as_ptr(), garbage.len(), &mut outcome);
+        assert!(signature.is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        assert_eq!(outcome.status, SolConfirmationStatus::Failed);
+
+        free_client(client);
+    }
+}
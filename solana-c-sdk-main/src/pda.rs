@@ -0,0 +1,247 @@
+// Program-derived addresses: addresses a program can sign for, derived from
+// seeds and the program id, and addresses derived from a base key and a seed
+// string (`create_with_seed`).
+
+use solana_program::pubkey::{Pubkey, MAX_SEEDS, MAX_SEED_LEN};
+use std::ffi::c_char;
+
+use crate::{
+    error::{ffi_call, mut_arg, ref_arg, slice_arg, str_arg, SolError},
+    wallet::SolPublicKey,
+};
+
+fn check_seeds(seeds: &[&[u8]], max_seeds: usize) -> Result<(), SolError> {
+    if seeds.len() > max_seeds {
+        return Err(SolError::InvalidInput(format!(
+            "{} seeds given, at most {} are allowed",
+            seeds.len(),
+            max_seeds
+        )));
+    }
+    match seeds.iter().position(|seed| seed.len() > MAX_SEED_LEN) {
+        Some(i) => Err(SolError::InvalidInput(format!(
+            "seed {} is {} bytes long, at most {} are allowed",
+            i,
+            seeds[i].len(),
+            MAX_SEED_LEN
+        ))),
+        None => Ok(()),
+    }
+}
+
+// The canonical address of `seeds` under `program_id` and its bump seed
+pub(crate) fn find_program_address(
+    seeds: &[&[u8]],
+    program_id: &Pubkey,
+) -> Result<(Pubkey, u8), SolError> {
+    // One seed is taken by the bump
+    check_seeds(seeds, MAX_SEEDS - 1)?;
+    Pubkey::try_find_program_address(seeds, program_id).ok_or_else(|| {
+        SolError::InvalidInput("no bump seed gives an address off the curve".to_string())
+    })
+}
+
+// The C seed list: `count` pointers to `seed_lens[i]` bytes each.
+// Safety: see `slice_arg`.
+unsafe fn seeds_arg<'a>(
+    seeds: *const *const u8,
+    seed_lens: *const usize,
+    count: usize,
+) -> Result<Vec<&'a [u8]>, SolError> {
+    let seeds = slice_arg(seeds, count, "seeds")?;
+    let lens = slice_arg(seed_lens, count, "seed lengths")?;
+    seeds
+        .iter()
+        .zip(lens)
+        .map(|(&seed, &len)| slice_arg(seed, len, "seed"))
+        .collect()
+}
+
+// ==================== C API ==================== //
+
+// Find the program address of `count` seeds (`seeds[i]` is `seed_lens[i]`
+// bytes) under `program_id`: the first bump, from 255 down, that gives an
+// address off the ed25519 curve. Writes the address and its bump.
+#[no_mangle]
+pub extern "C" fn sol_find_program_address(
+    seeds: *const *const u8,
+    seed_lens: *const usize,
+    count: usize,
+    program_id: *const SolPublicKey,
+    address: *mut SolPublicKey,
+    bump: *mut u8,
+) -> bool {
+    ffi_call(false, || {
+        let seeds = unsafe { seeds_arg(seeds, seed_lens, count)? };
+        let program_id = unsafe { ref_arg(program_id, "program_id")? }.to_pubkey();
+        let address = unsafe { mut_arg(address, "address")? };
+        let bump = unsafe { mut_arg(bump, "bump")? };
+
+        let (pda, pda_bump) = find_program_address(&seeds, &program_id)?;
+        *address = SolPublicKey::new(pda);
+        *bump = pda_bump;
+        Ok(true)
+    })
+}
+
+// Program address of exactly these seeds, which must already end with the
+// bump. Fails with `InvalidInput` if they give an address on the curve.
+#[no_mangle]
+pub extern "C" fn sol_create_program_address(
+    seeds: *const *const u8,
+    seed_lens: *const usize,
+    count: usize,
+    program_id: *const SolPublicKey,
+    address: *mut SolPublicKey,
+) -> bool {
+    ffi_call(false, || {
+        let seeds = unsafe { seeds_arg(seeds, seed_lens, count)? };
+        let program_id = unsafe { ref_arg(program_id, "program_id")? }.to_pubkey();
+        let address = unsafe { mut_arg(address, "address")? };
+
+        check_seeds(&seeds, MAX_SEEDS)?;
+        let pda = Pubkey::create_program_address(&seeds, &program_id).map_err(|err| {
+            SolError::InvalidInput(format!("cannot create program address: {}", err))
+        })?;
+        *address = SolPublicKey::new(pda);
+        Ok(true)
+    })
+}
+
+// Address derived from `base`, a seed string of at most 32 bytes and the
+// `owner` program, as used by `SystemInstruction::CreateAccountWithSeed`
+#[no_mangle]
+pub extern "C" fn sol_create_with_seed(
+    base: *const SolPublicKey,
+    seed: *const c_char,
+    owner: *const SolPublicKey,
+    address: *mut SolPublicKey,
+) -> bool {
+    ffi_call(false, || {
+        let base = unsafe { ref_arg(base, "base")? }.to_pubkey();
+        let seed = unsafe { str_arg(seed, "seed")? };
+        let owner = unsafe { ref_arg(owner, "owner")? }.to_pubkey();
+        let address = unsafe { mut_arg(address, "address")? };
+
+        let derived = Pubkey::create_with_seed(&base, seed, &owner).map_err(|err| {
+            SolError::InvalidInput(format!("cannot derive address with seed: {}", err))
+        })?;
+        *address = SolPublicKey::new(derived);
+        Ok(true)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+
+    fn c_seeds(seeds: &[&[u8]]) -> (Vec<*const u8>, Vec<usize>) {
+        (
+            seeds.iter().map(|seed| seed.as_ptr()).collect(),
+            seeds.iter().map(|seed| seed.len()).collect(),
+        )
+    }
+
+    #[test]
+    fn derives_addresses_like_the_sdk() {
+        let program = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let seeds: [&[u8]; 3] = [b"counter", user.as_ref(), &7u64.to_le_bytes()];
+        let (pointers, lens) = c_seeds(&seeds);
+        let program_id = SolPublicKey::new(program);
+        let mut address = SolPublicKey::new(Pubkey::default());
+        let mut bump = 0;
+
+        assert!(sol_find_program_address(
+            pointers.as_ptr(),
+            lens.as_ptr(),
+            seeds.len(),
+            &program_id,
+            &mut address,
+            &mut bump,
+        ));
+        let expected = Pubkey::find_program_address(&seeds, &program);
+        assert_eq!((address.to_pubkey(), bump), expected);
+
+        // The same address from the seeds with the bump appended
+        let bump_seed = [bump];
+        let with_bump: [&[u8]; 4] = [seeds[0], seeds[1], seeds[2], &bump_seed];
+        let (pointers, lens) = c_seeds(&with_bump);
+        let mut created = SolPublicKey::new(Pubkey::default());
+        assert!(sol_create_program_address(
+            pointers.as_ptr(),
+            lens.as_ptr(),
+            with_bump.len(),
+            &program_id,
+            &mut created,
+        ));
+        assert_eq!(created.to_pubkey(), expected.0);
+
+        let base = SolPublicKey::new(user);
+        let owner = SolPublicKey::new(solana_program::system_program::id());
+        assert!(sol_create_with_seed(
+            &base,
+            c"stake:1".as_ptr(),
+            &owner,
+            &mut created
+        ));
+        assert_eq!(
+            created.to_pubkey(),
+            Pubkey::create_with_seed(&user, "stake:1", &owner.to_pubkey()).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_seeds() {
+        let program_id = SolPublicKey::new(Pubkey::new_unique());
+        let mut address = SolPublicKey::new(Pubkey::default());
+        let mut bump = 0;
+
+        let long = [0u8; 33];
+        let seeds: [&[u8]; 1] = [&long];
+        let (pointers, lens) = c_seeds(&seeds);
+        assert!(!sol_find_program_address(
+            pointers.as_ptr(),
+            lens.as_ptr(),
+            1,
+            &program_id,
+            &mut address,
+            &mut bump,
+        ));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        let seeds: Vec<&[u8]> = vec![b"x"; 16];
+        let (pointers, lens) = c_seeds(&seeds);
+        assert!(!sol_find_program_address(
+            pointers.as_ptr(),
+            lens.as_ptr(),
+            seeds.len(),
+            &program_id,
+            &mut address,
+            &mut bump,
+        ));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        // Empty seeds may be NULL
+        let empty = [std::ptr::null::<u8>()];
+        assert!(sol_find_program_address(
+            empty.as_ptr(),
+            [0].as_ptr(),
+            1,
+            &program_id,
+            &mut address,
+            &mut bump,
+        ));
+
+        let base = SolPublicKey::new(Pubkey::new_unique());
+        let seed = std::ffi::CString::new("s".repeat(33)).unwrap();
+        assert!(!sol_create_with_seed(
+            &base,
+            seed.as_ptr(),
+            &program_id,
+            &mut address
+        ));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+    }
+}
@@ -0,0 +1,508 @@
+// Transaction simulation: run a transaction against the node's current state
+// without submitting it, to learn whether it would succeed, how many compute
+// units it needs, its program logs and the accounts it would leave.
+
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use solana_transaction_status::UiTransactionEncoding;
+use std::ffi::{c_char, CString};
+
+use crate::{
+    accounts::SolAccountInfo,
+    builder::SolTransactionBuilder,
+    client::SolClient,
+    error::{ffi_call, ffi_guard, mut_arg, ref_arg, slice_arg, SolError, SolErrorCode},
+    wallet::SolPublicKey,
+};
+
+impl SolClient {
+    // Simulate a transaction, returning the post-simulation state of
+    // `addresses` too. A failing transaction is a successful simulation: its
+    // error is in the result. Unless every signature is present, signatures
+    // are not checked and the latest blockhash replaces the message's.
+    pub fn simulate(
+        &self,
+        transaction: &VersionedTransaction,
+        addresses: &[Pubkey],
+    ) -> Result<RpcSimulateTransactionResult, SolError> {
+        let accounts = (!addresses.is_empty()).then(|| RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: addresses.iter().map(Pubkey::to_string).collect(),
+        });
+        let signed = transaction
+            .signatures
+            .iter()
+            .all(|signature| *signature != Signature::default());
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: signed,
+            replace_recent_blockhash: !signed,
+            commitment: Some(self.rpc_client.commitment()),
+            encoding: Some(UiTransactionEncoding::Base64),
+            accounts,
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result =
+            self.rpc(|rpc| rpc.simulate_transaction_with_config(transaction, config.clone()))?;
+        Ok(result.value)
+    }
+
+    // Fail with the error the transaction would fail with, without sending it
+    pub fn check_simulation(
+        &self,
+        transaction: impl Into<VersionedTransaction>,
+    ) -> Result<(), SolError> {
+        let result = self.simulate(&transaction.into(), &[])?;
+        match result.err {
+            Some(err) => {
+                log::warn!("Transaction simulation failed: {}", err);
+                Err(simulation_error(err, result.logs.unwrap_or_default()))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+// The error of a failed simulation, with its program logs
+pub(crate) fn simulation_error(err: TransactionError, simulation_logs: Vec<String>) -> SolError {
+    let mut error = SolError::from(err);
+    if let SolError::Program { logs, .. } = &mut error {
+        *logs = simulation_logs;
+    }
+    error
+}
+
+// ==================== C API ==================== //
+
+// Outcome of `sol_tx_builder_simulate` / `sol_simulate_transaction`. Release it
+// with `free_simulation_result`.
+#[repr(C)]
+pub struct SolSimulationResult {
+    pub success: bool,              // The transaction would succeed
+    pub error_code: SolErrorCode,   // Why it would fail, `Ok` on success
+    pub program_error_code: i64,    // Custom program error code, or -1
+    pub error_message: *mut c_char, // NULL on success
+    pub units_consumed: u64,        // Compute units used, 0 if the node did not say
+    pub logs: *mut *mut c_char,     // Program logs
+    pub log_count: usize,
+    pub accounts: *mut SolAccountInfo, // Post-simulation state of the requested accounts
+    pub account_found: *mut bool,      // Whether each requested account exists afterwards
+    pub account_count: usize,
+}
+
+fn into_raw_array<T>(items: Vec<T>) -> *mut T {
+    match items.is_empty() {
+        true => std::ptr::null_mut(),
+        false => Box::into_raw(items.into_boxed_slice()).cast(),
+    }
+}
+
+// Safety: `ptr` must be NULL or come from `into_raw_array` with `len` items.
+unsafe fn from_raw_array<T>(ptr: *mut T, len: usize) -> Vec<T> {
+    match ptr.is_null() {
+        true => Vec::new(),
+        false => Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)).into_vec(),
+    }
+}
+
+fn into_result(
+    result: RpcSimulateTransactionResult,
+    address_count: usize,
+) -> Result<SolSimulationResult, SolError> {
+    let logs = result.logs.unwrap_or_default();
+    let error = result.err.map(|err| simulation_error(err, logs.clone()));
+    let error_message = match &error {
+        Some(error) => CString::new(error.to_string())?.into_raw(),
+        None => std::ptr::null_mut(),
+    };
+    let mut accounts = result.accounts.unwrap_or_default();
+    accounts.resize(address_count, None);
+    let (found, accounts): (Vec<bool>, Vec<SolAccountInfo>) = accounts
+        .into_iter()
+        .map(
+            |account| match account.and_then(|account| account.decode()) {
+                Some(account) => (true, SolAccountInfo::new(account)),
+                None => (false, SolAccountInfo::empty()),
+            },
+        )
+        .unzip();
+    let logs: Vec<*mut c_char> = logs
+        .iter()
+        .map(|log| {
+            CString::new(log.replace('\0', " "))
+                .unwrap_or_default()
+                .into_raw()
+        })
+        .collect();
+
+    Ok(SolSimulationResult {
+        success: error.is_none(),
+        error_code: error.as_ref().map_or(SolErrorCode::Ok, SolError::code),
+        program_error_code: error.as_ref().map_or(-1, SolError::program_code),
+        error_message,
+        units_consumed: result.units_consumed.unwrap_or(0),
+        log_count: logs.len(),
+        logs: into_raw_array(logs),
+        account_count: accounts.len(),
+        accounts: into_raw_array(accounts),
+        account_found: into_raw_array(found),
+    })
+}
+
+// Simulate `transaction` into `out`
+// Safety: see `slice_arg` and `mut_arg`.
+unsafe fn simulate_into(
+    client: &SolClient,
+    transaction: VersionedTransaction,
+    addresses: *const SolPublicKey,
+    address_count: usize,
+    out: *mut SolSimulationResult,
+) -> Result<bool, SolError> {
+    let addresses: Vec<Pubkey> = slice_arg(addresses, address_count, "addresses")?
+        .iter()
+        .map(SolPublicKey::to_pubkey)
+        .collect();
+    let out = mut_arg(out, "out")?;
+    let result = client.simulate(&transaction, &addresses)?;
+    *out = into_result(result, addresses.len())?;
+    Ok(true)
+}
+
+// Simulate the transaction of `builder`: the signed one, or before signing the
+// built one, which needs a fee payer, without signature checks. `addresses`
+// (may be NULL when `address_count` is 0) are accounts whose post-simulation
+// state is wanted. Returns true if the simulation ran, whether or not the
+// transaction would succeed; check `out->success`.
+#[no_mangle]
+pub extern "C" fn sol_tx_builder_simulate(
+    builder: *const SolTransactionBuilder,
+    client: *mut SolClient,
+    addresses: *const SolPublicKey,
+    address_count: usize,
+    out: *mut SolSimulationResult,
+) -> bool {
+    ffi_call(false, || {
+        let builder = unsafe { ref_arg(builder, "builder")? };
+        let client = unsafe { ref_arg(client, "client")? };
+        let transaction = builder.transaction(client)?;
+        unsafe { simulate_into(client, transaction, addresses, address_count, out) }
+    })
+}
+
+// Same as `sol_tx_builder_simulate` for a transaction in wire format, e.g.
+// from `sol_tx_builder_serialize`. Transactions missing signatures are
+// simulated without signature checks.
+#[no_mangle]
+pub extern "C" fn sol_simulate_transaction(
+    client: *mut SolClient,
+    transaction: *const u8,
+    transaction_len: usize,
+    addresses: *const SolPublicKey,
+    address_count: usize,
+    out: *mut SolSimulationResult,
+) -> bool {
+    ffi_call(false, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let bytes = unsafe { slice_arg(transaction, transaction_len, "transaction")? };
+        let transaction: VersionedTransaction = bincode::deserialize(bytes).map_err(|err| {
+            SolError::InvalidInput(format!("not a serialized transaction: {}", err))
+        })?;
+        unsafe { simulate_into(client, transaction, addresses, address_count, out) }
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn free_simulation_result(result: *mut SolSimulationResult) {
+    ffi_guard((), || {
+        let Some(result) = (unsafe { result.as_mut() }) else {
+            return;
+        };
+        unsafe {
+            if !result.error_message.is_null() {
+                drop(CString::from_raw(result.error_message));
+            }
+            for log in from_raw_array(result.logs, result.log_count) {
+                drop(CString::from_raw(log));
+            }
+            for mut account in from_raw_array(result.accounts, result.account_count) {
+                crate::accounts::free_account_info(&mut account);
+            }
+            drop(from_raw_array(result.account_found, result.account_count));
+        }
+        result.error_message = std::ptr::null_mut();
+        result.logs = std::ptr::null_mut();
+        result.log_count = 0;
+        result.accounts = std::ptr::null_mut();
+        result.account_found = std::ptr::null_mut();
+        result.account_count = 0;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{
+        free_serialized_transaction, sol_tx_builder_add_transfer, sol_tx_builder_free,
+        sol_tx_builder_new, sol_tx_builder_serialize, sol_tx_builder_set_fee_payer,
+        sol_tx_builder_sign,
+    };
+    use crate::client::{new_sol_client_with_config, SolClientConfig};
+    use crate::error::sol_last_error_code;
+    use crate::mock_rpc::{transaction_handler, with_context, MockRpc};
+    use crate::smart_contract::free_client;
+    use crate::token::{mint_spl, transfer_sol};
+    use crate::wallet::SolKeyPair;
+    use serde_json::{json, Value};
+    use std::ffi::CStr;
+    use std::sync::{Arc, Mutex};
+
+    const LOGS: [&str; 2] = [
+        "Program 11111111111111111111111111111111 invoke [1]",
+        "Program 11111111111111111111111111111111 success",
+    ];
+
+    fn simulation(err: Value, accounts: Value) -> Value {
+        with_context(json!({
+            "err": err,
+            "logs": LOGS,
+            "accounts": accounts,
+            "unitsConsumed": 150,
+            "returnData": null,
+        }))
+    }
+
+    fn text(ptr: *const c_char) -> String {
+        unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn simulates_built_and_serialized_transactions() {
+        let recipient = Pubkey::new_unique();
+        let config = Arc::new(Mutex::new(Value::Null));
+        let seen = Arc::clone(&config);
+        let rpc = MockRpc::start(move |method, params| match method {
+            "simulateTransaction" => {
+                *seen.lock().unwrap() = params[1].clone();
+                Ok(simulation(
+                    Value::Null,
+                    json!([crate::accounts::tests::keyed_account(
+                        &recipient,
+                        &solana_sdk::system_program::id(),
+                        1_000,
+                        &[],
+                    )["account"]
+                        .clone()]),
+                ))
+            }
+            _ => transaction_handler(method, params),
+        });
+        let client = new_sol_client_with_config(rpc.url.as_ptr(), &SolClientConfig::default());
+        let payer = Box::into_raw(Box::new(SolKeyPair::new()));
+        let to = SolPublicKey::new(recipient);
+        let builder = sol_tx_builder_new();
+        assert!(sol_tx_builder_add_transfer(
+            builder,
+            &unsafe { &*payer }.pubkey,
+            &to,
+            1_000
+        ));
+
+        // Before signing, the built transaction runs without signature checks
+        let mut result = unsafe { std::mem::zeroed::<SolSimulationResult>() };
+        assert!(sol_tx_builder_set_fee_payer(
+            builder,
+            &unsafe { &*payer }.pubkey
+        ));
+        assert!(sol_tx_builder_simulate(
+            builder,
+            client,
+            std::ptr::null(),
+            0,
+            &mut result
+        ));
+        assert!(result.success);
+        free_simulation_result(&mut result);
+        let sent = config.lock().unwrap().clone();
+        assert_eq!(sent["sigVerify"], json!(false));
+        assert_eq!(sent["replaceRecentBlockhash"], json!(true));
+
+        let signers = [payer];
+        assert!(sol_tx_builder_sign(builder, client, signers.as_ptr(), 1));
+        assert!(sol_tx_builder_simulate(
+            builder,
+            client,
+            &to,
+            1,
+            &mut result
+        ));
+        assert!(result.success);
+        assert_eq!(result.error_code, SolErrorCode::Ok);
+        assert_eq!(result.program_error_code, -1);
+        assert!(result.error_message.is_null());
+        assert_eq!(result.units_consumed, 150);
+        let logs: Vec<String> = (0..result.log_count)
+            .map(|i| text(unsafe { *result.logs.add(i) }))
+            .collect();
+        assert_eq!(logs, LOGS);
+        assert_eq!(result.account_count, 1);
+        assert!(unsafe { *result.account_found });
+        assert_eq!(unsafe { &*result.accounts }.lamports, 1_000);
+        free_simulation_result(&mut result);
+        assert!(result.logs.is_null() && result.accounts.is_null());
+
+        let sent = config.lock().unwrap().clone();
+        assert_eq!(sent["sigVerify"], json!(true));
+        assert_eq!(sent["replaceRecentBlockhash"], json!(false));
+        assert_eq!(sent["encoding"], json!("base64"));
+        assert_eq!(
+            sent["accounts"]["addresses"],
+            json!([recipient.to_string()])
+        );
+
+        let mut len = 0;
+        let bytes = sol_tx_builder_serialize(builder, &mut len);
+        assert!(sol_simulate_transaction(
+            client,
+            bytes,
+            len,
+            std::ptr::null(),
+            0,
+            &mut result
+        ));
+        assert!(result.success && result.account_count == 0);
+        free_simulation_result(&mut result);
+        free_serialized_transaction(bytes, len);
+
+        assert!(!sol_simulate_transaction(
+            client,
+            [1u8, 2].as_ptr(),
+            2,
+            std::ptr::null(),
+            0,
+            &mut result
+        ));
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+
+        sol_tx_builder_free(builder);
+        free_client(client);
+        unsafe { drop(Box::from_raw(payer)) };
+    }
+
+    #[test]
+    fn failing_simulation_aborts_the_send() {
+        let sent = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&sent);
+        let rpc = MockRpc::start(move |method, params| match method {
+            "simulateTransaction" => Ok(simulation(
+                json!({"InstructionError": [0, {"Custom": 1}]}),
+                Value::Null,
+            )),
+            "sendTransaction" => {
+                *counter.lock().unwrap() += 1;
+                transaction_handler(method, params)
+            }
+            // The recipient's token account of `mint_spl` exists
+            "getAccountInfo" => Ok(with_context(
+                crate::accounts::tests::keyed_account(
+                    &Pubkey::new_unique(),
+                    &spl_token::id(),
+                    1_000,
+                    &[],
+                )["account"]
+                    .clone(),
+            )),
+            _ => transaction_handler(method, params),
+        });
+        let config = SolClientConfig {
+            simulate_before_send: true,
+            ..SolClientConfig::default()
+        };
+        let client = new_sol_client_with_config(rpc.url.as_ptr(), &config);
+        let payer = Box::into_raw(Box::new(SolKeyPair::new()));
+        let mut recipient = SolPublicKey::new(Pubkey::new_unique());
+
+        assert!(transfer_sol(client, payer, &mut recipient, 10).is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::ProgramError);
+        assert_eq!(crate::error::sol_last_error_program_code(), 1);
+        match crate::error::last_error() {
+            Some(SolError::Program { logs, .. }) => assert_eq!(logs, LOGS),
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert_eq!(*sent.lock().unwrap(), 0);
+
+        let mut mint_authority = SolKeyPair::new();
+        assert!(!mint_spl(
+            client,
+            payer,
+            &mut mint_authority,
+            &mut recipient,
+            10
+        ));
+        assert_eq!(sol_last_error_code(), SolErrorCode::ProgramError);
+        assert_eq!(*sent.lock().unwrap(), 0);
+
+        // The same simulation through the builder reports the failure in the result
+        let builder = sol_tx_builder_new();
+        assert!(sol_tx_builder_add_transfer(
+            builder,
+            &unsafe { &*payer }.pubkey,
+            &recipient,
+            10
+        ));
+        let signers = [payer];
+        assert!(sol_tx_builder_sign(builder, client, signers.as_ptr(), 1));
+        let mut result = unsafe { std::mem::zeroed::<SolSimulationResult>() };
+        assert!(sol_tx_builder_simulate(
+            builder,
+            client,
+            std::ptr::null(),
+            0,
+            &mut result
+        ));
+        assert!(!result.success);
+        assert_eq!(result.error_code, SolErrorCode::ProgramError);
+        assert_eq!(result.program_error_code, 1);
+        assert!(text(result.error_message).contains("custom program error 0x1"));
+        free_simulation_result(&mut result);
+
+        sol_tx_builder_free(builder);
+        free_client(client);
+        unsafe { drop(Box::from_raw(payer)) };
+    }
+
+    #[test]
+    fn automatic_unit_limit_counts_as_the_simulation_before_sending() {
+        let simulations = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&simulations);
+        let rpc = MockRpc::start(move |method, params| match method {
+            "simulateTransaction" => {
+                *counter.lock().unwrap() += 1;
+                Ok(simulation(Value::Null, Value::Null))
+            }
+            _ => transaction_handler(method, params),
+        });
+        let mut config = SolClientConfig {
+            simulate_before_send: true,
+            ..SolClientConfig::default()
+        };
+        config.compute_budget.auto_unit_limit = true;
+        let client = new_sol_client_with_config(rpc.url.as_ptr(), &config);
+        let payer = Box::into_raw(Box::new(SolKeyPair::new()));
+        let mut recipient = SolPublicKey::new(Pubkey::new_unique());
+
+        let signature = transfer_sol(client, payer, &mut recipient, 10);
+        assert!(!signature.is_null());
+        assert_eq!(*simulations.lock().unwrap(), 1);
+
+        crate::token::free_transaction_signature(signature);
+        free_client(client);
+        unsafe { drop(Box::from_raw(payer)) };
+    }
+}
@@ -1,31 +1,47 @@
-use borsh::{BorshDeserialize, BorshSerialize};
 use sha2::{Digest, Sha256};
-use solana_client::rpc_client::RpcClient;
 use solana_program::pubkey::Pubkey;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
-    signature::{Keypair, Signer},
-    signer::EncodableKey,
+    signature::{Keypair, Signature, Signer},
     system_program,
-    transaction::Transaction,
 };
-use spl_associated_token_account::tools::account;
-use std::ffi::{CStr, CString};
+use std::ffi::{c_void, CString};
 use std::os::raw::c_char;
 use std::str::FromStr;
 
 use crate::{
     client::SolClient,
+    confirm::{outcome_arg, write_outcome, SendOptions, SolSendOptions},
+    error::{ffi_call, ffi_guard, ref_arg, slice_arg, str_arg, SolError},
+    jobs::{spawn_job, Completion, JobOutput, SolCompletionCallback},
     wallet::{SolKeyPair, SolPublicKey},
 };
 
+// An account passed to an instruction, with explicit signer/writable flags
+#[repr(C)]
+pub struct SolAccountMeta {
+    pub pubkey: SolPublicKey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl SolAccountMeta {
+    pub fn to_account_meta(&self) -> AccountMeta {
+        AccountMeta {
+            pubkey: self.pubkey.to_pubkey(),
+            is_signer: self.is_signer,
+            is_writable: self.is_writable,
+        }
+    }
+}
+
 // ==================== Utility Functions ==================== //
 
 #[no_mangle]
 pub extern "C" fn get_system_program_id() -> SolPublicKey {
-    SolPublicKey {
-        data: system_program::ID.to_bytes(),
-    }
+    ffi_guard(SolPublicKey { data: [0u8; 32] }, || {
+        SolPublicKey::new(system_program::ID)
+    })
 }
 
 #[no_mangle]
@@ -36,40 +52,41 @@ pub extern "C" fn get_account_data_c(
     data_len: usize,
     data_offset: usize, // Offset for skipping metadata/discriminator
 ) -> usize {
-    let client = unsafe { &mut *client };
-    let pubkey = Pubkey::new_from_array(unsafe { (*account_pubkey).data });
-
-    // Fetch account data from Solana
-    match client.rpc_client.get_account(&pubkey) {
-        Ok(account) => {
-            let account_data = &account.data;
-            if account_data.len() <= data_offset {
-                eprintln!("❌ Account data too small.");
-                return 0;
-            }
-
-            let data_slice = &account_data[data_offset..];
-            let copy_len = std::cmp::min(data_len, data_slice.len());
-
-            // Copy data into provided buffer
-            unsafe {
-                std::ptr::copy_nonoverlapping(data_slice.as_ptr(), data_ptr, copy_len);
-            }
-
-            println!("✅ Account data fetched ({} bytes).", copy_len);
-            copy_len
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let pubkey = unsafe { ref_arg(account_pubkey, "account_pubkey")? }.to_pubkey();
+        if data_ptr.is_null() && data_len > 0 {
+            return Err(SolError::InvalidInput("data_ptr is null".to_string()));
         }
-        Err(err) => {
-            eprintln!("❌ Failed to fetch account: {:?}", err);
-            0
+
+        // Fetch account data from Solana
+        let account = client.rpc(|rpc| rpc.get_account(&pubkey))?;
+        let account_data = &account.data;
+        if account_data.len() <= data_offset {
+            return Err(SolError::InvalidInput(format!(
+                "account data ({} bytes) is smaller than offset {}",
+                account_data.len(),
+                data_offset
+            )));
         }
-    }
+
+        let data_slice = &account_data[data_offset..];
+        let copy_len = std::cmp::min(data_len, data_slice.len());
+
+        // Copy data into provided buffer
+        unsafe {
+            std::ptr::copy_nonoverlapping(data_slice.as_ptr(), data_ptr, copy_len);
+        }
+
+        log::debug!("Account data fetched ({} bytes)", copy_len);
+        Ok(copy_len)
+    })
 }
 
 // Load Payer Keypair
 
 // Compute Discriminator
-fn get_discriminator(method_name: &str) -> Vec<u8> {
+pub(crate) fn get_discriminator(method_name: &str) -> Vec<u8> {
     let mut hasher = Sha256::new();
     hasher.update(format!("global:{}", method_name).as_bytes());
     hasher.finalize()[..8].to_vec()
@@ -81,17 +98,33 @@ fn create_instruction(
     method_name: &str,
     accounts: Vec<AccountMeta>,
     data: Vec<u8>,
+) -> Result<Instruction, SolError> {
+    let program_id = parse_program_id(program_id)?;
+    Ok(anchor_instruction(&program_id, method_name, accounts, data))
+}
+
+fn parse_program_id(program_id: &str) -> Result<Pubkey, SolError> {
+    Pubkey::from_str(program_id).map_err(|err| {
+        SolError::InvalidInput(format!("invalid program ID '{}': {}", program_id, err))
+    })
+}
+
+// Prefix `data` with the Anchor discriminator of `method_name`
+pub(crate) fn anchor_instruction(
+    program_id: &Pubkey,
+    method_name: &str,
+    accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
 ) -> Instruction {
-    let discriminator = get_discriminator(method_name);
-    let mut instruction_data = discriminator;
+    let mut instruction_data = get_discriminator(method_name);
     instruction_data.extend(data);
-    let program_id = Pubkey::from_str(program_id).expect("Invalid program ID");
-
-    Instruction::new_with_bytes(program_id, &instruction_data, accounts)
+    Instruction::new_with_bytes(*program_id, &instruction_data, accounts)
 }
 
 // ==================== Transaction Functions ==================== //
 
+// Compatibility shim for `send_anchor_transaction_c`: every account is passed
+// writable, and as a signer if it is one of `signers`.
 #[no_mangle]
 pub extern "C" fn send_generic_transaction_c(
     client: *mut SolClient,
@@ -104,82 +137,548 @@ pub extern "C" fn send_generic_transaction_c(
     data_ptr: *const u8,
     data_len: usize,
 ) -> *mut c_char {
-    let client = unsafe { &mut *client };
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let (instruction, signer_keypairs) = unsafe {
+            generic_transaction_args(
+                program_id,
+                method_name,
+                account_pubkeys,
+                account_count,
+                signers,
+                signer_count,
+                data_ptr,
+                data_len,
+            )?
+        };
+
+        let sig = send_generic_transaction(client, instruction, &signer_keypairs)?;
+        Ok(CString::new(sig.to_string())?.into_raw())
+    })
+}
 
-    let program_id = unsafe { CStr::from_ptr(program_id).to_str().unwrap() };
-    let method_name = unsafe { CStr::from_ptr(method_name).to_str().unwrap() };
+#[no_mangle]
+pub extern "C" fn send_generic_transaction_async(
+    client: *mut SolClient,
+    program_id: *const c_char,
+    method_name: *const c_char,
+    account_pubkeys: *const SolPublicKey,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let task = unsafe {
+            generic_transaction_task(
+                client,
+                program_id,
+                method_name,
+                account_pubkeys,
+                account_count,
+                signers,
+                signer_count,
+                data_ptr,
+                data_len,
+                options,
+            )?
+        };
+        spawn_job(client, None, task)
+    })
+}
 
-    // Deserialize account pubkeys
-    let mut accounts = unsafe {
-        std::slice::from_raw_parts(account_pubkeys, account_count)
-            .iter()
-            .map(|a| AccountMeta::new(a.to_pubkey(), false)) // Default signer = false
-            .collect::<Vec<AccountMeta>>()
-    };
+#[no_mangle]
+pub extern "C" fn send_generic_transaction_with_callback(
+    client: *mut SolClient,
+    program_id: *const c_char,
+    method_name: *const c_char,
+    account_pubkeys: *const SolPublicKey,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+    callback: SolCompletionCallback,
+    user_data: *mut c_void,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let completion = Completion::new(callback, user_data)?;
+        let task = unsafe {
+            generic_transaction_task(
+                client,
+                program_id,
+                method_name,
+                account_pubkeys,
+                account_count,
+                signers,
+                signer_count,
+                data_ptr,
+                data_len,
+                options,
+            )?
+        };
+        spawn_job(client, Some(completion), task)
+    })
+}
 
+// Copy the arguments of `send_generic_transaction_c` into a job.
+// Safety: see `generic_transaction_args`.
+#[allow(clippy::too_many_arguments)]
+unsafe fn generic_transaction_task(
+    client: &SolClient,
+    program_id: *const c_char,
+    method_name: *const c_char,
+    account_pubkeys: *const SolPublicKey,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+) -> Result<impl FnOnce(&SolClient) -> Result<JobOutput, SolError>, SolError> {
+    let (instruction, signer_keypairs) = generic_transaction_args(
+        program_id,
+        method_name,
+        account_pubkeys,
+        account_count,
+        signers,
+        signer_count,
+        data_ptr,
+        data_len,
+    )?;
+    let options = SendOptions::from_arg(client, options)?;
+
+    Ok(move |client: &SolClient| {
+        let (sig, _) = send_transaction_with(client, instruction, &signer_keypairs, &options)?;
+        Ok(JobOutput::Signature(sig))
+    })
+}
+
+// Build the instruction and signer list of `send_generic_transaction_c`. Kept
+// for compatibility: every account is writable, and a signer if it matches one
+// of `signers`.
+// Safety: the pointer arguments must be valid as described by that function.
+#[allow(clippy::too_many_arguments)]
+unsafe fn generic_transaction_args(
+    program_id: *const c_char,
+    method_name: *const c_char,
+    account_pubkeys: *const SolPublicKey,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+) -> Result<(Instruction, Vec<Keypair>), SolError> {
     // Process signers
-    let signer_refs = unsafe {
-        std::slice::from_raw_parts(signers, signer_count)
-            .iter()
-            .map(|s| unsafe { &**s }) // Dereference raw pointers to SolKeyPair
-            .collect::<Vec<&SolKeyPair>>()
-    };
-
-    let payer = signer_refs
-        .first()
-        .expect("At least one signer (payer) required"); // Ensure the first signer is the payer
-
-    // Mark signer accounts as signers
-    for signer in &signer_refs {
-        if let Some(account) = accounts
-            .iter_mut()
-            .find(|acc| acc.pubkey == signer.get_pubkey())
-        {
-            account.is_signer = true;
-        }
+    let signer_refs = slice_arg(signers, signer_count, "signers")?
+        .iter()
+        .map(|s| ref_arg(*s, "signer")) // Dereference raw pointers to SolKeyPair
+        .collect::<Result<Vec<&SolKeyPair>, SolError>>()?;
+
+    let accounts = slice_arg(account_pubkeys, account_count, "accounts")?
+        .iter()
+        .map(|a| SolAccountMeta {
+            pubkey: SolPublicKey::new(a.to_pubkey()),
+            is_signer: signer_refs.iter().any(|s| s.get_pubkey() == a.to_pubkey()),
+            is_writable: true,
+        })
+        .collect::<Vec<SolAccountMeta>>();
+
+    // A NULL data pointer means no data, whatever its length
+    let data_len = if data_ptr.is_null() { 0 } else { data_len };
+
+    anchor_transaction_args(
+        program_id,
+        method_name,
+        accounts.as_ptr(),
+        accounts.len(),
+        signers,
+        signer_count,
+        data_ptr,
+        data_len,
+    )
+}
+
+// ==================== Anchor Instructions ==================== //
+
+// Call an Anchor method. `data` holds the Borsh-encoded arguments (the method
+// discriminator is prepended) and each account keeps its own signer/writable
+// flags. The first signer pays the fee. `options` may be NULL for the client's defaults.
+#[no_mangle]
+pub extern "C" fn send_anchor_transaction_c(
+    client: *mut SolClient,
+    program_id: *const c_char,
+    method_name: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+) -> *mut c_char {
+    let (signature, slot) = ffi_call((std::ptr::null_mut(), None), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let (instruction, signer_keypairs) = unsafe {
+            anchor_transaction_args(
+                program_id,
+                method_name,
+                accounts,
+                account_count,
+                signers,
+                signer_count,
+                data_ptr,
+                data_len,
+            )?
+        };
+        let options = unsafe { SendOptions::from_arg(client, options)? };
+
+        let (sig, slot) = send_transaction_with(client, instruction, &signer_keypairs, &options)?;
+        Ok((CString::new(sig.to_string())?.into_raw(), Some(slot)))
+    });
+    write_outcome(unsafe { outcome_arg(options) }, slot);
+    signature
+}
+
+#[no_mangle]
+pub extern "C" fn send_anchor_transaction_async(
+    client: *mut SolClient,
+    program_id: *const c_char,
+    method_name: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let task = unsafe {
+            anchor_transaction_task(
+                client,
+                program_id,
+                method_name,
+                accounts,
+                account_count,
+                signers,
+                signer_count,
+                data_ptr,
+                data_len,
+                options,
+            )?
+        };
+        spawn_job(client, None, task)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn send_anchor_transaction_with_callback(
+    client: *mut SolClient,
+    program_id: *const c_char,
+    method_name: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+    callback: SolCompletionCallback,
+    user_data: *mut c_void,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let completion = Completion::new(callback, user_data)?;
+        let task = unsafe {
+            anchor_transaction_task(
+                client,
+                program_id,
+                method_name,
+                accounts,
+                account_count,
+                signers,
+                signer_count,
+                data_ptr,
+                data_len,
+                options,
+            )?
+        };
+        spawn_job(client, Some(completion), task)
+    })
+}
+
+// Copy the arguments of `send_anchor_transaction_c` into a job.
+// Safety: see `anchor_transaction_args`.
+#[allow(clippy::too_many_arguments)]
+unsafe fn anchor_transaction_task(
+    client: &SolClient,
+    program_id: *const c_char,
+    method_name: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+) -> Result<impl FnOnce(&SolClient) -> Result<JobOutput, SolError>, SolError> {
+    let (instruction, signer_keypairs) = anchor_transaction_args(
+        program_id,
+        method_name,
+        accounts,
+        account_count,
+        signers,
+        signer_count,
+        data_ptr,
+        data_len,
+    )?;
+    let options = SendOptions::from_arg(client, options)?;
+
+    Ok(move |client: &SolClient| {
+        let (sig, _) = send_transaction_with(client, instruction, &signer_keypairs, &options)?;
+        Ok(JobOutput::Signature(sig))
+    })
+}
+
+// Build the instruction and signer list of `send_anchor_transaction_c`.
+// Safety: the pointer arguments must be valid as described by that function.
+#[allow(clippy::too_many_arguments)]
+unsafe fn anchor_transaction_args(
+    program_id: *const c_char,
+    method_name: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+) -> Result<(Instruction, Vec<Keypair>), SolError> {
+    let program_id = str_arg(program_id, "program ID")?;
+    let method_name = str_arg(method_name, "method name")?;
+    let accounts = slice_arg(accounts, account_count, "accounts")?
+        .iter()
+        .map(SolAccountMeta::to_account_meta)
+        .collect::<Vec<AccountMeta>>();
+    let signer_keypairs = signer_keypairs(signers, signer_count)?;
+    let data = slice_arg(data_ptr, data_len, "data")?.to_vec();
+
+    let instruction = create_instruction(program_id, method_name, accounts, data)?;
+    Ok((instruction, signer_keypairs))
+}
+
+// Copy a C signer list. The first signer is the payer, so it cannot be empty.
+// Safety: `signers` must point to `signer_count` valid `SolKeyPair` pointers.
+pub(crate) unsafe fn signer_keypairs(
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+) -> Result<Vec<Keypair>, SolError> {
+    let signer_keypairs = slice_arg(signers, signer_count, "signers")?
+        .iter()
+        .map(|s| ref_arg(*s, "signer")?.to_keypair()) // Dereference raw pointers to SolKeyPair
+        .collect::<Result<Vec<Keypair>, SolError>>()?;
+
+    if signer_keypairs.is_empty() {
+        return Err(SolError::InvalidInput(
+            "at least one signer (payer) is required".to_string(),
+        ));
     }
+    Ok(signer_keypairs)
+}
 
-    // Deserialize additional data if provided
-    let data = if data_ptr.is_null() {
-        vec![]
-    } else {
-        unsafe { std::slice::from_raw_parts(data_ptr, data_len).to_vec() }
-    };
-
-    // Create the transaction instruction
-    let instruction = create_instruction(program_id, method_name, accounts, data);
-
-    // Get latest blockhash
-    let blockhash = match client.rpc_client.get_latest_blockhash() {
-        Ok(bh) => bh,
-        Err(e) => {
-            eprintln!("Failed to get blockhash: {:?}", e);
-            return CString::new("Failed to fetch blockhash")
-                .unwrap()
-                .into_raw();
-        }
-    };
+// ==================== Raw Instructions ==================== //
+
+// Call any program: `data` is sent unchanged (no Anchor discriminator) and each
+// account keeps its own signer/writable flags. The first signer pays the fee.
+// `options` may be NULL for the client's defaults.
+#[no_mangle]
+pub extern "C" fn send_raw_instruction_c(
+    client: *mut SolClient,
+    program_id: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+) -> *mut c_char {
+    let (signature, slot) = ffi_call((std::ptr::null_mut(), None), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let (instruction, signer_keypairs) = unsafe {
+            raw_instruction_args(
+                program_id,
+                accounts,
+                account_count,
+                signers,
+                signer_count,
+                data_ptr,
+                data_len,
+            )?
+        };
+        let options = unsafe { SendOptions::from_arg(client, options)? };
+
+        let (sig, slot) = send_transaction_with(client, instruction, &signer_keypairs, &options)?;
+        Ok((CString::new(sig.to_string())?.into_raw(), Some(slot)))
+    });
+    write_outcome(unsafe { outcome_arg(options) }, slot);
+    signature
+}
+
+#[no_mangle]
+pub extern "C" fn send_raw_instruction_async(
+    client: *mut SolClient,
+    program_id: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let task = unsafe {
+            raw_instruction_task(
+                client,
+                program_id,
+                accounts,
+                account_count,
+                signers,
+                signer_count,
+                data_ptr,
+                data_len,
+                options,
+            )?
+        };
+        spawn_job(client, None, task)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn send_raw_instruction_with_callback(
+    client: *mut SolClient,
+    program_id: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+    callback: SolCompletionCallback,
+    user_data: *mut c_void,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let completion = Completion::new(callback, user_data)?;
+        let task = unsafe {
+            raw_instruction_task(
+                client,
+                program_id,
+                accounts,
+                account_count,
+                signers,
+                signer_count,
+                data_ptr,
+                data_len,
+                options,
+            )?
+        };
+        spawn_job(client, Some(completion), task)
+    })
+}
+
+// Copy the arguments of `send_raw_instruction_c` into a job.
+// Safety: see `raw_instruction_args`.
+#[allow(clippy::too_many_arguments)]
+unsafe fn raw_instruction_task(
+    client: &SolClient,
+    program_id: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+    options: *const SolSendOptions,
+) -> Result<impl FnOnce(&SolClient) -> Result<JobOutput, SolError>, SolError> {
+    let (instruction, signer_keypairs) = raw_instruction_args(
+        program_id,
+        accounts,
+        account_count,
+        signers,
+        signer_count,
+        data_ptr,
+        data_len,
+    )?;
+    let options = SendOptions::from_arg(client, options)?;
+
+    Ok(move |client: &SolClient| {
+        let (sig, _) = send_transaction_with(client, instruction, &signer_keypairs, &options)?;
+        Ok(JobOutput::Signature(sig))
+    })
+}
 
-    // Convert signers to Keypair list
-    let signer_keypairs: Vec<Keypair> = signer_refs.iter().map(|s| s.to_keypair()).collect();
+// Build the instruction and signer list of `send_raw_instruction_c`.
+// Safety: the pointer arguments must be valid as described by that function.
+unsafe fn raw_instruction_args(
+    program_id: *const c_char,
+    accounts: *const SolAccountMeta,
+    account_count: usize,
+    signers: *const *mut SolKeyPair,
+    signer_count: usize,
+    data_ptr: *const u8,
+    data_len: usize,
+) -> Result<(Instruction, Vec<Keypair>), SolError> {
+    let program_id = parse_program_id(str_arg(program_id, "program ID")?)?;
+    let accounts = slice_arg(accounts, account_count, "accounts")?
+        .iter()
+        .map(SolAccountMeta::to_account_meta)
+        .collect::<Vec<AccountMeta>>();
+    let data = slice_arg(data_ptr, data_len, "data")?;
+    let signer_keypairs = signer_keypairs(signers, signer_count)?;
+
+    Ok((Instruction::new_with_bytes(program_id, data, accounts), signer_keypairs))
+}
 
-    let signer_refs: Vec<&Keypair> = signer_keypairs.iter().collect();
+// Sign with `signers` (the first one pays the fee), send and confirm
+pub(crate) fn send_generic_transaction(
+    client: &SolClient,
+    instruction: Instruction,
+    signers: &[Keypair],
+) -> Result<Signature, SolError> {
+    Ok(send_transaction_with(client, instruction, signers, &SendOptions::of(client))?.0)
+}
 
-    let transaction = Transaction::new_signed_with_payer(
+// Same as `send_generic_transaction`, under the budget of `options`, as a v0
+// transaction if they have lookup tables. Also returns the slot it landed in.
+pub(crate) fn send_transaction_with(
+    client: &SolClient,
+    instruction: Instruction,
+    signers: &[Keypair],
+    options: &SendOptions,
+) -> Result<(Signature, u64), SolError> {
+    let signer_refs: Vec<&Keypair> = signers.iter().collect();
+    let tables = client.fetch_lookup_tables(&options.lookup_tables)?;
+
+    // Signed over the latest blockhash
+    let transaction = client.sign_with_budget(
         &[instruction],
-        Some(&payer.get_pubkey()), // Payer must be the first signer
+        &signers[0].pubkey(), // Payer must be the first signer
         &signer_refs,
-        blockhash,
-    );
+        &options.budget,
+        &tables,
+    )?;
 
-    let result = client.rpc_client.send_and_confirm_transaction(&transaction);
-
-    match result {
-        Ok(sig) => CString::new(sig.to_string()).unwrap().into_raw(),
-        Err(err) => CString::new(format!("Transaction failed: {:?}", err))
-            .unwrap()
-            .into_raw(),
-    }
+    client.send_budgeted(&transaction, &options.budget)
 }
 
 // Initialize Account
@@ -189,67 +688,221 @@ pub extern "C" fn initialize_account_c(
     payer: *mut SolKeyPair,
     account: *mut SolKeyPair,
     program_id: *const c_char,
-) {
-    let client = unsafe { &mut *client };
-    let payer = unsafe { &mut *payer };
-
-    let program_id = unsafe { CStr::from_ptr(program_id).to_str().unwrap() };
-    let account = &unsafe { &mut *account }.to_keypair();
+) -> bool {
+    ffi_call(false, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let payer = unsafe { ref_arg(payer, "payer")? }.to_keypair()?;
+
+        let program_id = unsafe { str_arg(program_id, "program ID")? };
+        let account = &unsafe { ref_arg(account, "account")? }.to_keypair()?;
+
+        let instruction = create_instruction(
+            program_id,
+            "initialize",
+            vec![
+                AccountMeta::new(account.pubkey(), true),
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            vec![],
+        )?;
+
+        let transaction = client.sign_with_budget(
+            &[instruction],
+            &payer.pubkey(),
+            &[&payer, account],
+            &client.config.compute_budget,
+            &[],
+        )?;
+
+        let (sig, _) = client.send_budgeted(&transaction, &client.config.compute_budget)?;
+        log::info!("Account initialized: {} (signature {})", account.pubkey(), sig);
+        Ok(true)
+    })
+}
 
-    let instruction = create_instruction(
-        program_id,
-        "initialize",
-        vec![
-            AccountMeta::new(account.pubkey(), true),
-            AccountMeta::new(payer.to_keypair().pubkey(), true),
-            AccountMeta::new_readonly(system_program::ID, false),
-        ],
-        vec![],
-    );
-
-    let blockhash = match client.rpc_client.get_latest_blockhash() {
-        Ok(b) => b,
-        Err(e) => {
-            eprintln!("Failed to fetch latest blockhash: {:?}", e);
+// ==================== Free Memory ==================== //
+#[no_mangle]
+pub extern "C" fn free_client(client: *mut SolClient) {
+    ffi_guard((), || {
+        if client.is_null() {
             return;
         }
-    };
+        unsafe {
+            drop(Box::from_raw(client));
+        }
+    })
+}
 
-    let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
-        Some(&payer.to_keypair().pubkey()),
-        &[&payer.to_keypair(), &account],
-        blockhash,
-    );
-
-    match client.rpc_client.send_and_confirm_transaction(&transaction) {
-        Ok(sig) => {
-            println!("✅ Account initialized: {}", account.pubkey());
-            println!("Transaction Signature: {}", sig);
+#[no_mangle]
+pub extern "C" fn free_payer(payer: *mut SolKeyPair) {
+    ffi_guard((), || {
+        if payer.is_null() {
+            return;
         }
-        Err(err) => {
-            eprintln!("❌ Failed to initialize account: {:?}", err);
+        unsafe {
+            drop(Box::from_raw(payer));
         }
-    }
+    })
 }
 
-// ==================== Free Memory ==================== //
-#[no_mangle]
-pub extern "C" fn free_client(client: *mut SolClient) {
-    if client.is_null() {
-        return;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::new_sol_client;
+    use crate::error::{sol_last_error_code, SolErrorCode};
+    use crate::mock_rpc::{recording_handler, MockRpc, SentTransaction};
+    use crate::token::free_transaction_signature;
+    use solana_sdk::transaction::Transaction;
+
+    fn meta(pubkey: Pubkey, is_signer: bool, is_writable: bool) -> SolAccountMeta {
+        SolAccountMeta {
+            pubkey: SolPublicKey::new(pubkey),
+            is_signer,
+            is_writable,
+        }
     }
-    unsafe {
-        Box::from_raw(client);
+
+    // A mock that lands every transaction and keeps the last one sent
+    fn recording_rpc() -> (MockRpc, SentTransaction<Transaction>) {
+        let sent = SentTransaction::default();
+        let rpc = MockRpc::start(recording_handler(&sent));
+        (rpc, sent)
     }
-}
 
-#[no_mangle]
-pub extern "C" fn free_payer(payer: *mut SolKeyPair) {
-    if payer.is_null() {
-        return;
+    // (pubkey, is_signer, is_writable) of each account of the first instruction
+    fn account_flags(transaction: &Transaction) -> Vec<(Pubkey, bool, bool)> {
+        let message = &transaction.message;
+        message.instructions[0]
+            .accounts
+            .iter()
+            .map(|&i| {
+                let i = i as usize;
+                (message.account_keys[i], message.is_signer(i), message.is_maybe_writable(i, None))
+            })
+            .collect()
     }
-    unsafe {
-        Box::from_raw(payer);
+
+    #[test]
+    fn raw_instruction_is_sent_unchanged() {
+        let (rpc, sent) = recording_rpc();
+        let client = new_sol_client(rpc.url.as_ptr());
+        let mut payer = SolKeyPair::new();
+        let program_id = Pubkey::new_unique();
+        let program_id_str = CString::new(program_id.to_string()).unwrap();
+        let (sysvar, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let accounts = [meta(sysvar, false, false), meta(vault, false, true)];
+        let signers = [&mut payer as *mut SolKeyPair];
+        let data = [9u8, 0, 1];
+
+        let signature = send_raw_instruction_c(
+            client,
+            program_id_str.as_ptr(),
+            accounts.as_ptr(),
+            accounts.len(),
+            signers.as_ptr(),
+            signers.len(),
+            data.as_ptr(),
+            data.len(),
+            std::ptr::null(),
+        );
+        assert!(!signature.is_null());
+        free_transaction_signature(signature);
+
+        let transaction = sent.lock().unwrap().take().unwrap();
+        let message = &transaction.message;
+        let instruction = &message.instructions[0];
+        assert_eq!(*instruction.program_id(&message.account_keys), program_id);
+        assert_eq!(instruction.data, data);
+        assert_eq!(account_flags(&transaction), [(sysvar, false, false), (vault, false, true)]);
+        assert_eq!(message.account_keys[0], payer.get_pubkey());
+
+        free_client(client);
+    }
+
+    #[test]
+    fn anchor_accounts_keep_their_flags() {
+        let (rpc, sent) = recording_rpc();
+        let client = new_sol_client(rpc.url.as_ptr());
+        let mut payer = SolKeyPair::new();
+        let mut authority = SolKeyPair::new();
+        let program_id = CString::new(Pubkey::new_unique().to_string()).unwrap();
+        let (counter, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let signers = [&mut payer as *mut SolKeyPair, &mut authority as *mut SolKeyPair];
+
+        let accounts = [
+            meta(counter, false, true),
+            meta(authority.get_pubkey(), true, false),
+            meta(mint, false, false),
+        ];
+        let signature = send_anchor_transaction_c(
+            client,
+            program_id.as_ptr(),
+            c"increment".as_ptr(),
+            accounts.as_ptr(),
+            accounts.len(),
+            signers.as_ptr(),
+            signers.len(),
+            [5u8].as_ptr(),
+            1,
+            std::ptr::null(),
+        );
+        assert!(!signature.is_null());
+        free_transaction_signature(signature);
+        let transaction = sent.lock().unwrap().take().unwrap();
+        let mut expected_data = get_discriminator("increment");
+        expected_data.push(5);
+        assert_eq!(transaction.message.instructions[0].data, expected_data);
+        assert_eq!(
+            account_flags(&transaction),
+            [
+                (counter, false, true),
+                (authority.get_pubkey(), true, false),
+                (mint, false, false),
+            ]
+        );
+
+        // The old entry point marks every account writable, and signers as signers
+        let pubkeys = [SolPublicKey::new(counter), SolPublicKey::new(authority.get_pubkey())];
+        let signature = send_generic_transaction_c(
+            client,
+            program_id.as_ptr(),
+            c"increment".as_ptr(),
+            pubkeys.as_ptr(),
+            pubkeys.len(),
+            signers.as_ptr(),
+            signers.len(),
+            std::ptr::null(),
+            8,
+        );
+        assert!(!signature.is_null());
+        free_transaction_signature(signature);
+        let transaction = sent.lock().unwrap().take().unwrap();
+        assert_eq!(transaction.message.instructions[0].data, get_discriminator("increment"));
+        assert_eq!(
+            account_flags(&transaction),
+            [(counter, false, true), (authority.get_pubkey(), true, true)]
+        );
+
+        free_client(client);
+    }
+
+    #[test]
+    fn raw_instruction_requires_a_payer() {
+        let client = new_sol_client(c"http://127.0.0.1:1".as_ptr());
+        let signature = send_raw_instruction_c(
+            client,
+            c"11111111111111111111111111111111".as_ptr(),
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+        );
+        assert!(signature.is_null());
+        assert_eq!(sol_last_error_code(), SolErrorCode::InvalidInput);
+        free_client(client);
     }
 }
@@ -4,7 +4,6 @@ use solana_account_decoder::UiAccountData;
 
 use solana_client::{
     rpc_request::TokenAccountsFilter,
-    rpc_client::RpcClient, // RpcClient 임포트 확인
     rpc_config::RpcTransactionConfig, // 설정 추가
 };
 use solana_sdk::{
@@ -12,20 +11,25 @@ use solana_sdk::{
     program_pack::Pack,
     pubkey::Pubkey,
     signer::Signer,
-    transaction::Transaction,
     commitment_config::CommitmentConfig, // 커밋먼트 설정 추가
-    signature::{Signature, ParseSignatureError}, // Signature와 ParseSignatureError를 여기서 한 번에 가져옴
+    signature::{Keypair, Signature},
 };
-use spl_associated_token_account;
 use spl_token::state::Mint; // Add this line to import the module
 use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding}; // 트랜잭션 정보 타입
 use std::{
-    ffi::{c_char, CString, NulError},
+    ffi::{c_char, c_void, CString},
     str::FromStr, // FromStr 트레잇 사용
 };
 
+use crate::confirm::{outcome_arg, write_outcome, SendOptions, SolSendOptions};
+use crate::error::{ffi_call, ffi_guard, ref_arg, str_arg, SolError};
+use crate::fees::SolComputeBudget;
+use crate::jobs::{spawn_job, Completion, JobOutput, SolCompletionCallback};
 use crate::wallet::SolKeyPair;
-use crate::{client::SolClient, wallet::SolPublicKey};
+use crate::{
+    client::{SolClient, SolCommitment},
+    wallet::SolPublicKey,
+};
 
 #[repr(C)]
 pub struct SolMint {
@@ -54,35 +58,39 @@ pub extern "C" fn get_all_tokens(
     client: *mut SolClient,
     wallet: *mut SolPublicKey,
 ) -> *mut TokenList {
-    // Safety: Ensure pointers are not null
-    let client = unsafe {
-        assert!(!client.is_null());
-        &*client
-    };
+    ffi_call(std::ptr::null_mut(), || {
+        // Safety: Ensure pointers are not null
+        let client = unsafe { ref_arg(client, "client")? };
+        let wallet = unsafe { ref_arg(wallet, "wallet")? };
+
+        let entries = fetch_token_entries(client, &wallet.to_pubkey())?;
+        Ok(into_token_list(entries))
+    })
+}
 
-    let wallet = unsafe {
-        assert!(!wallet.is_null());
-        &*wallet
-    };
+#[no_mangle]
+pub extern "C" fn get_all_tokens_async(client: *mut SolClient, wallet: *mut SolPublicKey) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let wallet = unsafe { ref_arg(wallet, "wallet")? }.to_pubkey();
+
+        spawn_job(client, None, move |client| {
+            Ok(JobOutput::Tokens(fetch_token_entries(client, &wallet)?))
+        })
+    })
+}
 
-    let wallet_pubkey = Pubkey::new_from_array(wallet.data);
+// (mint, balance, owner) of one token account
+pub type TokenEntry = (CString, CString, CString);
 
+fn fetch_token_entries(client: &SolClient, wallet_pubkey: &Pubkey) -> Result<Vec<TokenEntry>, SolError> {
     // Fetch all token accounts owned by the wallet
-    let token_accounts = match client.rpc_client.get_token_accounts_by_owner(
-        &wallet_pubkey,
-        TokenAccountsFilter::ProgramId(spl_token::id()),
-    ) {
-        Ok(accounts) => accounts,
-        Err(err) => {
-            eprintln!(
-                "Error fetching token accounts for wallet {}: {:?}",
-                wallet_pubkey, err
-            );
-            return std::ptr::null_mut();
-        }
-    };
+    let token_accounts = client.rpc(|rpc| {
+        rpc.get_token_accounts_by_owner(wallet_pubkey, TokenAccountsFilter::ProgramId(spl_token::id()))
+    })?;
 
-    let mut tokens: Vec<TokenInfo> = Vec::new();
+    // Build every string first so nothing leaks if one of them fails
+    let mut entries: Vec<TokenEntry> = Vec::new();
 
     for keyed_account in token_accounts {
         if let UiAccountData::Json(parsed_data) = keyed_account.account.data {
@@ -94,24 +102,33 @@ pub extern "C" fn get_all_tokens(
                         .and_then(|v| v.as_str()),
                     info.get("owner").and_then(|v| v.as_str()),
                 ) {
-                    let mint_c = CString::new(mint).unwrap();
-                    let balance_c = CString::new(balance).unwrap();
-                    let owner_c = CString::new(owner).unwrap();
-
-                    tokens.push(TokenInfo {
-                        mint: mint_c.into_raw(),
-                        balance: balance_c.into_raw(),
-                        owner: owner_c.into_raw(),
-                    });
+                    entries.push((
+                        CString::new(mint)?,
+                        CString::new(balance)?,
+                        CString::new(owner)?,
+                    ));
                 }
             }
         } else {
-            eprintln!(
+            log::warn!(
                 "Unexpected account data format for account: {}",
                 keyed_account.pubkey
             );
         }
     }
+    Ok(entries)
+}
+
+pub fn into_token_list(entries: Vec<TokenEntry>) -> *mut TokenList {
+    let mut tokens: Vec<TokenInfo> = entries
+        .into_iter()
+        .map(|(mint, balance, owner)| TokenInfo {
+            mint: mint.into_raw(),
+            balance: balance.into_raw(),
+            owner: owner.into_raw(),
+        })
+        .collect();
+    tokens.shrink_to_fit(); // free_token_list rebuilds the Vec with capacity == len
 
     let token_list = Box::new(TokenList {
         data: tokens.as_mut_ptr(),
@@ -124,11 +141,13 @@ pub extern "C" fn get_all_tokens(
 
 #[no_mangle]
 pub extern "C" fn token_list_get_data(list: *const TokenList) -> *mut TokenInfo {
-    if list.is_null() {
-        std::ptr::null_mut()
-    } else {
-        unsafe { (*list).data }
-    }
+    ffi_guard(std::ptr::null_mut(), || {
+        if list.is_null() {
+            std::ptr::null_mut()
+        } else {
+            unsafe { (*list).data }
+        }
+    })
 }
 
 // --- 새로운 함수: 트랜잭션 상세 정보 조회 (JSON 반환) ---
@@ -137,112 +156,115 @@ pub extern "C" fn get_transaction_details_json(
     client: *mut SolClient,
     signature_str: *const c_char,
 ) -> *mut c_char {
-    // Safety: 포인터 유효성 검사
-    let client = unsafe {
-        if client.is_null() {
-            eprintln!("Error: SolClient pointer is null in get_transaction_details_json");
-            return std::ptr::null_mut();
-        }
-        &*client
-    };
-    let signature_cstr = unsafe {
-        if signature_str.is_null() {
-            eprintln!("Error: Signature string pointer is null in get_transaction_details_json");
-            return std::ptr::null_mut();
-        }
-        std::ffi::CStr::from_ptr(signature_str)
-    };
+    ffi_call(std::ptr::null_mut(), || {
+        // Safety: 포인터 유효성 검사
+        let client = unsafe { ref_arg(client, "client")? };
+        fetch_transaction_details_json(client, signature_str, client.transaction_commitment())
+    })
+}
+
+// Same as `get_transaction_details_json`, but looks the transaction up at the given commitment
+// (`Processed` is not supported by the RPC and is read as `Confirmed`)
+#[no_mangle]
+pub extern "C" fn get_transaction_details_json_with_commitment(
+    client: *mut SolClient,
+    signature_str: *const c_char,
+    commitment: u32,
+) -> *mut c_char {
+    ffi_call(std::ptr::null_mut(), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        fetch_transaction_details_json(
+            client,
+            signature_str,
+            SolCommitment::try_from(commitment)?.to_transaction_commitment_config(),
+        )
+    })
+}
+
+fn fetch_transaction_details_json(
+    client: &SolClient,
+    signature_str: *const c_char,
+    commitment: CommitmentConfig,
+) -> Result<*mut c_char, SolError> {
+    let tx_with_meta = fetch_transaction(client, signature_str, commitment)?;
+
+    // 트랜잭션 정보를 JSON 문자열로 직렬화
+    let json_string = serde_json::to_string(&tx_with_meta).map_err(|e| {
+        SolError::Internal(format!("failed to serialize transaction details: {}", e))
+    })?;
+
+    // JSON 문자열을 C 문자열 포인터로 변환하여 반환
+    Ok(CString::new(json_string)?.into_raw())
+}
 
+// Look a transaction and its status metadata up by signature
+pub(crate) fn fetch_transaction(
+    client: &SolClient,
+    signature_str: *const c_char,
+    commitment: CommitmentConfig,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta, SolError> {
     // C 문자열을 Rust 문자열로 변환
-    let signature_slice = match signature_cstr.to_str() {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("Error converting signature C string to Rust string: {}", e);
-            return std::ptr::null_mut();
-        }
-    };
+    let signature_slice = unsafe { str_arg(signature_str, "signature")? };
 
     // 문자열을 Signature 객체로 파싱
-    let signature = match Signature::from_str(signature_slice) {
-        Ok(sig) => sig,
-        Err(e) => {
-            eprintln!("Error parsing signature string '{}': {}", signature_slice, e);
-            return std::ptr::null_mut();
-        }
-    };
+    let signature = Signature::from_str(signature_slice).map_err(|e| {
+        SolError::InvalidInput(format!("invalid signature '{}': {}", signature_slice, e))
+    })?;
 
     // RPC 호출 설정 (JsonParsed 인코딩 사용 필수)
     let config = RpcTransactionConfig {
         encoding: Some(UiTransactionEncoding::JsonParsed),
-        commitment: Some(CommitmentConfig::confirmed()), // 또는 finalized()
+        commitment: Some(commitment),
         max_supported_transaction_version: Some(0), // 최신 버전 지원
     };
 
     // RPC 호출: get_transaction
-    match client.rpc_client.get_transaction_with_config(&signature, config) {
-        Ok(tx_with_meta) => {
-            // 트랜잭션 정보를 JSON 문자열로 직렬화
-            match serde_json::to_string(&tx_with_meta) {
-                Ok(json_string) => {
-                    // JSON 문자열을 C 문자열 포인터로 변환하여 반환
-                    match CString::new(json_string) {
-                        Ok(c_json) => c_json.into_raw(),
-                        Err(e) => {
-                            eprintln!("Error converting JSON string to CString: {}", e);
-                            std::ptr::null_mut()
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error serializing transaction details to JSON: {}", e);
-                    std::ptr::null_mut()
-                }
-            }
-        }
-        Err(e) => {
-            eprintln!("Error fetching transaction details for signature {}: {}", signature, e);
-            std::ptr::null_mut()
-        }
-    }
+    Ok(client.rpc(|rpc| rpc.get_transaction_with_config(&signature, config))?)
 }
 
 // --- 새로운 함수: JSON 문자열 메모리 해제 ---
 #[no_mangle]
 pub extern "C" fn free_transaction_details_json(json_ptr: *mut c_char) {
-    if !json_ptr.is_null() {
-        unsafe {
-            // CString::from_raw을 호출하여 Rust가 메모리 소유권을 다시 가져오고,
-            // _guard 변수가 범위를 벗어날 때 자동으로 메모리가 해제됩니다.
-            let _guard = CString::from_raw(json_ptr);
+    ffi_guard((), || {
+        if !json_ptr.is_null() {
+            unsafe {
+                // CString::from_raw을 호출하여 Rust가 메모리 소유권을 다시 가져오고,
+                // _guard 변수가 범위를 벗어날 때 자동으로 메모리가 해제됩니다.
+                let _guard = CString::from_raw(json_ptr);
+            }
         }
-    }
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn token_list_get_len(list: *const TokenList) -> usize {
-    if list.is_null() {
-        0
-    } else {
-        unsafe { (*list).len }
-    }
+    ffi_guard(0, || {
+        if list.is_null() {
+            0
+        } else {
+            unsafe { (*list).len }
+        }
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn free_token_list(list: *mut TokenList) {
-    if list.is_null() {
-        return;
-    }
+    ffi_guard((), || {
+        if list.is_null() {
+            return;
+        }
 
-    unsafe {
-        let list = Box::from_raw(list);
-        for i in 0..list.len {
-            let token_info = &mut *list.data.add(i);
-            CString::from_raw(token_info.mint as *mut c_char);
-            CString::from_raw(token_info.balance as *mut c_char);
-            CString::from_raw(token_info.owner as *mut c_char);
+        unsafe {
+            let list = Box::from_raw(list);
+            for i in 0..list.len {
+                let token_info = &mut *list.data.add(i);
+                drop(CString::from_raw(token_info.mint as *mut c_char));
+                drop(CString::from_raw(token_info.balance as *mut c_char));
+                drop(CString::from_raw(token_info.owner as *mut c_char));
+            }
+            drop(Vec::from_raw_parts(list.data, list.len, list.len));
         }
-        Vec::from_raw_parts(list.data, list.len, list.len);
-    }
+    })
 }
 
 #[no_mangle]
@@ -252,86 +274,124 @@ pub extern "C" fn transfer_sol(
     recipient: *mut SolPublicKey,
     lamports: u64,
 ) -> *mut c_char { // Return type changed to *mut c_char
-    // Safety: Ensure pointers are not null
-    let client = unsafe {
-        assert!(!client.is_null());
-        &*client
-    };
+    ffi_call(std::ptr::null_mut(), || {
+        // Safety: Ensure pointers are not null
+        let client = unsafe { ref_arg(client, "client")? };
+        let sender = unsafe { ref_arg(sender, "sender")? };
+        let recipient = unsafe { ref_arg(recipient, "recipient")? };
+
+        let options = SendOptions::of(client);
+        let (signature, _) = send_sol(client, &sender.to_keypair()?, &recipient.to_pubkey(), lamports, &options)?;
+
+        // Convert signature to CString and transfer ownership to C
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
 
-    let sender = unsafe {
-        assert!(!sender.is_null());
-        &*sender
-    };
+// Same as `transfer_sol`, with per-call `options` (NULL = the client's defaults)
+#[no_mangle]
+pub extern "C" fn transfer_sol_with_options(
+    client: *mut SolClient,
+    sender: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    lamports: u64,
+    options: *const SolSendOptions,
+) -> *mut c_char {
+    let (signature, slot) = ffi_call((std::ptr::null_mut(), None), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let sender = unsafe { ref_arg(sender, "sender")? };
+        let recipient = unsafe { ref_arg(recipient, "recipient")? };
+        let options = unsafe { SendOptions::from_arg(client, options)? };
+
+        let (signature, slot) = send_sol(client, &sender.to_keypair()?, &recipient.to_pubkey(), lamports, &options)?;
+        Ok((CString::new(signature.to_string())?.into_raw(), Some(slot)))
+    });
+    write_outcome(unsafe { outcome_arg(options) }, slot);
+    signature
+}
 
-    let recipient = unsafe {
-        assert!(!recipient.is_null());
-        &*recipient
-    };
+#[no_mangle]
+pub extern "C" fn transfer_sol_async(
+    client: *mut SolClient,
+    sender: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    lamports: u64,
+    options: *const SolSendOptions,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let task = unsafe { transfer_sol_task(client, sender, recipient, lamports, options)? };
+        spawn_job(client, None, task)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn transfer_sol_with_callback(
+    client: *mut SolClient,
+    sender: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    lamports: u64,
+    options: *const SolSendOptions,
+    callback: SolCompletionCallback,
+    user_data: *mut c_void,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let completion = Completion::new(callback, user_data)?;
+        let task = unsafe { transfer_sol_task(client, sender, recipient, lamports, options)? };
+        spawn_job(client, Some(completion), task)
+    })
+}
+
+// Copy the arguments of `transfer_sol_with_options` into a job.
+// Safety: non-null pointers must be valid for the duration of the call.
+unsafe fn transfer_sol_task(
+    client: &SolClient,
+    sender: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    lamports: u64,
+    options: *const SolSendOptions,
+) -> Result<impl FnOnce(&SolClient) -> Result<JobOutput, SolError>, SolError> {
+    let sender = ref_arg(sender, "sender")?.to_keypair()?;
+    let recipient = ref_arg(recipient, "recipient")?.to_pubkey();
+    let options = SendOptions::from_arg(client, options)?;
+
+    Ok(move |client: &SolClient| {
+        Ok(JobOutput::Signature(send_sol(client, &sender, &recipient, lamports, &options)?.0))
+    })
+}
 
-    let sender_keypair = sender.to_keypair(); // Get Keypair
+// Returns the signature and the slot the transfer was confirmed in
+fn send_sol(
+    client: &SolClient,
+    sender_keypair: &Keypair,
+    recipient_pubkey: &Pubkey,
+    lamports: u64,
+    options: &SendOptions,
+) -> Result<(Signature, u64), SolError> {
     let sender_pubkey = sender_keypair.pubkey(); // Get Pubkey from Keypair
-    let recipient_pubkey = Pubkey::new_from_array(recipient.data);
-
-    // Verify that the sender's account exists
-    // match client.rpc_client.get_account(&sender_pubkey) {
-    //     Ok(account) => {
-    //         if account.lamports < lamports {
-    //             eprintln!(
-    //                 "Sender does not have enough SOL. Balance: {}, Required: {}",
-    //                 account.lamports, lamports
-    //             );
-    //             return false;
-    //         }
-    //     }
-    //     Err(err) => {
-    //         eprintln!("Error checking sender's account: {:?}", err);
-    //         return false;
-    //     }
-    // }
 
     // Step 1: Create the transfer instruction
     let transfer_instruction =
-        solana_sdk::system_instruction::transfer(&sender_pubkey, &recipient_pubkey, lamports);
-
-    // Step 2: Fetch the recent blockhash
-    let recent_blockhash = match client.rpc_client.get_latest_blockhash() {
-        Ok(blockhash) => blockhash,
-        Err(err) => {
-            eprintln!("Error fetching latest blockhash: {:?}", err);
-            return std::ptr::null_mut(); // Return null on error
-        }
-    };
+        solana_sdk::system_instruction::transfer(&sender_pubkey, recipient_pubkey, lamports);
 
-    // Step 3: Create and sign the transaction
-    // Note: No longer needs to be mutable if you don't modify it after creation
-    let transaction = Transaction::new_signed_with_payer(
+    // Step 2 & 3: Create and sign the transaction over the recent blockhash
+    let tables = client.fetch_lookup_tables(&options.lookup_tables)?;
+    let transaction = client.sign_with_budget(
         &[transfer_instruction],
-        Some(&sender_pubkey),    // Fee payer
-        &[&sender_keypair],      // Signer (pass the actual Keypair)
-        recent_blockhash,
+        &sender_pubkey,          // Fee payer
+        &[sender_keypair],       // Signer (pass the actual Keypair)
+        &options.budget,
+        &tables,
+    )?;
+
+    // Step 4: Send and confirm the transaction
+    let (signature, slot) = client.send_budgeted(&transaction, &options.budget)?;
+    log::info!(
+        "Successfully transferred {} lamports from {} to {}. Signature: {}",
+        lamports, sender_pubkey, recipient_pubkey, signature
     );
-
-    // Step 4: Send and confirm the transaction (using send_and_confirm for simplicity)
-    match client.rpc_client.send_and_confirm_transaction_with_spinner(&transaction) {
-        Ok(signature) => { // Capture the signature here
-            println!(
-                "Successfully transferred {} lamports from {} to {}. Signature: {}",
-                lamports, sender_pubkey, recipient_pubkey, signature
-            );
-            // Convert signature to CString and return pointer
-            match CString::new(signature.to_string()) {
-                Ok(c_string) => c_string.into_raw(), // Transfer ownership to C
-                Err(e) => {
-                    eprintln!("Error converting signature to CString: {}", e);
-                    std::ptr::null_mut() // Return null if conversion fails
-                }
-            }
-        }
-        Err(err) => {
-            eprintln!("Error sending and confirming transaction: {:?}", err);
-            std::ptr::null_mut() // Return null on error
-        }
-    }
+    Ok((signature, slot))
 }
 
 #[no_mangle]
@@ -342,114 +402,166 @@ pub extern "C" fn transfer_spl(
     mint: *mut SolPublicKey,
     amount: u64,
 ) -> *mut c_char { // Return type changed to *mut c_char
-    // Safety checks...
-    // Safety: Ensure pointers are not null
-    let client = unsafe {
-        assert!(!client.is_null());
-        &*client
-    };
+    ffi_call(std::ptr::null_mut(), || {
+        // Safety: Ensure pointers are not null
+        let client = unsafe { ref_arg(client, "client")? };
+        let sender = unsafe { ref_arg(sender, "sender")? };
+        let recipient = unsafe { ref_arg(recipient, "recipient")? };
+        let mint = unsafe { ref_arg(mint, "mint")? };
+
+        let options = SendOptions::of(client);
+        let (signature, _) = send_spl(client, sender, &recipient.to_pubkey(), &mint.to_pubkey(), amount, &options)?;
+
+        // Convert signature to CString and transfer ownership to C
+        Ok(CString::new(signature.to_string())?.into_raw())
+    })
+}
 
-    let sender = unsafe {
-        assert!(!sender.is_null());
-        &*sender
-    };
+// Same as `transfer_spl`, with per-call `options` (NULL = the client's defaults).
+// Their budget also applies to the creation of the recipient's token account.
+#[no_mangle]
+pub extern "C" fn transfer_spl_with_options(
+    client: *mut SolClient,
+    sender: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    mint: *mut SolPublicKey,
+    amount: u64,
+    options: *const SolSendOptions,
+) -> *mut c_char {
+    let (signature, slot) = ffi_call((std::ptr::null_mut(), None), || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let sender = unsafe { ref_arg(sender, "sender")? };
+        let recipient = unsafe { ref_arg(recipient, "recipient")? };
+        let mint = unsafe { ref_arg(mint, "mint")? };
+        let options = unsafe { SendOptions::from_arg(client, options)? };
+
+        let (signature, slot) =
+            send_spl(client, sender, &recipient.to_pubkey(), &mint.to_pubkey(), amount, &options)?;
+        Ok((CString::new(signature.to_string())?.into_raw(), Some(slot)))
+    });
+    write_outcome(unsafe { outcome_arg(options) }, slot);
+    signature
+}
 
-    let recipient = unsafe {
-        assert!(!recipient.is_null());
-        &*recipient
-    };
+#[no_mangle]
+pub extern "C" fn transfer_spl_async(
+    client: *mut SolClient,
+    sender: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    mint: *mut SolPublicKey,
+    amount: u64,
+    options: *const SolSendOptions,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let task = unsafe { transfer_spl_task(client, sender, recipient, mint, amount, options)? };
+        spawn_job(client, None, task)
+    })
+}
 
-    let mint = unsafe {
-        assert!(!mint.is_null());
-        &*mint
-    };
+#[no_mangle]
+pub extern "C" fn transfer_spl_with_callback(
+    client: *mut SolClient,
+    sender: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    mint: *mut SolPublicKey,
+    amount: u64,
+    options: *const SolSendOptions,
+    callback: SolCompletionCallback,
+    user_data: *mut c_void,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let completion = Completion::new(callback, user_data)?;
+        let task = unsafe { transfer_spl_task(client, sender, recipient, mint, amount, options)? };
+        spawn_job(client, Some(completion), task)
+    })
+}
 
-    let sender_keypair = sender.to_keypair(); // Get the keypair once
+// Copy the arguments of `transfer_spl_with_options` into a job.
+// Safety: non-null pointers must be valid for the duration of the call.
+unsafe fn transfer_spl_task(
+    client: &SolClient,
+    sender: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    mint: *mut SolPublicKey,
+    amount: u64,
+    options: *const SolSendOptions,
+) -> Result<impl FnOnce(&SolClient) -> Result<JobOutput, SolError>, SolError> {
+    // Copy the keypair: the caller may free it before the job runs
+    let sender = SolKeyPair::from_existing(ref_arg(sender, "sender")?.to_keypair()?);
+    let recipient = ref_arg(recipient, "recipient")?.to_pubkey();
+    let mint = ref_arg(mint, "mint")?.to_pubkey();
+    let options = SendOptions::from_arg(client, options)?;
+
+    Ok(move |client: &SolClient| {
+        Ok(JobOutput::Signature(send_spl(client, &sender, &recipient, &mint, amount, &options)?.0))
+    })
+}
+
+// Returns the signature and the slot the transfer was confirmed in
+fn send_spl(
+    client: &SolClient,
+    sender: &SolKeyPair,
+    recipient_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    amount: u64,
+    options: &SendOptions,
+) -> Result<(Signature, u64), SolError> {
+    let sender_keypair = sender.to_keypair()?; // Get the keypair once
     let sender_pubkey = sender_keypair.pubkey();
-    let recipient_pubkey = Pubkey::new_from_array(recipient.data);
-    let mint_pubkey = mint.to_pubkey(); // Assuming to_pubkey() exists
 
     // Step 1 & 2: Get or create recipient's associated token account & derive sender's ATA
-    let recipient_assoc = match _get_or_create_associated_token_account(
+    let recipient_assoc = _get_or_create_associated_token_account(
         client,
-        sender, // Pass the original SolKeyPair pointer
-        &recipient_pubkey,
-        &mint_pubkey,
-    ) {
-        Ok(assoc) => assoc,
-        Err(err) => {
-            eprintln!("Error managing recipient's associated token account: {}", err);
-            return std::ptr::null_mut(); // Return null on error
-        }
-    };
+        sender,
+        recipient_pubkey,
+        mint_pubkey,
+        &options.budget,
+    )?;
     let sender_assoc =
-        spl_associated_token_account::get_associated_token_address(&sender_pubkey, &mint_pubkey);
+        spl_associated_token_account::get_associated_token_address(&sender_pubkey, mint_pubkey);
 
     // Step 3: Create the transfer instruction
-    let transfer_instruction = match spl_token::instruction::transfer(
+    let transfer_instruction = spl_token::instruction::transfer(
         &spl_token::id(),
         &sender_assoc,
         &recipient_assoc,
         &sender_pubkey, // Authority is the owner of the sender wallet
         &[&sender_pubkey], // Signer is the owner as well
         amount,
-    ) {
-        Ok(instruction) => instruction,
-        Err(err) => {
-            eprintln!("Error creating transfer instruction: {:?}", err);
-            return std::ptr::null_mut(); // Return null on error
-        }
-    };
+    )?;
 
-    // Step 4: Fetch the recent blockhash
-    let recent_blockhash = match client.rpc_client.get_latest_blockhash() {
-        Ok(blockhash) => blockhash,
-        Err(err) => {
-            eprintln!("Error fetching latest blockhash: {:?}", err);
-            return std::ptr::null_mut(); // Return null on error
-        }
-    };
-
-    // Step 5: Create and sign the transaction
-    let transaction = Transaction::new_signed_with_payer(
+    // Step 4 & 5: Create and sign the transaction over the recent blockhash
+    let tables = client.fetch_lookup_tables(&options.lookup_tables)?;
+    let transaction = client.sign_with_budget(
         &[transfer_instruction],
-        Some(&sender_pubkey),    // Fee payer
+        &sender_pubkey,          // Fee payer
         &[&sender_keypair],      // Pass the actual Keypair for signing
-        recent_blockhash,
+        &options.budget,
+        &tables,
+    )?;
+
+    // Step 6: Send and confirm the transaction
+    let (signature, slot) = client.send_budgeted(&transaction, &options.budget)?;
+    log::info!(
+        "Successfully transferred {} tokens from {} to {}. Signature: {}",
+        amount, sender_assoc, recipient_assoc, signature
     );
-
-    // Step 6: Send and confirm the transaction (Using send_and_confirm for simplicity)
-    match client.rpc_client.send_and_confirm_transaction_with_spinner(&transaction) {
-        Ok(signature) => {
-            println!(
-                "Successfully transferred {} tokens from {} to {}. Signature: {}",
-                amount, sender_assoc, recipient_assoc, signature
-            );
-            // Convert signature to CString and return pointer
-            match CString::new(signature.to_string()) {
-                Ok(c_string) => c_string.into_raw(), // Transfer ownership to C
-                Err(e) => {
-                    eprintln!("Error converting signature to CString: {}", e);
-                    std::ptr::null_mut() // Return null if conversion fails
-                }
-            }
-        }
-        Err(err) => {
-            eprintln!("Error sending and confirming transaction: {:?}", err);
-            std::ptr::null_mut() // Return null on error
-        }
-    }
+    Ok((signature, slot))
 }
 
 // Function to free the memory allocated for the transaction signature string
 #[no_mangle]
 pub extern "C" fn free_transaction_signature(signature: *mut c_char) {
-    if !signature.is_null() {
-        unsafe {
-            // Takes ownership back from C and frees the memory when `_cstring` goes out of scope
-            let _cstring = CString::from_raw(signature);
+    ffi_guard((), || {
+        if !signature.is_null() {
+            unsafe {
+                // Takes ownership back from C and frees the memory when `_cstring` goes out of scope
+                let _cstring = CString::from_raw(signature);
+            }
         }
-    }
+    })
 }
 
 #[no_mangle]
@@ -458,85 +570,47 @@ pub extern "C" fn create_spl_token(
     payer: *mut SolKeyPair,
     mint: *mut SolKeyPair,
 ) -> bool {
-    // Safety: Ensure the client pointer is not null
-    let client = unsafe {
-        assert!(!client.is_null());
-        &*client
-    };
-
-    let payer = unsafe {
-        assert!(!payer.is_null());
-        &*payer
-    };
-
-    let mint = unsafe {
-        assert!(!mint.is_null());
-        &*mint
-    };
-
-    let minimum_balance_for_rent_exemption = match client
-        .rpc_client
-        .get_minimum_balance_for_rent_exemption(Mint::LEN)
-    {
-        Ok(balance) => balance,
-        Err(err) => {
-            eprintln!(
-                "Error getting minimum balance for rent exemption: {:?}",
-                err
-            );
-            return false;
-        }
-    };
-
-    let create_account_instruction: Instruction = solana_sdk::system_instruction::create_account(
-        &&payer.to_keypair().pubkey(),
-        &mint.to_keypair().pubkey(),
-        minimum_balance_for_rent_exemption,
-        Mint::LEN as u64,
-        &spl_token::ID,
-    );
-
-    // Create the mint instruction
-    let mint_instruction = spl_token::instruction::initialize_mint(
-        &spl_token::id(),
-        &mint.to_keypair().pubkey(),
-        &mint.to_keypair().pubkey(),
-        None,
-        9, // Decimals
-    );
-
-    let mint_instruction = match mint_instruction {
-        Ok(instruction) => instruction,
-        Err(err) => {
-            eprintln!("Error creating mint instruction: {:?}", err);
-            return false;
-        }
-    };
-
-    let recent_blockhash = match client.rpc_client.get_latest_blockhash() {
-        Ok(blockhash) => blockhash,
-        Err(err) => {
-            eprintln!("Error getting latest blockhash: {:?}", err);
-            return false;
-        }
-    };
-
-    // Create and sign the transaction
-    let mut transaction = Transaction::new_signed_with_payer(
-        &[create_account_instruction, mint_instruction],
-        Some(&payer.to_keypair().pubkey()),
-        &[&mint.to_keypair(), &payer.to_keypair()],
-        recent_blockhash,
-    );
-
-    // Send the transaction
-    match client.rpc_client.send_transaction(&transaction) {
-        Ok(_) => true,
-        Err(err) => {
-            eprintln!("Error sending and confirming transaction: {:?}", err);
-            return false;
-        }
-    }
+    ffi_call(false, || {
+        // Safety: Ensure the client pointer is not null
+        let client = unsafe { ref_arg(client, "client")? };
+        let payer = unsafe { ref_arg(payer, "payer")? };
+        let mint = unsafe { ref_arg(mint, "mint")? };
+        let payer_keypair = payer.to_keypair()?;
+        let mint_keypair = mint.to_keypair()?;
+
+        let minimum_balance_for_rent_exemption =
+            client.rpc(|rpc| rpc.get_minimum_balance_for_rent_exemption(Mint::LEN))?;
+
+        let create_account_instruction: Instruction = solana_sdk::system_instruction::create_account(
+            &payer_keypair.pubkey(),
+            &mint_keypair.pubkey(),
+            minimum_balance_for_rent_exemption,
+            Mint::LEN as u64,
+            &spl_token::ID,
+        );
+
+        // Create the mint instruction
+        let mint_instruction = spl_token::instruction::initialize_mint(
+            &spl_token::id(),
+            &mint_keypair.pubkey(),
+            &mint_keypair.pubkey(),
+            None,
+            9, // Decimals
+        )?;
+
+        // Create and sign the transaction under the client's compute budget
+        let transaction = client.sign_with_budget(
+            &[create_account_instruction, mint_instruction],
+            &payer_keypair.pubkey(),
+            &[&mint_keypair, &payer_keypair],
+            &client.config.compute_budget,
+            &[],
+        )?;
+
+        // Send the transaction and wait for the mint to exist
+        client.send_budgeted(&transaction, &client.config.compute_budget)?;
+        Ok(true)
+    })
 }
 
 #[no_mangle]
@@ -544,47 +618,38 @@ pub extern "C" fn get_mint_info(
     client: *mut SolClient,
     mint_pubkey: *mut SolPublicKey,
 ) -> *mut SolMint {
-    // Safety: Ensure the client pointer is not null
-    let client = unsafe {
-        assert!(!client.is_null());
-        &*client
-    };
-
-    let mint = unsafe {
-        assert!(!mint_pubkey.is_null());
-        &*mint_pubkey
-    };
-
-    let mint_pubkey = mint.to_pubkey();
-    let mint_info = match client.rpc_client.get_account_data(&mint_pubkey) {
-        Ok(data) => data,
-        Err(_) => return std::ptr::null_mut(),
-    };
-
-    let mint_info = match Mint::unpack(&mint_info) {
-        Ok(mint_info) => mint_info,
-        Err(_) => return std::ptr::null_mut(),
-    };
-
-    let mint_authority = SolPublicKey {
-        data: mint_info
-            .mint_authority
-            .map_or([0u8; 32], |pubkey| pubkey.to_bytes()),
-    };
-
-    let freeze_authority = SolPublicKey {
-        data: mint_info.freeze_authority.unwrap_or_default().to_bytes(),
-    };
-
-    let sol_mint = SolMint {
-        mint_authority: Box::into_raw(Box::new(mint_authority)),
-        supply: mint_info.supply,
-        decimals: mint_info.decimals,
-        is_initialized: mint_info.is_initialized,
-        freeze_authority: Box::into_raw(Box::new(freeze_authority)),
-    };
-
-    Box::into_raw(Box::new(sol_mint))
+    ffi_call(std::ptr::null_mut(), || {
+        // Safety: Ensure the client pointer is not null
+        let client = unsafe { ref_arg(client, "client")? };
+        let mint = unsafe { ref_arg(mint_pubkey, "mint_pubkey")? };
+
+        let mint_pubkey = mint.to_pubkey();
+        let mint_info = client.rpc(|rpc| rpc.get_account_data(&mint_pubkey))?;
+
+        let mint_info = Mint::unpack(&mint_info).map_err(|err| {
+            SolError::InvalidInput(format!("account {} is not an SPL mint: {}", mint_pubkey, err))
+        })?;
+
+        let mint_authority = SolPublicKey {
+            data: mint_info
+                .mint_authority
+                .map_or([0u8; 32], |pubkey| pubkey.to_bytes()),
+        };
+
+        let freeze_authority = SolPublicKey {
+            data: mint_info.freeze_authority.unwrap_or_default().to_bytes(),
+        };
+
+        let sol_mint = SolMint {
+            mint_authority: Box::into_raw(Box::new(mint_authority)),
+            supply: mint_info.supply,
+            decimals: mint_info.decimals,
+            is_initialized: mint_info.is_initialized,
+            freeze_authority: Box::into_raw(Box::new(freeze_authority)),
+        };
+
+        Ok(Box::into_raw(Box::new(sol_mint)))
+    })
 }
 
 #[no_mangle]
@@ -594,38 +659,28 @@ pub extern "C" fn get_or_create_associated_token_account(
     owner: *mut SolPublicKey,
     mint: *mut SolKeyPair,
 ) -> *mut SolPublicKey {
-    // Safety: Ensure pointers are not null
-    let client = unsafe {
-        assert!(!client.is_null());
-        &*client
-    };
-    let payer = unsafe {
-        assert!(!payer.is_null());
-        &*payer
-    };
-    let owner = unsafe {
-        assert!(!owner.is_null());
-        &*owner
-    };
-    let mint = unsafe {
-        assert!(!mint.is_null());
-        &*mint
-    };
-
-    // Extract public keys
-    let owner_pubkey = Pubkey::new_from_array(owner.data);
-    let mint_pubkey = mint.to_keypair().pubkey();
-
-    // Call the helper function to get or create the associated token account
-    match _get_or_create_associated_token_account(client, payer, &owner_pubkey, &mint_pubkey) {
-        Ok(assoc) => Box::into_raw(Box::new(SolPublicKey {
-            data: assoc.to_bytes(),
-        })),
-        Err(err) => {
-            eprintln!("Error managing associated token account: {}", err);
-            std::ptr::null_mut()
-        }
-    }
+    ffi_call(std::ptr::null_mut(), || {
+        // Safety: Ensure pointers are not null
+        let client = unsafe { ref_arg(client, "client")? };
+        let payer = unsafe { ref_arg(payer, "payer")? };
+        let owner = unsafe { ref_arg(owner, "owner")? };
+        let mint = unsafe { ref_arg(mint, "mint")? };
+
+        // Extract public keys
+        let owner_pubkey = Pubkey::new_from_array(owner.data);
+        let mint_pubkey = mint.to_keypair()?.pubkey();
+
+        // Call the helper function to get or create the associated token account
+        let budget = &client.config.compute_budget;
+        let assoc = _get_or_create_associated_token_account(
+            client,
+            payer,
+            &owner_pubkey,
+            &mint_pubkey,
+            budget,
+        )?;
+        Ok(Box::into_raw(Box::new(SolPublicKey::new(assoc))))
+    })
 }
 
 pub fn _get_or_create_associated_token_account(
@@ -633,59 +688,44 @@ pub fn _get_or_create_associated_token_account(
     payer: &SolKeyPair,
     recipient_pubkey: &Pubkey,
     mint_pubkey: &Pubkey,
-) -> Result<Pubkey, String> {
+    budget: &SolComputeBudget,
+) -> Result<Pubkey, SolError> {
+    let payer_keypair = payer.to_keypair()?;
     let assoc =
         spl_associated_token_account::get_associated_token_address(recipient_pubkey, mint_pubkey);
 
-    match client.rpc_client.get_account(&assoc) {
-        Ok(account) => {
+    // A failed lookup is an error, not a missing account
+    match client.fetch_account(&assoc)? {
+        Some(_) => {
             // Associated token account exists
-            println!("Associated token account already exists at: {}", assoc);
+            log::debug!("Associated token account already exists at: {}", assoc);
             Ok(assoc)
         }
-        Err(ref err)
-            if matches!(
-                err.kind(),
-                solana_client::client_error::ClientErrorKind::RpcError(_)
-            ) =>
-        {
+        None => {
             // Create the associated token account
-            println!("Associated token account does not exist. Proceeding to create...");
+            log::debug!("Associated token account does not exist. Proceeding to create...");
             let assoc_instruction =
                 spl_associated_token_account::instruction::create_associated_token_account(
-                    &payer.to_keypair().pubkey(),
+                    &payer_keypair.pubkey(),
                     recipient_pubkey,
                     mint_pubkey,
                     &spl_token::id(),
                 );
 
-            let recent_blockhash = client
-                .rpc_client
-                .get_latest_blockhash()
-                .map_err(|err| format!("Error fetching latest blockhash: {:?}", err))?;
-
-            let mut assoc_transaction = Transaction::new_signed_with_payer(
+            let assoc_transaction = client.sign_with_budget(
                 &[assoc_instruction],
-                Some(&payer.to_keypair().pubkey()),
-                &[&payer.to_keypair()],
-                recent_blockhash,
-            );
+                &payer_keypair.pubkey(),
+                &[&payer_keypair],
+                budget,
+                &[],
+            )?;
 
-            client
-                .rpc_client
-                .send_transaction(&assoc_transaction)
-                .map_err(|err| format!("Error creating associated token account: {:?}", err))?;
+            // Confirmed before anything that uses the account is sent
+            client.send_budgeted(&assoc_transaction, budget)?;
 
-            println!(
-                "Associated token account created successfully at: {}",
-                assoc
-            );
+            log::info!("Associated token account created at: {}", assoc);
             Ok(assoc)
         }
-        Err(err) => Err(format!(
-            "Unexpected error checking associated token account: {:?}",
-            err
-        )),
     }
 }
 
@@ -697,85 +737,148 @@ pub extern "C" fn mint_spl(
     recipient: *mut SolPublicKey,
     amount: u64,
 ) -> bool {
-    // Safety: Ensure pointers are not null
-    let client = unsafe {
-        assert!(!client.is_null());
-        &*client
-    };
-    let payer = unsafe {
-        assert!(!payer.is_null());
-        &*payer
-    };
-    let mint_authority = unsafe {
-        assert!(!mint_authority.is_null());
-        &*mint_authority
-    };
-    let recipient = unsafe {
-        assert!(!recipient.is_null());
-        &*recipient
-    };
+    ffi_call(false, || {
+        // Safety: Ensure pointers are not null
+        let client = unsafe { ref_arg(client, "client")? };
+        let payer = unsafe { ref_arg(payer, "payer")? };
+        let mint_authority = unsafe { ref_arg(mint_authority, "mint_authority")? }.to_keypair()?;
+        let recipient = unsafe { ref_arg(recipient, "recipient")? };
+
+        let options = SendOptions::of(client);
+        send_mint(client, payer, &mint_authority, &recipient.to_pubkey(), amount, &options)?;
+        Ok(true)
+    })
+}
+
+// Same as `mint_spl`, with per-call `options` (NULL = the client's defaults).
+// Their budget also applies to the creation of the recipient's token account.
+#[no_mangle]
+pub extern "C" fn mint_spl_with_options(
+    client: *mut SolClient,
+    payer: *mut SolKeyPair,
+    mint_authority: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    amount: u64,
+    options: *const SolSendOptions,
+) -> bool {
+    let slot = ffi_call(None, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let payer = unsafe { ref_arg(payer, "payer")? };
+        let mint_authority = unsafe { ref_arg(mint_authority, "mint_authority")? }.to_keypair()?;
+        let recipient = unsafe { ref_arg(recipient, "recipient")? };
+        let options = unsafe { SendOptions::from_arg(client, options)? };
+
+        let (_, slot) =
+            send_mint(client, payer, &mint_authority, &recipient.to_pubkey(), amount, &options)?;
+        Ok(Some(slot))
+    });
+    write_outcome(unsafe { outcome_arg(options) }, slot);
+    slot.is_some()
+}
+
+#[no_mangle]
+pub extern "C" fn mint_spl_async(
+    client: *mut SolClient,
+    payer: *mut SolKeyPair,
+    mint_authority: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    amount: u64,
+    options: *const SolSendOptions,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let task = unsafe { mint_spl_task(client, payer, mint_authority, recipient, amount, options)? };
+        spawn_job(client, None, task)
+    })
+}
 
-    let mint_authority_pubkey = mint_authority.to_keypair().pubkey();
-    let recipient_pubkey = Pubkey::new_from_array(recipient.data);
+#[no_mangle]
+pub extern "C" fn mint_spl_with_callback(
+    client: *mut SolClient,
+    payer: *mut SolKeyPair,
+    mint_authority: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    amount: u64,
+    options: *const SolSendOptions,
+    callback: SolCompletionCallback,
+    user_data: *mut c_void,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let completion = Completion::new(callback, user_data)?;
+        let task = unsafe { mint_spl_task(client, payer, mint_authority, recipient, amount, options)? };
+        spawn_job(client, Some(completion), task)
+    })
+}
+
+// Copy the arguments of `mint_spl_with_options` into a job.
+// Safety: non-null pointers must be valid for the duration of the call.
+unsafe fn mint_spl_task(
+    client: &SolClient,
+    payer: *mut SolKeyPair,
+    mint_authority: *mut SolKeyPair,
+    recipient: *mut SolPublicKey,
+    amount: u64,
+    options: *const SolSendOptions,
+) -> Result<impl FnOnce(&SolClient) -> Result<JobOutput, SolError>, SolError> {
+    let payer = SolKeyPair::from_existing(ref_arg(payer, "payer")?.to_keypair()?);
+    let mint_authority = ref_arg(mint_authority, "mint_authority")?.to_keypair()?;
+    let recipient = ref_arg(recipient, "recipient")?.to_pubkey();
+    let options = SendOptions::from_arg(client, options)?;
+
+    Ok(move |client: &SolClient| {
+        let (signature, _) =
+            send_mint(client, &payer, &mint_authority, &recipient, amount, &options)?;
+        Ok(JobOutput::Signature(signature))
+    })
+}
+
+// Mint `amount` tokens to the associated token account of `recipient_pubkey`,
+// creating it first if needed. Returns the signature and the slot the mint was confirmed in.
+fn send_mint(
+    client: &SolClient,
+    payer: &SolKeyPair,
+    mint_authority_keypair: &Keypair,
+    recipient_pubkey: &Pubkey,
+    amount: u64,
+    options: &SendOptions,
+) -> Result<(Signature, u64), SolError> {
+    let payer_keypair = payer.to_keypair()?;
+    let mint_authority_pubkey = mint_authority_keypair.pubkey();
 
     // Get or create associated token account
-    let assoc = match _get_or_create_associated_token_account(
+    let assoc = _get_or_create_associated_token_account(
         client,
         payer,
-        &recipient_pubkey,
+        recipient_pubkey,
         &mint_authority_pubkey,
-    ) {
-        Ok(assoc) => assoc,
-        Err(err) => {
-            eprintln!("Error managing associated token account: {}", err);
-            return false;
-        }
-    };
+        &options.budget,
+    )?;
 
     // Step 3: Create the mint_to instruction
-    let mint_instruction = match spl_token::instruction::mint_to(
+    let mint_instruction = spl_token::instruction::mint_to(
         &spl_token::id(),
         &mint_authority_pubkey,
         &assoc,
-        &mint_authority.to_keypair().pubkey(),
-        &[&mint_authority.to_keypair().pubkey()],
+        &mint_authority_pubkey,
+        &[&mint_authority_pubkey],
         amount,
-    ) {
-        Ok(instruction) => instruction,
-        Err(err) => {
-            eprintln!("Error creating mint instruction: {:?}", err);
-            return false;
-        }
-    };
-
-    // Step 4: Fetch the recent blockhash
-    let recent_blockhash = match client.rpc_client.get_latest_blockhash() {
-        Ok(blockhash) => blockhash,
-        Err(err) => {
-            eprintln!("Error fetching latest blockhash: {:?}", err);
-            return false;
-        }
-    };
+    )?;
 
-    // Step 5: Create and sign the mint transaction
-    let mut transaction = Transaction::new_signed_with_payer(
+    // Step 4 & 5: Create and sign the mint transaction over the recent blockhash
+    let tables = client.fetch_lookup_tables(&options.lookup_tables)?;
+    let transaction = client.sign_with_budget(
         &[mint_instruction],
-        Some(&payer.to_keypair().pubkey()), // Fee payer
-        &[&mint_authority.to_keypair(), &payer.to_keypair()], // Required signers
-        recent_blockhash,
-    );
+        &payer_keypair.pubkey(), // Fee payer
+        &[mint_authority_keypair, &payer_keypair], // Required signers
+        &options.budget,
+        &tables,
+    )?;
 
     // Step 6: Send and confirm the mint transaction
-    match client.rpc_client.send_transaction(&transaction) {
-        Ok(_) => {
-            println!("Successfully minted {} tokens to {}", amount, assoc);
-            true
-        }
-        Err(err) => {
-            eprintln!("Error minting tokens: {:?}", err);
-            false
-        }
-    }
+    let (signature, slot) = client.send_budgeted(&transaction, &options.budget)?;
+    log::info!("Successfully minted {} tokens to {}", amount, assoc);
+    Ok((signature, slot))
 }
 
 #[no_mangle]
@@ -784,41 +887,52 @@ pub extern "C" fn get_associated_token_balance(
     owner: *mut SolPublicKey,
     mint: *mut SolPublicKey,
 ) -> u64 {
-    // Safety: Ensure the client pointer is not null
-    let client = unsafe {
-        assert!(!client.is_null());
-        &*client
-    };
-
-    let owner = unsafe {
-        assert!(!owner.is_null());
-        &*owner
-    };
+    ffi_call(0, || {
+        // Safety: Ensure the client pointer is not null
+        let client = unsafe { ref_arg(client, "client")? };
+        let owner = unsafe { ref_arg(owner, "owner")? };
+        let mint = unsafe { ref_arg(mint, "mint")? };
+
+        fetch_associated_token_balance(client, owner, mint, client.rpc_client.commitment())
+    })
+}
 
-    let mint = unsafe {
-        assert!(!mint.is_null());
-        &*mint
-    };
+// Same as `get_associated_token_balance`, but reads at the given commitment instead of the client's
+#[no_mangle]
+pub extern "C" fn get_associated_token_balance_with_commitment(
+    client: *mut SolClient,
+    owner: *mut SolPublicKey,
+    mint: *mut SolPublicKey,
+    commitment: u32,
+) -> u64 {
+    ffi_call(0, || {
+        let client = unsafe { ref_arg(client, "client")? };
+        let owner = unsafe { ref_arg(owner, "owner")? };
+        let mint = unsafe { ref_arg(mint, "mint")? };
+
+        let commitment = SolCommitment::try_from(commitment)?.to_commitment_config();
+        fetch_associated_token_balance(client, owner, mint, commitment)
+    })
+}
 
+fn fetch_associated_token_balance(
+    client: &SolClient,
+    owner: &SolPublicKey,
+    mint: &SolPublicKey,
+    commitment: CommitmentConfig,
+) -> Result<u64, SolError> {
     let owner_pubkey = owner.to_pubkey();
     let mint_pubkey = mint.to_pubkey();
 
     let assoc =
         spl_associated_token_account::get_associated_token_address(&owner_pubkey, &mint_pubkey);
 
-    let balance = match client.rpc_client.get_token_account_balance(&assoc) {
-        Ok(balance) => balance,
-        Err(err) => {
-            eprintln!("Error getting token account balance: {:?}", err);
-            return 0;
-        }
-    };
+    let balance = client
+        .rpc(|rpc| rpc.get_token_account_balance_with_commitment(&assoc, commitment))?
+        .value;
 
-    match balance.amount.parse::<u64>() {
-        Ok(amount) => amount,
-        Err(err) => {
-            eprintln!("Error parsing token account balance: {:?}", err);
-            0
-        }
-    }
+    balance.amount.parse::<u64>().map_err(|err| SolError::RpcServer {
+        code: 0,
+        message: format!("invalid token amount '{}': {}", balance.amount, err),
+    })
 }
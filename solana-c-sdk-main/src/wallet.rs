@@ -1,10 +1,11 @@
-use serde_json;
 use solana_program::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
-use std::ffi::CStr;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Write};
 use std::os::raw::c_char;
+
+use crate::error::{ffi_call, ffi_guard, ref_arg, str_arg, SolError};
+
 #[repr(C)]
 pub struct SolKeyPair {
     pub bytes: [u8; 64], // Store the keypair as raw bytes
@@ -37,8 +38,9 @@ impl SolKeyPair {
     }
 
     // Convert raw bytes back to a Keypair
-    pub fn to_keypair(&self) -> Keypair {
-        Keypair::from_bytes(&self.bytes).expect("Failed to convert bytes to Keypair")
+    pub fn to_keypair(&self) -> Result<Keypair, SolError> {
+        Keypair::from_bytes(&self.bytes)
+            .map_err(|err| SolError::InvalidInput(format!("invalid keypair bytes: {}", err)))
     }
 
     // Return the public key as a Pubkey
@@ -71,73 +73,69 @@ pub struct SolSecretKey {
 
 #[no_mangle]
 pub extern "C" fn get_public_key(wallet: *mut SolKeyPair) -> *mut SolPublicKey {
-    let wallet = unsafe {
-        assert!(!wallet.is_null());
-        &mut *wallet
-    };
+    ffi_call(std::ptr::null_mut(), || {
+        let wallet = unsafe { ref_arg(wallet, "wallet")? };
 
-    let keypair = wallet.to_keypair();
-    let public_key = keypair.pubkey();
-    let public_key = SolPublicKey {
-        data: public_key.to_bytes(),
-    };
-    Box::into_raw(Box::new(public_key))
+        let keypair = wallet.to_keypair()?;
+        let public_key = keypair.pubkey();
+        let public_key = SolPublicKey {
+            data: public_key.to_bytes(),
+        };
+        Ok(Box::into_raw(Box::new(public_key)))
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn get_secret_key(wallet: *mut SolKeyPair) -> *mut SolSecretKey {
-    let wallet = unsafe {
-        assert!(!wallet.is_null());
-        &mut *wallet
-    };
+    ffi_call(std::ptr::null_mut(), || {
+        let wallet = unsafe { ref_arg(wallet, "wallet")? };
 
-    let secret_key = wallet.to_keypair().to_bytes();
-    let secret_key = SolSecretKey { data: secret_key };
-    Box::into_raw(Box::new(secret_key))
+        let secret_key = wallet.to_keypair()?.to_bytes();
+        let secret_key = SolSecretKey { data: secret_key };
+        Ok(Box::into_raw(Box::new(secret_key)))
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn get_wallet_address(wallet: *mut SolKeyPair) -> *mut c_char {
-    let wallet = unsafe {
-        assert!(!wallet.is_null());
-        &mut *wallet
-    };
+    ffi_call(std::ptr::null_mut(), || {
+        let wallet = unsafe { ref_arg(wallet, "wallet")? };
 
-    let public_key = wallet.to_keypair().pubkey();
-    let address = public_key.to_string();
-    let c_str = std::ffi::CString::new(address).unwrap();
-    c_str.into_raw()
+        let public_key = wallet.to_keypair()?.pubkey();
+        let address = public_key.to_string();
+        let c_str = std::ffi::CString::new(address)?;
+        Ok(c_str.into_raw())
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn create_wallet() -> *mut SolKeyPair {
-    let keypair = Keypair::new();
-    let wallet = Box::new(SolKeyPair::from_existing(keypair));
+    ffi_guard(std::ptr::null_mut(), || {
+        let keypair = Keypair::new();
+        let wallet = Box::new(SolKeyPair::from_existing(keypair));
 
-    Box::into_raw(wallet)
+        Box::into_raw(wallet)
+    })
 }
 
 // Generate and save a Solana wallet, returning the public key as a C string
 #[no_mangle]
 pub extern "C" fn create_and_save_wallet(file_path: *const c_char) -> *mut SolKeyPair {
-    // Convert the C string to a Rust string
-    let c_str = unsafe { CStr::from_ptr(file_path) };
-    let file_path_str = match c_str.to_str() {
-        Ok(str) => str,
-        Err(_) => return std::ptr::null_mut(),
-    };
-
-    // Create a new keypair (Solana wallet)
-    let keypair = Keypair::new();
-
-    // Save the private key in Solana CLI format (JSON array)
-    match save_wallet_to_file(&keypair, file_path_str) {
-        Ok(_) => {
-            let wallet = Box::new(SolKeyPair::from_existing(keypair));
-            Box::into_raw(wallet)
-        }
-        Err(_) => std::ptr::null_mut(),
-    }
+    ffi_call(std::ptr::null_mut(), || {
+        // Convert the C string to a Rust string
+        let file_path_str = unsafe { str_arg(file_path, "file path")? };
+
+        // Create a new keypair (Solana wallet)
+        let keypair = Keypair::new();
+
+        // Save the private key in Solana CLI format (JSON array)
+        save_wallet_to_file(&keypair, file_path_str).map_err(|err| {
+            SolError::InvalidInput(format!("failed to save wallet to {}: {}", file_path_str, err))
+        })?;
+
+        let wallet = Box::new(SolKeyPair::from_existing(keypair));
+        Ok(Box::into_raw(wallet))
+    })
 }
 
 // Save the wallet's private key to a file in Solana CLI format
@@ -157,28 +155,27 @@ fn save_wallet_to_file(keypair: &Keypair, file_path: &str) -> std::io::Result<()
 
 #[no_mangle]
 pub extern "C" fn new_keypair() -> *mut SolKeyPair {
-    let keypair = Keypair::new();
-    Box::into_raw(Box::new(SolKeyPair::from_existing(keypair)))
+    ffi_guard(std::ptr::null_mut(), || Box::into_raw(Box::new(SolKeyPair::new())))
 }
 
 // Load a Solana wallet from the file, returning the public key as a C string
 #[no_mangle]
 pub extern "C" fn load_wallet_from_file(file_path: *const c_char) -> *mut SolKeyPair {
-    // Convert the C string to a Rust string
-    let c_str = unsafe { CStr::from_ptr(file_path) };
-    let file_path_str = match c_str.to_str() {
-        Ok(str) => str,
-        Err(_) => return std::ptr::null_mut(),
-    };
-
-    // Load the private key from the file in Solana CLI format
-    match load_wallet(file_path_str) {
-        Ok(keypair) => {
-            let wallet = Box::new(SolKeyPair::from_existing(keypair));
-            Box::into_raw(wallet)
-        }
-        Err(_) => std::ptr::null_mut(),
-    }
+    ffi_call(std::ptr::null_mut(), || {
+        // Convert the C string to a Rust string
+        let file_path_str = unsafe { str_arg(file_path, "file path")? };
+
+        // Load the private key from the file in Solana CLI format
+        let keypair = load_wallet(file_path_str).map_err(|err| {
+            SolError::InvalidInput(format!(
+                "failed to load wallet from {}: {}",
+                file_path_str, err
+            ))
+        })?;
+
+        let wallet = Box::new(SolKeyPair::from_existing(keypair));
+        Ok(Box::into_raw(wallet))
+    })
 }
 
 // Load the wallet's private key from the file in Solana CLI format
@@ -210,41 +207,29 @@ fn load_wallet(file_path: &str) -> Result<Keypair, std::io::Error> {
 
 #[no_mangle]
 pub extern "C" fn get_pubkey_from_address(address: *const c_char) -> *mut SolPublicKey {
-    let c_str = unsafe {
-        assert!(!address.is_null());
-        CStr::from_ptr(address)
-    };
-
-    let address_str = match c_str.to_str() {
-        Ok(str) => str,
-        Err(_) => return std::ptr::null_mut(),
-    };
-
-    match Pubkey::try_from(address_str) {
-        Ok(pubkey) => {
-            let public_key = SolPublicKey {
-                data: pubkey.to_bytes(),
-            };
-            Box::into_raw(Box::new(public_key))
-        }
-        Err(_) => std::ptr::null_mut(),
-    }
+    ffi_call(std::ptr::null_mut(), || {
+        let address_str = unsafe { str_arg(address, "address")? };
+
+        let pubkey = Pubkey::try_from(address_str).map_err(|err| {
+            SolError::InvalidInput(format!("invalid address '{}': {}", address_str, err))
+        })?;
+
+        let public_key = SolPublicKey {
+            data: pubkey.to_bytes(),
+        };
+        Ok(Box::into_raw(Box::new(public_key)))
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn get_address_from_pubkey(pubkey: *const SolPublicKey) -> *mut c_char {
-    let pubkey = unsafe {
-        assert!(!pubkey.is_null());
-        &*pubkey
-    };
-
-    let pubkey_array = Pubkey::new_from_array(pubkey.data);
-    let address = pubkey_array.to_string();
+    ffi_call(std::ptr::null_mut(), || {
+        let pubkey = unsafe { ref_arg(pubkey, "pubkey")? };
 
-    let c_str = match std::ffi::CString::new(address) {
-        Ok(cstring) => cstring,
-        Err(_) => return std::ptr::null_mut(),
-    };
+        let pubkey_array = Pubkey::new_from_array(pubkey.data);
+        let address = pubkey_array.to_string();
 
-    c_str.into_raw()
+        let c_str = std::ffi::CString::new(address)?;
+        Ok(c_str.into_raw())
+    })
 }
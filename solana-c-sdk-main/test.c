@@ -2,7 +2,7 @@
 #include <stdlib.h>
 #include <sys/time.h>
 
-#include "header/anchor_counter_interface.c"
+#include "header/anchor_counter.h"
 
 const char *file_path = "wallet_keypair.json";
 const char *file_path_payer = "wallet_keypair.json";
@@ -361,30 +361,21 @@ void test_counter()
     SolKeyPair *payer = load_wallet_from_file(payer_path);
     SolKeyPair *account = new_keypair();
 
-    // Get system program ID
-    SolPublicKey SYSTEM_PROGRAM_ID = get_system_program_id();
-
-    // Prepare to initialize account
-    SolPublicKey initialize_accounts[3] = {
-        account->pubkey,
-        payer->pubkey,
-        SYSTEM_PROGRAM_ID};
-
     SolKeyPair *initialize_signers[2] = {payer, account};
 
-    // Call initialize
-    char *initialize_result = anchor_counter_initialize_c(
+    // Call initialize; the system program account comes from the IDL
+    char *initialize_result = anchor_counter_initialize(
         client,
         program_id,
-        initialize_accounts,
-        3,
+        &account->pubkey,
+        &payer->pubkey,
         initialize_signers,
         2);
 
     if (initialize_result != NULL)
     {
         printf("Initialize Result: %s\n", initialize_result);
-        free(initialize_result);
+        free_transaction_signature(initialize_result);
     }
     else
     {
@@ -392,47 +383,34 @@ void test_counter()
     }
 
     // Call increment method
-    SolPublicKey increment_accounts[2] = {
-        account->pubkey,
-        payer->pubkey};
-
     SolKeyPair *increment_signers[1] = {payer};
 
     for (int i = 0; i < 2; i++)
     {
-        char *increment_result = anchor_counter_increment_c(
+        char *increment_result = anchor_counter_increment(
             client,
             program_id,
-            increment_accounts,
-            2,
+            &account->pubkey,
+            &payer->pubkey,
             increment_signers,
             1);
 
         if (increment_result != NULL)
         {
             printf("Increment Result: %s\n", increment_result);
-            free(increment_result);
+            free_transaction_signature(increment_result);
         }
         else
         {
             printf("❌ Failed to increment account.\n");
         }
 
-        // Fetch and print the updated account value
-        uint8_t account_data[512]; // Larger buffer to handle future expansion
-        size_t data_offset = 8;
-        size_t bytes_copied = get_account_data_c(
-            client,
-            &account->pubkey,
-            account_data,
-            sizeof(account_data),
-            data_offset);
-
-        if (bytes_copied > 0)
+        // Fetch and decode the updated account
+        AnchorCounterCounter counter;
+        if (anchor_counter_fetch_counter(client, &account->pubkey, &counter))
         {
-            printf("✅ Data Copied: %zu bytes\n", bytes_copied);
-            uint64_t *counter = (uint64_t *)account_data;
-            printf("🔢 Counter Value: %lu\n", *counter);
+            printf("🔢 Counter Value: %lu\n", counter.count);
+            anchor_counter_free_counter(&counter);
         }
         else
         {
@@ -481,8 +459,16 @@ void test()
     // measure_time("Test Smart Contract", test_smart_contract);
 }
 
+// Print SDK logs; the SDK itself never writes to stdout
+static void print_sdk_log(SolLogLevel level, const char *target, const char *message, void *user_data)
+{
+    static const char *names[] = {"OFF", "ERROR", "WARN", "INFO", "DEBUG", "TRACE"};
+    printf("[%s %s] %s\n", names[level], target, message);
+}
+
 int main()
 {
+    sol_set_log_callback(SolLogLevel_Info, print_sdk_log, NULL);
     test();
     return 0;
 }